@@ -14,6 +14,51 @@ pub struct AudioDeviceInfo {
     pub is_default: bool,
 }
 
+/// Queryable capabilities of an audio device, used to validate that a
+/// requested capture format is actually supported before opening a stream
+/// instead of failing opaquely inside `build_input_stream`.
+#[derive(Debug, Clone)]
+pub struct DeviceProperties {
+    pub name: String,
+    /// The device's current/native sample rate, as reported by
+    /// `default_input_config`.
+    pub native_sample_rate: u32,
+    /// Lowest sample rate any supported input config on this device accepts.
+    pub min_sample_rate: u32,
+    /// Highest sample rate any supported input config on this device accepts.
+    pub max_sample_rate: u32,
+    /// Channel counts offered by any supported input config.
+    pub supported_channels: Vec<u16>,
+    /// Best-effort guess at whether this is a multi-device aggregate (e.g.
+    /// a macOS Aggregate Device or Multi-Output Device) -- cpal exposes no
+    /// direct API for this, so it's a name heuristic, not a guarantee.
+    pub is_aggregate: bool,
+}
+
+impl DeviceProperties {
+    /// Whether `rate` falls within `[min_sample_rate, max_sample_rate]`.
+    /// This is a coarse range check -- a device can have unsupported gaps
+    /// within that span that cpal's range API doesn't distinguish.
+    pub fn supports_sample_rate(&self, rate: u32) -> bool {
+        (self.min_sample_rate..=self.max_sample_rate).contains(&rate)
+    }
+
+    /// Whether any supported config offers exactly `channels` channels.
+    pub fn supports_channels(&self, channels: u16) -> bool {
+        self.supported_channels.contains(&channels)
+    }
+}
+
+/// Heuristic for `DeviceProperties.is_aggregate` -- cpal has no API for
+/// this, so fall back to matching the naming convention macOS uses for
+/// aggregate/multi-output devices. `pub(crate)` so callers that only have an
+/// `AudioDeviceInfo` (e.g. the tray's device list) can apply the same
+/// heuristic without a full `properties_for` round trip.
+pub(crate) fn is_aggregate_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.contains("aggregate") || lower.contains("multi-output")
+}
+
 impl Default for AudioDeviceManager {
     fn default() -> Self {
         Self::new()
@@ -62,12 +107,135 @@ impl AudioDeviceManager {
         self.host.input_devices().ok()?.nth(index)
     }
 
+    /// Get an input device by name, matching `Device::name()` exactly.
+    ///
+    /// Unlike index-based lookup, the name is stable across reboots and
+    /// device reconnections (the index shifts whenever devices are
+    /// plugged/unplugged).
+    pub fn input_device_by_name(&self, name: &str) -> Option<Device> {
+        self.host
+            .input_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+    }
+
+    /// Resolve the device persisted in config, falling back to the system
+    /// default if the saved device name is no longer available.
+    pub fn resolve_configured_device(&self) -> Option<Device> {
+        match crate::config::get_input_device() {
+            Some(name) => self.input_device_by_name(&name).or_else(|| {
+                eprintln!("saved input device '{name}' not found, falling back to default");
+                self.default_input_device()
+            }),
+            None => self.default_input_device(),
+        }
+    }
+
     /// Get the default input config for a device.
     pub fn default_input_config(
         device: &Device,
     ) -> Result<cpal::SupportedStreamConfig, cpal::DefaultStreamConfigError> {
         device.default_input_config()
     }
+
+    /// List all available output devices with metadata.
+    pub fn list_output_devices(&self) -> Vec<AudioDeviceInfo> {
+        let default_name = self.host.default_output_device().and_then(|d| d.name().ok());
+
+        let devices = match self.host.output_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                eprintln!("failed to enumerate output devices: {e}");
+                return Vec::new();
+            }
+        };
+
+        devices
+            .enumerate()
+            .filter_map(|(index, device)| {
+                let name = device.name().ok()?;
+                let is_default = default_name.as_deref() == Some(&name);
+                Some(AudioDeviceInfo {
+                    name,
+                    index,
+                    is_default,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the default output device.
+    pub fn default_output_device(&self) -> Option<Device> {
+        self.host.default_output_device()
+    }
+
+    /// Get an output device by name, matching `Device::name()` exactly.
+    pub fn output_device_by_name(&self, name: &str) -> Option<Device> {
+        self.host
+            .output_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+    }
+
+    /// Get the default output config for a device.
+    pub fn default_output_config(
+        device: &Device,
+    ) -> Result<cpal::SupportedStreamConfig, cpal::DefaultStreamConfigError> {
+        device.default_output_config()
+    }
+
+    /// Compare two `list_input_devices` snapshots for hotplug detection.
+    /// Ignores `index` (which shifts whenever any device is plugged or
+    /// unplugged) and compares only identity (`name`) and `is_default`, so a
+    /// device reordering with no actual add/remove doesn't count as a change.
+    pub fn devices_changed(previous: &[AudioDeviceInfo], current: &[AudioDeviceInfo]) -> bool {
+        let fingerprint = |devices: &[AudioDeviceInfo]| -> Vec<(String, bool)> {
+            devices.iter().map(|d| (d.name.clone(), d.is_default)).collect()
+        };
+        fingerprint(previous) != fingerprint(current)
+    }
+
+    /// Query capabilities (supported sample rates/channels, aggregate
+    /// guess) for the input device at `index`, as returned by
+    /// `list_input_devices`.
+    pub fn device_properties(&self, index: usize) -> Option<DeviceProperties> {
+        let device = self.input_device_by_index(index)?;
+        Self::properties_for(&device)
+    }
+
+    /// Build `DeviceProperties` from an already-resolved input device, for
+    /// callers (like `Recorder::start`) that resolved the device by name
+    /// rather than index.
+    pub(crate) fn properties_for(device: &Device) -> Option<DeviceProperties> {
+        let name = device.name().ok()?;
+        let native_sample_rate = device
+            .default_input_config()
+            .map(|c| c.sample_rate().0)
+            .unwrap_or(0);
+
+        let configs: Vec<_> = device.supported_input_configs().ok()?.collect();
+        if configs.is_empty() {
+            return None;
+        }
+
+        let min_sample_rate = configs.iter().map(|c| c.min_sample_rate().0).min()?;
+        let max_sample_rate = configs.iter().map(|c| c.max_sample_rate().0).max()?;
+
+        let mut supported_channels: Vec<u16> = configs.iter().map(|c| c.channels()).collect();
+        supported_channels.sort_unstable();
+        supported_channels.dedup();
+
+        let is_aggregate = is_aggregate_name(&name);
+
+        Some(DeviceProperties {
+            name,
+            native_sample_rate,
+            min_sample_rate,
+            max_sample_rate,
+            supported_channels,
+            is_aggregate,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -111,4 +279,146 @@ mod tests {
         let result = manager.input_device_by_index(99999);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_input_device_by_nonexistent_name() {
+        let manager = AudioDeviceManager::new();
+        let result = manager.input_device_by_name("Nonexistent Device XYZ");
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_input_device_by_name_matches_listed_device() {
+        let manager = AudioDeviceManager::new();
+        if let Some(first) = manager.list_input_devices().into_iter().next() {
+            let found = manager.input_device_by_name(&first.name);
+            assert!(found.is_some());
+        }
+    }
+
+    #[test]
+    fn test_resolve_configured_device_falls_back_without_panicking() {
+        let manager = AudioDeviceManager::new();
+        // Whatever is persisted in config (or nothing at all), this should
+        // never panic — it either resolves a device or falls back to default.
+        let _ = manager.resolve_configured_device();
+    }
+
+    #[test]
+    fn test_list_output_devices_default_flag() {
+        let manager = AudioDeviceManager::new();
+        let devices = manager.list_output_devices();
+        let default_count = devices.iter().filter(|d| d.is_default).count();
+        assert!(default_count <= 1);
+    }
+
+    #[test]
+    fn test_output_device_by_nonexistent_name() {
+        let manager = AudioDeviceManager::new();
+        let result = manager.output_device_by_name("Nonexistent Device XYZ");
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_output_device_by_name_matches_listed_device() {
+        let manager = AudioDeviceManager::new();
+        if let Some(first) = manager.list_output_devices().into_iter().next() {
+            let found = manager.output_device_by_name(&first.name);
+            assert!(found.is_some());
+        }
+    }
+
+    #[test]
+    fn test_device_properties_invalid_index_is_none() {
+        let manager = AudioDeviceManager::new();
+        assert!(manager.device_properties(99999).is_none());
+    }
+
+    #[test]
+    fn test_is_aggregate_name_heuristic() {
+        assert!(is_aggregate_name("Aggregate Device"));
+        assert!(is_aggregate_name("Built-in Multi-Output Device"));
+        assert!(!is_aggregate_name("USB Microphone"));
+    }
+
+    #[test]
+    fn test_supports_sample_rate_checks_range() {
+        let props = DeviceProperties {
+            name: "Test".to_string(),
+            native_sample_rate: 48000,
+            min_sample_rate: 44100,
+            max_sample_rate: 48000,
+            supported_channels: vec![1, 2],
+            is_aggregate: false,
+        };
+        assert!(props.supports_sample_rate(44100));
+        assert!(props.supports_sample_rate(48000));
+        assert!(!props.supports_sample_rate(96000));
+    }
+
+    #[test]
+    fn test_devices_changed_false_for_identical_snapshots() {
+        let snapshot = vec![AudioDeviceInfo {
+            name: "USB Mic".to_string(),
+            index: 0,
+            is_default: true,
+        }];
+        assert!(!AudioDeviceManager::devices_changed(&snapshot, &snapshot));
+    }
+
+    #[test]
+    fn test_devices_changed_ignores_index_only_reordering() {
+        let previous = vec![
+            AudioDeviceInfo { name: "A".to_string(), index: 0, is_default: true },
+            AudioDeviceInfo { name: "B".to_string(), index: 1, is_default: false },
+        ];
+        let current = vec![
+            AudioDeviceInfo { name: "A".to_string(), index: 1, is_default: true },
+            AudioDeviceInfo { name: "B".to_string(), index: 0, is_default: false },
+        ];
+        assert!(!AudioDeviceManager::devices_changed(&previous, &current));
+    }
+
+    #[test]
+    fn test_devices_changed_true_when_device_added() {
+        let previous = vec![AudioDeviceInfo {
+            name: "A".to_string(),
+            index: 0,
+            is_default: true,
+        }];
+        let current = vec![
+            AudioDeviceInfo { name: "A".to_string(), index: 0, is_default: true },
+            AudioDeviceInfo { name: "B".to_string(), index: 1, is_default: false },
+        ];
+        assert!(AudioDeviceManager::devices_changed(&previous, &current));
+    }
+
+    #[test]
+    fn test_devices_changed_true_when_default_flag_moves() {
+        let previous = vec![AudioDeviceInfo {
+            name: "A".to_string(),
+            index: 0,
+            is_default: true,
+        }];
+        let current = vec![AudioDeviceInfo {
+            name: "A".to_string(),
+            index: 0,
+            is_default: false,
+        }];
+        assert!(AudioDeviceManager::devices_changed(&previous, &current));
+    }
+
+    #[test]
+    fn test_supports_channels_checks_list() {
+        let props = DeviceProperties {
+            name: "Test".to_string(),
+            native_sample_rate: 48000,
+            min_sample_rate: 44100,
+            max_sample_rate: 48000,
+            supported_channels: vec![1, 2],
+            is_aggregate: false,
+        };
+        assert!(props.supports_channels(2));
+        assert!(!props.supports_channels(4));
+    }
 }
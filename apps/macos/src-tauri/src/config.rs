@@ -1,7 +1,12 @@
 //! File-backed configuration for Lyre.
 //!
-//! Stores `server_url` and `token` as a JSON file in the app's data directory
-//! (`~/Library/Application Support/com.lyre.app/config.json`).
+//! Stores `server_url` and `token` as a JSON file in the platform's standard
+//! per-app data directory, resolved via the `dirs` crate: `~/Library/Application
+//! Support/com.lyre.app/config.json` on macOS, `$XDG_DATA_HOME` (or
+//! `~/.local/share`)`/com.lyre.app/config.json` on Linux, and
+//! `%APPDATA%\com.lyre.app\config.json` on Windows. The location can be
+//! overridden with the `LYRE_CONFIG` environment variable, which is useful
+//! for tests, portable installs, and multi-profile setups.
 
 use std::fs;
 use std::path::PathBuf;
@@ -9,9 +14,29 @@ use std::path::PathBuf;
 const APP_DIR_NAME: &str = "com.lyre.app";
 const CONFIG_FILE: &str = "config.json";
 
+/// Environment variable that, when set, overrides the default config path.
+const CONFIG_ENV_VAR: &str = "LYRE_CONFIG";
+
+/// Current config schema version. Bump this and append a migration to
+/// `MIGRATIONS` whenever `AppConfig`'s on-disk layout changes.
+const CURRENT_CONFIG_VERSION: u32 = 1;
+
+fn current_config_version() -> u32 {
+    CURRENT_CONFIG_VERSION
+}
+
 /// Configuration for the Lyre app.
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+///
+/// `server_url` and `token` as returned by [`load_config`] reflect the
+/// effective, merged configuration: `LYRE_SERVER_URL`/`LYRE_TOKEN` env vars
+/// take precedence over the on-disk JSON file, which in turn takes
+/// precedence over the zero-value default. The struct itself always
+/// round-trips the raw file contents when serialized back to disk.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AppConfig {
+    /// Schema version, used to migrate older config files forward.
+    #[serde(default = "current_config_version")]
+    pub version: u32,
     pub server_url: String,
     pub token: String,
     /// Custom output directory for recordings. None = use default.
@@ -20,37 +45,153 @@ pub struct AppConfig {
     /// Persisted input device name. None = follow system default ("Auto").
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub input_device: Option<String>,
+    /// Whether `upload::audio_metadata` may shell out to `ffprobe` when its
+    /// native parsers can't determine a file's duration/sample rate. Off by
+    /// default so environments without ffmpeg installed see no behavior
+    /// change; see `upload::resolve_ffprobe_path`.
+    #[serde(default)]
+    pub ffprobe_fallback: bool,
+    /// Persisted global shortcut that toggles recording. None = use
+    /// `hotkey::DEFAULT_HOTKEY`. Stored as a `tauri_plugin_global_shortcut`
+    /// accelerator string, e.g. "CommandOrControl+Shift+R".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+    /// Minimum `tracing` level to emit, e.g. "debug", "info", "warn". None =
+    /// use the built-in default (see `logging::init_logging`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Persisted output format choice, as `RecordFormat::key()` ("wav",
+    /// "flac", or "mp3"). None = use `RecordFormat::default()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    /// Whether the "Mic + System Audio" capture mode was selected. None/Some(false)
+    /// = `CaptureMode::Single`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mix_system_audio: Option<bool>,
 }
 
-/// Returns the path to the config file.
-fn config_path() -> Result<PathBuf, String> {
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_CONFIG_VERSION,
+            server_url: String::new(),
+            token: String::new(),
+            output_dir: None,
+            input_device: None,
+            ffprobe_fallback: false,
+            hotkey: None,
+            log_level: None,
+            output_format: None,
+            mix_system_audio: None,
+        }
+    }
+}
+
+/// A single forward migration step, keyed by the version it upgrades *from*.
+/// `MIGRATIONS[i]` transforms a v`i` document into a v`i+1` document.
+type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// v0 (no `version` field) -> v1: layout is unchanged, just tag the version
+/// so future migrations can rely on it being present.
+fn migrate_v0_to_v1(mut raw: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = raw.as_object_mut() {
+        obj.insert("version".to_string(), serde_json::json!(1));
+    }
+    raw
+}
+
+/// Migrate a raw JSON document to the current `AppConfig` schema, applying
+/// each registered migration in sequence starting from the document's
+/// declared (or implied) version.
+fn migrate(mut raw: serde_json::Value) -> AppConfig {
+    let mut version = raw.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    while (version as usize) < MIGRATIONS.len() {
+        raw = MIGRATIONS[version as usize](raw);
+        version += 1;
+    }
+    serde_json::from_value(raw).unwrap_or_default()
+}
+
+/// Returns the default path to the config file, under the platform's
+/// standard per-app data directory (see the module docs for the exact path
+/// per OS).
+fn default_config_path() -> Result<PathBuf, String> {
     let data_dir = dirs::data_dir().ok_or("could not determine app data directory")?;
     Ok(data_dir.join(APP_DIR_NAME).join(CONFIG_FILE))
 }
 
-/// Read config from the JSON file.
-/// Returns default (empty) config if the file does not exist.
+/// Resolve the config path, honoring the `LYRE_CONFIG` environment variable
+/// when set, and falling back to the default location otherwise.
+fn config_path() -> Result<PathBuf, String> {
+    resolve_config_path(std::env::var(CONFIG_ENV_VAR).ok().map(PathBuf::from))
+}
+
+/// Resolve the config path given an optional explicit override: the
+/// override if present, otherwise the default location. This function does
+/// not read `LYRE_CONFIG` itself; `config_path` passes it in as the override.
+fn resolve_config_path(override_path: Option<PathBuf>) -> Result<PathBuf, String> {
+    match override_path {
+        Some(path) => Ok(path),
+        None => default_config_path(),
+    }
+}
+
+/// Environment variables that override `server_url`/`token` from the file,
+/// e.g. for CI, containers, and ephemeral dev environments. See
+/// [`AppConfig`] for the full precedence order.
+const SERVER_URL_ENV_VAR: &str = "LYRE_SERVER_URL";
+const TOKEN_ENV_VAR: &str = "LYRE_TOKEN";
+
+/// Read config, merging in environment overrides.
+///
+/// Precedence (highest first): `LYRE_SERVER_URL`/`LYRE_TOKEN` env vars, then
+/// the on-disk JSON file, then the zero-value default. Old config files
+/// (missing or behind `version`) are migrated forward in memory and
+/// rewritten to disk — before the env overlay is applied, so env-only
+/// overrides are never persisted.
 pub fn load_config() -> Result<AppConfig, String> {
+    let mut config = load_config_from_disk()?;
+    if let Ok(server_url) = std::env::var(SERVER_URL_ENV_VAR) {
+        config.server_url = server_url;
+    }
+    if let Ok(token) = std::env::var(TOKEN_ENV_VAR) {
+        config.token = token;
+    }
+    Ok(config)
+}
+
+/// Read config from the JSON file, without applying env overrides.
+/// Returns default (empty) config if the file does not exist.
+fn load_config_from_disk() -> Result<AppConfig, String> {
     let path = config_path()?;
     if !path.exists() {
         return Ok(AppConfig::default());
     }
     let content = fs::read_to_string(&path).map_err(|e| format!("failed to read config: {e}"))?;
-    serde_json::from_str(&content).map_err(|e| format!("failed to parse config: {e}"))
+    let raw: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("failed to parse config: {e}"))?;
+    let raw_version = raw.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    let config = migrate(raw);
+    if raw_version < CURRENT_CONFIG_VERSION {
+        write_config(&config)?;
+    }
+    Ok(config)
 }
 
 /// Save config to the JSON file.
 /// Creates the directory if it doesn't exist.
 pub fn save_config(server_url: &str, token: &str) -> Result<(), String> {
-    let mut config = load_config().unwrap_or_default();
+    let mut config = load_config_from_disk().unwrap_or_default();
     config.server_url = server_url.to_string();
-    config.token = token.to_string();
+    config.token = secret_backend().store_token(token)?;
     write_config(&config)
 }
 
 /// Save the output directory to config. Pass None to reset to default.
 pub fn save_output_dir(output_dir: Option<&str>) -> Result<(), String> {
-    let mut config = load_config().unwrap_or_default();
+    let mut config = load_config_from_disk().unwrap_or_default();
     config.output_dir = output_dir.map(|s| s.to_string());
     write_config(&config)
 }
@@ -68,7 +209,7 @@ pub fn get_output_dir() -> std::path::PathBuf {
 
 /// Save the selected input device name. Pass None for "Auto" (system default).
 pub fn save_input_device(device_name: Option<&str>) -> Result<(), String> {
-    let mut config = load_config().unwrap_or_default();
+    let mut config = load_config_from_disk().unwrap_or_default();
     config.input_device = device_name.map(|s| s.to_string());
     write_config(&config)
 }
@@ -81,7 +222,77 @@ pub fn get_input_device() -> Option<String> {
         .filter(|s| !s.is_empty())
 }
 
-/// Write the full config to disk.
+/// Enable or disable the `ffprobe` metadata fallback.
+pub fn save_ffprobe_fallback(enabled: bool) -> Result<(), String> {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    config.ffprobe_fallback = enabled;
+    write_config(&config)
+}
+
+/// Save the global recording-toggle shortcut. Pass None to reset to
+/// `hotkey::DEFAULT_HOTKEY`.
+pub fn save_hotkey(shortcut: Option<&str>) -> Result<(), String> {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    config.hotkey = shortcut.map(|s| s.to_string());
+    write_config(&config)
+}
+
+/// Get the persisted global shortcut. None means "use the default".
+pub fn get_hotkey() -> Option<String> {
+    load_config()
+        .ok()
+        .and_then(|c| c.hotkey)
+        .filter(|s| !s.is_empty())
+}
+
+/// Save the minimum `tracing` log level. Pass None to reset to the default.
+pub fn save_log_level(level: Option<&str>) -> Result<(), String> {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    config.log_level = level.map(|s| s.to_string());
+    write_config(&config)
+}
+
+/// Get the persisted log level. None means "use the default".
+pub fn get_log_level() -> Option<String> {
+    load_config()
+        .ok()
+        .and_then(|c| c.log_level)
+        .filter(|s| !s.is_empty())
+}
+
+/// Save the output format choice. Pass None (or an unrecognized key) to
+/// reset to `RecordFormat::default()`.
+pub fn save_output_format(key: Option<&str>) -> Result<(), String> {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    config.output_format = key.map(|s| s.to_string());
+    write_config(&config)
+}
+
+/// Get the persisted output format key. None means "use the default".
+pub fn get_output_format() -> Option<String> {
+    load_config()
+        .ok()
+        .and_then(|c| c.output_format)
+        .filter(|s| !s.is_empty())
+}
+
+/// Save whether the "Mic + System Audio" capture mode is selected.
+pub fn save_mix_system_audio(enabled: bool) -> Result<(), String> {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    config.mix_system_audio = Some(enabled);
+    write_config(&config)
+}
+
+/// Get whether "Mic + System Audio" was last selected. Defaults to false.
+pub fn get_mix_system_audio() -> bool {
+    load_config()
+        .ok()
+        .and_then(|c| c.mix_system_audio)
+        .unwrap_or(false)
+}
+
+/// Write the full config to disk, then lock it down to owner-only (0600) on
+/// Unix so the token isn't readable by other processes running as the user.
 fn write_config(config: &AppConfig) -> Result<(), String> {
     let path = config_path()?;
     if let Some(parent) = path.parent() {
@@ -90,7 +301,47 @@ fn write_config(config: &AppConfig) -> Result<(), String> {
     }
     let content = serde_json::to_string_pretty(config)
         .map_err(|e| format!("failed to serialize config: {e}"))?;
-    fs::write(&path, content).map_err(|e| format!("failed to write config: {e}"))
+    fs::write(&path, content).map_err(|e| format!("failed to write config: {e}"))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let perms = fs::Permissions::from_mode(0o600);
+        fs::set_permissions(&path, perms)
+            .map_err(|e| format!("failed to set config file permissions: {e}"))?;
+    }
+
+    Ok(())
+}
+
+/// Abstraction over where the auth token is ultimately persisted.
+///
+/// The default backend keeps the token in the JSON config file (protected
+/// only by the 0600 file permissions above). An OS keychain-backed
+/// implementation can swap in transparently: `store_token` would write the
+/// secret to the keychain and return a lookup reference, while the JSON file
+/// keeps only that reference instead of the raw token.
+pub trait SecretBackend: Send + Sync {
+    /// Persist `token` and return the value that should be written to the
+    /// JSON config in its place (the raw token itself, for the default
+    /// backend, or a keychain reference for a keychain-backed one).
+    fn store_token(&self, token: &str) -> Result<String, String>;
+}
+
+/// Default backend: the token is stored as-is in the config file.
+struct PlaintextBackend;
+
+impl SecretBackend for PlaintextBackend {
+    fn store_token(&self, token: &str) -> Result<String, String> {
+        Ok(token.to_string())
+    }
+}
+
+/// Returns the active secret backend. Currently always the plaintext
+/// backend; swapping in a keychain-backed `SecretBackend` here is the
+/// intended extension point.
+fn secret_backend() -> &'static dyn SecretBackend {
+    &PlaintextBackend
 }
 
 /// Returns true if both server_url and token are non-empty.
@@ -116,16 +367,18 @@ mod tests {
     use super::*;
     use std::env;
 
-    /// Set up a temp directory as the data dir for isolated tests.
+    /// Point the config file at a fresh temp path for isolated tests, via
+    /// `LYRE_CONFIG` rather than `$HOME` -- `dirs::data_dir()` resolves
+    /// differently per platform (some of which ignore `$HOME` entirely), but
+    /// the `LYRE_CONFIG` override is honored the same way everywhere.
     fn with_temp_config<F: FnOnce()>(f: F) {
         let tmp = tempfile::tempdir().unwrap();
-        let original = env::var("HOME").ok();
-        // dirs::data_dir() on macOS uses $HOME/Library/Application Support
-        env::set_var("HOME", tmp.path());
+        let original = env::var(CONFIG_ENV_VAR).ok();
+        env::set_var(CONFIG_ENV_VAR, tmp.path().join(CONFIG_FILE));
         f();
-        // Restore
-        if let Some(home) = original {
-            env::set_var("HOME", home);
+        match original {
+            Some(path) => env::set_var(CONFIG_ENV_VAR, path),
+            None => env::remove_var(CONFIG_ENV_VAR),
         }
     }
 
@@ -138,6 +391,11 @@ mod tests {
             assert!(config.token.is_empty());
             assert!(config.output_dir.is_none());
             assert!(config.input_device.is_none());
+            assert!(!config.ffprobe_fallback);
+            assert!(config.hotkey.is_none());
+            assert!(config.log_level.is_none());
+            assert!(config.output_format.is_none());
+            assert!(!config.mix_system_audio.unwrap_or(false));
             assert!(!has_config());
 
             // Save server config
@@ -199,10 +457,16 @@ mod tests {
     #[test]
     fn test_app_config_serialization() {
         let config = AppConfig {
+            version: CURRENT_CONFIG_VERSION,
             server_url: "https://lyre.example.com".to_string(),
             token: "lyre_abc123".to_string(),
             output_dir: Some("/custom/path".to_string()),
             input_device: Some("USB Mic".to_string()),
+            ffprobe_fallback: true,
+            hotkey: Some("CommandOrControl+Shift+R".to_string()),
+            log_level: Some("debug".to_string()),
+            output_format: Some("flac".to_string()),
+            mix_system_audio: Some(true),
         };
         let json = serde_json::to_string(&config).unwrap();
         assert!(json.contains("server_url"));
@@ -210,12 +474,94 @@ mod tests {
         assert!(json.contains("output_dir"));
         assert!(json.contains("input_device"));
         assert!(json.contains("USB Mic"));
+        assert!(json.contains("ffprobe_fallback"));
+        assert!(json.contains("hotkey"));
+        assert!(json.contains("log_level"));
+        assert!(json.contains("output_format"));
+        assert!(json.contains("mix_system_audio"));
 
         let parsed: AppConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.server_url, config.server_url);
         assert_eq!(parsed.token, config.token);
         assert_eq!(parsed.output_dir, config.output_dir);
         assert_eq!(parsed.input_device, config.input_device);
+        assert_eq!(parsed.ffprobe_fallback, config.ffprobe_fallback);
+        assert_eq!(parsed.log_level, config.log_level);
+        assert_eq!(parsed.output_format, config.output_format);
+        assert_eq!(parsed.mix_system_audio, config.mix_system_audio);
+    }
+
+    #[test]
+    fn test_save_ffprobe_fallback_roundtrip() {
+        with_temp_config(|| {
+            assert!(!load_config().unwrap().ffprobe_fallback);
+            save_ffprobe_fallback(true).unwrap();
+            assert!(load_config().unwrap().ffprobe_fallback);
+            save_ffprobe_fallback(false).unwrap();
+            assert!(!load_config().unwrap().ffprobe_fallback);
+        });
+    }
+
+    #[test]
+    fn test_save_log_level_roundtrip() {
+        with_temp_config(|| {
+            assert!(get_log_level().is_none());
+            save_log_level(Some("debug")).unwrap();
+            assert_eq!(get_log_level(), Some("debug".to_string()));
+            save_log_level(None).unwrap();
+            assert!(get_log_level().is_none());
+            save_log_level(Some("")).unwrap();
+            assert!(get_log_level().is_none());
+        });
+    }
+
+    #[test]
+    fn test_save_output_format_roundtrip() {
+        with_temp_config(|| {
+            assert!(get_output_format().is_none());
+            save_output_format(Some("flac")).unwrap();
+            assert_eq!(get_output_format(), Some("flac".to_string()));
+            save_output_format(None).unwrap();
+            assert!(get_output_format().is_none());
+            save_output_format(Some("")).unwrap();
+            assert!(get_output_format().is_none());
+        });
+    }
+
+    #[test]
+    fn test_save_mix_system_audio_roundtrip() {
+        with_temp_config(|| {
+            assert!(!get_mix_system_audio());
+            save_mix_system_audio(true).unwrap();
+            assert!(get_mix_system_audio());
+            save_mix_system_audio(false).unwrap();
+            assert!(!get_mix_system_audio());
+        });
+    }
+
+    #[test]
+    fn test_lyre_config_env_var_overrides_default_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom_path = tmp.path().join("custom-config.json");
+        let original = env::var(CONFIG_ENV_VAR).ok();
+        env::set_var(CONFIG_ENV_VAR, &custom_path);
+
+        save_config("https://lyre.example.com", "tok").unwrap();
+        assert!(custom_path.exists());
+        let config = load_config().unwrap();
+        assert_eq!(config.server_url, "https://lyre.example.com");
+
+        match original {
+            Some(v) => env::set_var(CONFIG_ENV_VAR, v),
+            None => env::remove_var(CONFIG_ENV_VAR),
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_path_prefers_override() {
+        let explicit = PathBuf::from("/tmp/explicit-config.json");
+        let resolved = resolve_config_path(Some(explicit.clone())).unwrap();
+        assert_eq!(resolved, explicit);
     }
 
     #[test]
@@ -226,5 +572,128 @@ mod tests {
         assert_eq!(parsed.server_url, "https://lyre.example.com");
         assert!(parsed.output_dir.is_none());
         assert!(parsed.input_device.is_none());
+        // version is missing from the raw JSON, so it defaults to current.
+        assert_eq!(parsed.version, CURRENT_CONFIG_VERSION);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_file_has_restrictive_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        with_temp_config(|| {
+            save_config("https://lyre.example.com", "secret-token").unwrap();
+            let path = config_path().unwrap();
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        });
+    }
+
+    #[test]
+    fn test_plaintext_backend_stores_token_unchanged() {
+        let backend = PlaintextBackend;
+        assert_eq!(backend.store_token("lyre_abc123").unwrap(), "lyre_abc123");
+    }
+
+    // --- Env var overrides ---
+
+    #[test]
+    fn test_env_overrides_take_precedence_over_file() {
+        with_temp_config(|| {
+            save_config("https://lyre.example.com", "file-token").unwrap();
+
+            let orig_url = env::var(SERVER_URL_ENV_VAR).ok();
+            let orig_token = env::var(TOKEN_ENV_VAR).ok();
+            env::set_var(SERVER_URL_ENV_VAR, "https://lyre.env.example.com");
+            env::set_var(TOKEN_ENV_VAR, "env-token");
+
+            let config = load_config().unwrap();
+            assert_eq!(config.server_url, "https://lyre.env.example.com");
+            assert_eq!(config.token, "env-token");
+
+            // The file itself should not have been rewritten with env values.
+            let on_disk = load_config_from_disk().unwrap();
+            assert_eq!(on_disk.server_url, "https://lyre.example.com");
+            assert_eq!(on_disk.token, "file-token");
+
+            match orig_url {
+                Some(v) => env::set_var(SERVER_URL_ENV_VAR, v),
+                None => env::remove_var(SERVER_URL_ENV_VAR),
+            }
+            match orig_token {
+                Some(v) => env::set_var(TOKEN_ENV_VAR, v),
+                None => env::remove_var(TOKEN_ENV_VAR),
+            }
+        });
+    }
+
+    #[test]
+    fn test_has_config_true_from_env_alone_without_file() {
+        with_temp_config(|| {
+            assert!(!has_config());
+
+            let orig_url = env::var(SERVER_URL_ENV_VAR).ok();
+            let orig_token = env::var(TOKEN_ENV_VAR).ok();
+            env::set_var(SERVER_URL_ENV_VAR, "https://lyre.env.example.com");
+            env::set_var(TOKEN_ENV_VAR, "env-token");
+
+            assert!(has_config());
+
+            match orig_url {
+                Some(v) => env::set_var(SERVER_URL_ENV_VAR, v),
+                None => env::remove_var(SERVER_URL_ENV_VAR),
+            }
+            match orig_token {
+                Some(v) => env::set_var(TOKEN_ENV_VAR, v),
+                None => env::remove_var(TOKEN_ENV_VAR),
+            }
+        });
+    }
+
+    // --- Schema migration ---
+
+    #[test]
+    fn test_migrate_v0_no_version_field() {
+        let raw = serde_json::json!({
+            "server_url": "https://lyre.example.com",
+            "token": "tok",
+        });
+        let config = migrate(raw);
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.server_url, "https://lyre.example.com");
+        assert_eq!(config.token, "tok");
+    }
+
+    #[test]
+    fn test_migrate_already_current_is_a_no_op() {
+        let raw = serde_json::json!({
+            "version": CURRENT_CONFIG_VERSION,
+            "server_url": "https://lyre.example.com",
+            "token": "tok",
+            "output_dir": "/tmp/recordings",
+        });
+        let config = migrate(raw);
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.output_dir, Some("/tmp/recordings".to_string()));
+    }
+
+    #[test]
+    fn test_load_config_migrates_and_rewrites_v0_file_on_disk() {
+        with_temp_config(|| {
+            let path = config_path().unwrap();
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(
+                &path,
+                r#"{"server_url":"https://lyre.example.com","token":"tok"}"#,
+            )
+            .unwrap();
+
+            let config = load_config().unwrap();
+            assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+
+            // The file on disk should now carry the current version so the
+            // migration only runs once.
+            let rewritten = fs::read_to_string(&path).unwrap();
+            assert!(rewritten.contains(&format!("\"version\": {CURRENT_CONFIG_VERSION}")));
+        });
     }
 }
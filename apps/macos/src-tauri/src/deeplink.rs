@@ -0,0 +1,58 @@
+//! `lyre://configure?server=<url>&token=<token>` deep link handling, so the
+//! web app can hand a user a single link that provisions the desktop client
+//! instead of asking them to copy a server URL and token into Settings.
+//!
+//! Wired up in `setup()` via `tauri_plugin_deep_link`'s `on_open_url`. The
+//! OS-level scheme association (`CFBundleURLTypes` in Info.plist) is a
+//! packaging concern, not this module.
+
+use tauri::{AppHandle, Manager};
+
+/// Handle every incoming `lyre://` URL from an OS "open URL" event. Only the
+/// `configure` host is recognized; anything else is ignored.
+pub fn handle_urls(app: &AppHandle, urls: Vec<url::Url>) {
+    for url in urls {
+        if url.scheme() != "lyre" || url.host_str() != Some("configure") {
+            continue;
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            handle_configure(&app, &url).await;
+        });
+    }
+}
+
+/// Parse `server`/`token` query params, save them, and verify the
+/// connection. The main window is shown either way so the user sees the
+/// result (the saved settings, or an error to retry from).
+async fn handle_configure(app: &AppHandle, url: &url::Url) {
+    let params: std::collections::HashMap<String, String> =
+        url.query_pairs().into_owned().collect();
+
+    let server = params.get("server").map(|s| s.trim()).unwrap_or("");
+    let token = params.get("token").map(|s| s.trim()).unwrap_or("");
+    if server.is_empty() || token.is_empty() {
+        eprintln!("lyre://configure link is missing a server or token parameter");
+        show_main_window(app);
+        return;
+    }
+
+    if let Err(e) = crate::config::save_config(server, token) {
+        eprintln!("failed to save config from deep link: {e}");
+        show_main_window(app);
+        return;
+    }
+
+    if let Err(e) = crate::http_client::test_connection(server, token).await {
+        eprintln!("deep link credentials failed the connection test: {e}");
+    }
+
+    show_main_window(app);
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::tray::{self, TrayState};
+
+/// Default global shortcut that toggles recording, used when the user has
+/// not configured `config::AppConfig.hotkey`.
+pub const DEFAULT_HOTKEY: &str = "CommandOrControl+Shift+R";
+
+/// Register the persisted (or default) global shortcut at startup. Called
+/// once during app setup, after `tray::setup_tray` has managed `TrayState`.
+pub fn register_startup_hotkey(app: &AppHandle) {
+    let shortcut = crate::config::get_hotkey().unwrap_or_else(|| DEFAULT_HOTKEY.to_string());
+    if let Err(e) = register_hotkey(app, &shortcut) {
+        eprintln!("failed to register startup hotkey \"{shortcut}\": {e}");
+    }
+}
+
+/// Parse and register `shortcut` as the global recording-toggle shortcut,
+/// replacing any previously registered shortcut. Returns `Err` (rather than
+/// panicking) if the accelerator string is invalid or already claimed by
+/// another app.
+pub fn register_hotkey(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let parsed: Shortcut = shortcut
+        .parse()
+        .map_err(|e| format!("invalid shortcut \"{shortcut}\": {e}"))?;
+
+    let manager = app.global_shortcut();
+    let _ = manager.unregister_all();
+    manager
+        .register(parsed)
+        .map_err(|e| format!("failed to register shortcut \"{shortcut}\": {e}"))
+}
+
+/// Shared handler for every registered shortcut. There is only ever one
+/// shortcut registered at a time, so we always toggle recording on press.
+pub(crate) fn handle_shortcut(app: &AppHandle, state: &Mutex<TrayState>) {
+    tray::toggle_recording(app, state);
+}
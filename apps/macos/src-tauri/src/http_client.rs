@@ -1,12 +1,26 @@
 //! HTTP client for communicating with the Lyre web app.
 //!
-//! Uses `reqwest` with `rustls-tls` to avoid OpenSSL dependency.
-//! The primary operation is `test_connection` which calls `GET /api/live`
-//! with a Bearer token and verifies the response.
+//! Uses `reqwest` with `rustls-tls` to avoid OpenSSL dependency. The one
+//! idempotent request here, GET `/api/live` in `test_connection`, goes
+//! through `request_with_retry`, which retries transient failures with
+//! exponential backoff; the upload requests in `upload` use their own client
+//! and are not retried by this module. `RequestError` distinguishes failure categories so callers can
+//! tell e.g. "wrong token" apart from "server temporarily unreachable" and
+//! decide whether retrying is worthwhile.
+
+use std::time::Duration;
 
 use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
 use serde::Deserialize;
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REDIRECTS: usize = 5;
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
 /// Response from the `/api/live` endpoint.
 #[derive(Debug, Deserialize)]
 struct LiveResponse {
@@ -15,10 +29,134 @@ struct LiveResponse {
     version: Option<String>,
 }
 
+/// Failure categories for an HTTP request, distinct enough that callers can
+/// decide whether retrying is worthwhile — a wrong token never will, while
+/// connectivity or server-side hiccups often resolve on their own.
+#[derive(Debug, Clone)]
+pub enum RequestError {
+    /// DNS resolution or TCP connection failed.
+    Unreachable(String),
+    /// TLS handshake failed.
+    Tls(String),
+    /// The request timed out (connect or read).
+    Timeout,
+    /// HTTP 401/403 — the token is missing or wrong.
+    Unauthorized,
+    /// Other HTTP 4xx.
+    ClientError(StatusCode),
+    /// HTTP 5xx.
+    ServerError(StatusCode),
+    /// Response body did not parse as expected.
+    InvalidResponse(String),
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreachable(e) => write!(f, "server unreachable: {e}"),
+            Self::Tls(e) => write!(f, "TLS error: {e}"),
+            Self::Timeout => write!(f, "request timed out"),
+            Self::Unauthorized => write!(f, "authentication failed — check your device token"),
+            Self::ClientError(status) => write!(f, "server returned HTTP {status}"),
+            Self::ServerError(status) => write!(f, "server returned HTTP {status}"),
+            Self::InvalidResponse(e) => write!(f, "invalid server response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl From<RequestError> for String {
+    fn from(e: RequestError) -> String {
+        e.to_string()
+    }
+}
+
+impl RequestError {
+    /// Whether retrying this request is likely to help. Authentication and
+    /// other 4xx errors are permanent for a given request; connectivity
+    /// hiccups and 5xx responses often clear up on their own.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Unreachable(_) | Self::Timeout | Self::ServerError(_)
+        )
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return Self::Timeout;
+        }
+        if e.is_connect() {
+            let message = e.to_string();
+            if message.to_lowercase().contains("tls") || message.to_lowercase().contains("certificate")
+            {
+                return Self::Tls(message);
+            }
+            return Self::Unreachable(message);
+        }
+        Self::Unreachable(e.to_string())
+    }
+}
+
+/// Build a client configured with the repo's shared timeout and
+/// redirect policy.
+fn build_client(headers: HeaderMap) -> Result<reqwest::Client, RequestError> {
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(READ_TIMEOUT)
+        .redirect(Policy::limited(MAX_REDIRECTS))
+        .build()
+        .map_err(|e| RequestError::Unreachable(format!("failed to build HTTP client: {e}")))
+}
+
+/// Turn a sent response into `Ok` or a categorized `RequestError`, based on
+/// its status code.
+fn classify_response(response: reqwest::Response) -> Result<reqwest::Response, RequestError> {
+    let status = response.status();
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        Err(RequestError::Unauthorized)
+    } else if status.is_client_error() {
+        Err(RequestError::ClientError(status))
+    } else if status.is_server_error() {
+        Err(RequestError::ServerError(status))
+    } else {
+        Ok(response)
+    }
+}
+
+/// Send a request built fresh by `build` on every attempt (a
+/// `RequestBuilder` can't be cloned after `.send()`), retrying up to
+/// `MAX_RETRIES` times with exponential backoff when the failure looks
+/// transient. Only call this for idempotent requests.
+async fn request_with_retry<F>(build: F) -> Result<reqwest::Response, RequestError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 0..=MAX_RETRIES {
+        let outcome = match build().send().await {
+            Ok(response) => classify_response(response),
+            Err(e) => Err(RequestError::from_reqwest(e)),
+        };
+        match outcome {
+            Ok(response) => return Ok(response),
+            Err(e) if e.is_retryable() && attempt < MAX_RETRIES => {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("the loop above always returns on its last iteration")
+}
+
 /// Test the connection to a Lyre web server.
 ///
-/// Calls `GET <server_url>/api/live` with a Bearer token.
-/// Returns `Ok(())` if the server responds with `{ "status": "ok" }`.
+/// Calls `GET <server_url>/api/live` with a Bearer token, retrying
+/// transient failures. Returns `Ok(())` if the server responds with
+/// `{ "status": "ok" }`.
 pub async fn test_connection(server_url: &str, token: &str) -> Result<(), String> {
     let url = normalize_url(server_url);
     let endpoint = format!("{url}/api/live");
@@ -31,30 +169,14 @@ pub async fn test_connection(server_url: &str, token: &str) -> Result<(), String
             .map_err(|e| format!("invalid token format: {e}"))?,
     );
 
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("failed to build HTTP client: {e}"))?;
-
-    let response = client
-        .get(&endpoint)
-        .send()
-        .await
-        .map_err(|e| format!("connection failed: {e}"))?;
+    let client = build_client(headers)?;
 
-    let status = response.status();
-    if status == reqwest::StatusCode::UNAUTHORIZED {
-        return Err("authentication failed — check your device token".to_string());
-    }
-    if !status.is_success() {
-        return Err(format!("server returned HTTP {status}"));
-    }
+    let response = request_with_retry(|| client.get(&endpoint)).await?;
 
     let body: LiveResponse = response
         .json()
         .await
-        .map_err(|e| format!("invalid server response: {e}"))?;
+        .map_err(|e| RequestError::InvalidResponse(e.to_string()))?;
 
     if body.status != "ok" {
         return Err(format!(
@@ -104,4 +226,23 @@ mod tests {
     fn test_normalize_url_already_clean() {
         assert_eq!(normalize_url("https://lyre.dev"), "https://lyre.dev");
     }
+
+    #[test]
+    fn test_request_error_is_retryable() {
+        assert!(RequestError::Unreachable("x".into()).is_retryable());
+        assert!(RequestError::Timeout.is_retryable());
+        assert!(RequestError::ServerError(StatusCode::BAD_GATEWAY).is_retryable());
+        assert!(!RequestError::Unauthorized.is_retryable());
+        assert!(!RequestError::ClientError(StatusCode::NOT_FOUND).is_retryable());
+        assert!(!RequestError::Tls("x".into()).is_retryable());
+    }
+
+    #[test]
+    fn test_request_error_display_distinguishes_categories() {
+        assert!(RequestError::Unauthorized.to_string().contains("token"));
+        assert!(RequestError::Timeout.to_string().contains("timed out"));
+        assert!(RequestError::ServerError(StatusCode::SERVICE_UNAVAILABLE)
+            .to_string()
+            .contains("503"));
+    }
 }
@@ -1,20 +1,34 @@
 pub mod audio;
 pub mod config;
 pub mod http_client;
+pub mod loudness;
+pub mod midi;
+pub mod player;
 pub mod recorder;
 pub mod recordings;
 pub mod system_audio;
 pub mod upload;
+pub mod upload_queue;
 
-pub use audio::AudioDeviceManager;
-pub use config::{clear_config, get_input_device, get_input_device_full, get_output_dir, has_config, load_config, save_config, save_input_device, save_output_dir, AppConfig};
-pub use recorder::{generate_filename, Recorder, RecorderConfig, RecorderState};
+pub use audio::{AudioDeviceManager, DeviceProperties};
+pub use config::{clear_config, get_input_device, get_input_device_full, get_output_dir, has_config, load_config, save_config, save_ffprobe_fallback, save_input_device, save_output_dir, AppConfig};
+pub use loudness::{analyze_loudness, analyze_loudness_with_reference, LoudnessInfo, DEFAULT_REFERENCE_LUFS};
+pub use midi::{parse_midi_message, resolve_action, MidiAction, MidiBinding, MidiControl, MidiError, MidiMessageKind};
+pub use player::{
+    spawn_player_actor, Player, PlayerCommand, PlayerConfig, PlayerError, PlayerHandle,
+    PlayerState, PlayerStatus,
+};
+pub use recorder::{generate_filename, spawn_actor, AudioLevel, CaptureMode, ChannelMode, OnDeviceLost, RecordFormat, Recorder, RecorderCommand, RecorderConfig, RecorderHandle, RecorderState, RecorderStatus, StopOutcome};
 pub use recordings::{
-    batch_delete_recordings, default_output_dir, delete_recording, find_cleanable_recordings,
-    list_recordings, CleanupFilter, CleanupResult, RecordingInfo,
+    batch_delete_recordings, default_output_dir, delete_recording, empty_trash,
+    find_broken_recordings, find_cleanable_recordings, find_duplicate_recordings,
+    find_duplicates_by_tags, get_waveform, list_recordings, list_recordings_with_options,
+    list_segments, rebuild_cache, restore_recording, BrokenRecording, CleanupFilter,
+    CleanupResult, RecordingInfo, ScanOptions, TagSimilarityFlags, WaveformPeak,
 };
 pub use system_audio::{
-    check_permission, list_audio_input_devices, AudioInputDeviceInfo, CaptureConfig,
-    CaptureError, PermissionStatus, SystemAudioCapture,
+    check_permission, list_audio_input_devices, AudioInputDeviceInfo, CaptureBackendKind,
+    CaptureConfig, CaptureError, MixControl, MixSettings, PermissionStatus, SystemAudioCapture,
 };
-pub use upload::{cancel_upload, upload_recording, ServerFolder, ServerTag, UploadOptions, UploadProgress, UploadResult};
+pub use upload::{cancel_upload, upload_batch, upload_recording, upload_recording_with_progress, ServerFolder, ServerTag, UploadOptions, UploadProgress, UploadResult};
+pub use upload_queue::{enqueue_upload, list_pending, retry_now, upload_state_for, PendingUpload, UploadState};
@@ -0,0 +1,26 @@
+//! Wires up the `tracing` subscriber used by `upload`'s structured,
+//! per-operation logging (see `upload::upload_recording_inner`).
+//!
+//! Level is configurable via `config::get_log_level` (persisted from the
+//! tray/settings UI) and overridable with the `RUST_LOG` env var for local
+//! debugging, same precedence as `config::load_config`'s env overlay.
+
+use tracing_subscriber::EnvFilter;
+
+/// Default level when neither `RUST_LOG` nor the persisted config set one.
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Install the global `tracing` subscriber. Call once, early in `main`.
+pub fn init_logging() {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .or_else(crate::config::get_log_level)
+        .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+
+    let filter = EnvFilter::try_new(&level).unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .init();
+}
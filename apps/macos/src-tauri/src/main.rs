@@ -1,10 +1,17 @@
 mod audio;
 mod config;
+mod deeplink;
+mod hotkey;
 mod http_client;
+mod logging;
+mod loudness;
+mod player;
+mod protocol;
 mod recorder;
 mod recordings;
 mod tray;
 mod upload;
+mod upload_queue;
 
 use tauri::Manager;
 
@@ -96,11 +103,27 @@ fn list_recordings() -> Result<Vec<recordings::RecordingInfo>, String> {
     recordings::list_recordings(&output_dir)
 }
 
-/// Tauri command: delete a local recording file.
+/// Tauri command: delete a local recording file. When `soft` is true, the
+/// file is moved to the output directory's trash instead of being
+/// unlinked -- see `restore_recording`/`empty_trash`.
 #[tauri::command]
-fn delete_recording(file_path: String) -> Result<(), String> {
+fn delete_recording(file_path: String, soft: bool) -> Result<(), String> {
     let output_dir = config::get_output_dir();
-    recordings::delete_recording(&file_path, &output_dir)
+    recordings::delete_recording(&file_path, &output_dir, soft)
+}
+
+/// Tauri command: bring a soft-deleted recording back out of the trash.
+#[tauri::command]
+fn restore_recording(file_name: String) -> Result<(), String> {
+    let output_dir = config::get_output_dir();
+    recordings::restore_recording(&file_name, &output_dir)
+}
+
+/// Tauri command: permanently delete everything in the trash.
+#[tauri::command]
+fn empty_trash() -> recordings::CleanupResult {
+    let output_dir = config::get_output_dir();
+    recordings::empty_trash(&output_dir)
 }
 
 /// Tauri command: reveal a recording in Finder.
@@ -113,12 +136,71 @@ fn reveal_recording(file_path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Tauri command: get (computing and caching if necessary) a downsampled
+/// waveform for a recording. Returns None if the file's format has no PCM
+/// decoder wired up (see `recordings::get_waveform`).
+#[tauri::command]
+fn get_waveform(file_path: String) -> Result<Option<Vec<recordings::WaveformPeak>>, String> {
+    recordings::get_waveform(&file_path, recordings::DEFAULT_WAVEFORM_BUCKETS)
+}
+
 /// Tauri command: upload a local recording to the Lyre web app.
 #[tauri::command]
 async fn upload_recording(file_path: String) -> Result<upload::UploadResult, String> {
     upload::upload_recording(&file_path).await
 }
 
+/// Tauri command: add an upload to the durable background queue instead of
+/// uploading inline. Returns immediately with the queued entry's id.
+#[tauri::command]
+fn enqueue_upload(options: upload::UploadOptions) -> Result<String, String> {
+    upload_queue::enqueue_upload(options)
+}
+
+/// Tauri command: list uploads currently waiting in the background queue.
+#[tauri::command]
+fn list_pending_uploads() -> Vec<upload_queue::PendingUpload> {
+    upload_queue::list_pending()
+}
+
+/// Tauri command: drain the background queue now. Queued uploads are
+/// processed only when this runs; nothing drains the queue on a timer.
+#[tauri::command]
+async fn retry_now(app: tauri::AppHandle) {
+    upload_queue::retry_now(&app).await
+}
+
+/// Tauri command: cancel an in-progress upload by its handle (the id
+/// returned by `enqueue_upload`, or the handle carried in `upload-progress`
+/// events for an inline upload).
+#[tauri::command]
+fn cancel_upload(handle: String) {
+    upload::cancel_upload(&handle)
+}
+
+/// Tauri command: set (and persist) the global shortcut that toggles
+/// recording. Pass empty string to reset to `hotkey::DEFAULT_HOTKEY`.
+#[tauri::command]
+fn set_hotkey(app: tauri::AppHandle, shortcut: String) -> Result<(), String> {
+    let shortcut = if shortcut.trim().is_empty() {
+        hotkey::DEFAULT_HOTKEY.to_string()
+    } else {
+        shortcut
+    };
+    hotkey::register_hotkey(&app, &shortcut)?;
+    config::save_hotkey(if shortcut == hotkey::DEFAULT_HOTKEY {
+        None
+    } else {
+        Some(&shortcut)
+    })
+}
+
+/// Tauri command: get the currently configured global shortcut.
+#[tauri::command]
+fn get_hotkey() -> String {
+    config::get_hotkey().unwrap_or_else(|| hotkey::DEFAULT_HOTKEY.to_string())
+}
+
 /// Tauri command: preview which recordings match a cleanup filter.
 /// Returns the list of recordings that would be deleted without actually deleting them.
 #[tauri::command]
@@ -134,15 +216,51 @@ fn preview_cleanup(
 #[tauri::command]
 fn batch_delete_recordings(
     file_paths: Vec<String>,
+    soft: bool,
 ) -> Result<recordings::CleanupResult, String> {
     let output_dir = config::get_output_dir();
-    Ok(recordings::batch_delete_recordings(&file_paths, &output_dir))
+    Ok(recordings::batch_delete_recordings(
+        &file_paths,
+        &output_dir,
+        soft,
+    ))
+}
+
+/// Tauri command: find recordings that fail to decode (crash/disk-full
+/// cutoffs), so the cleanup UI can offer "remove broken files" as a batch
+/// action alongside `preview_cleanup`.
+#[tauri::command]
+fn find_broken_recordings() -> Result<Vec<recordings::BrokenRecording>, String> {
+    let output_dir = config::get_output_dir();
+    Ok(recordings::find_broken_recordings(&output_dir))
+}
+
+/// Tauri command: force a full recompute of the metadata cache, in case a
+/// file was edited in place without its size or mtime changing.
+#[tauri::command]
+fn rebuild_cache() -> Result<Vec<recordings::RecordingInfo>, String> {
+    let output_dir = config::get_output_dir();
+    recordings::rebuild_cache(&output_dir)
 }
 
 fn main() {
-    tauri::Builder::default()
+    logging::init_logging();
+
+    protocol::register(tauri::Builder::default())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, _shortcut, event| {
+                    if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        if let Some(state) = app.try_state::<std::sync::Mutex<tray::TrayState>>() {
+                            hotkey::handle_shortcut(app, &state);
+                        }
+                    }
+                })
+                .build(),
+        )
         .invoke_handler(tauri::generate_handler![
             get_config,
             save_config,
@@ -153,13 +271,34 @@ fn main() {
             open_output_dir,
             list_recordings,
             delete_recording,
+            restore_recording,
+            empty_trash,
             reveal_recording,
+            get_waveform,
             upload_recording,
+            enqueue_upload,
+            list_pending_uploads,
+            retry_now,
+            cancel_upload,
             preview_cleanup,
             batch_delete_recordings,
+            find_broken_recordings,
+            rebuild_cache,
+            set_hotkey,
+            get_hotkey,
         ])
         .setup(|app| {
             tray::setup_tray(app)?;
+            hotkey::register_startup_hotkey(app.handle());
+
+            // lyre://configure?server=...&token=... deep link provisioning.
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    deeplink::handle_urls(&handle, event.urls());
+                });
+            }
 
             // Hide window on close instead of quitting (keeps tray app alive).
             let main_window = app.get_webview_window("main");
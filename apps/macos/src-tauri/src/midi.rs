@@ -0,0 +1,291 @@
+//! Optional MIDI control for hands-free recording start/stop.
+//!
+//! Lets a foot pedal or control surface toggle recording without touching
+//! the UI: `MidiControl` opens an input port via `midir`, parses incoming
+//! Note-On/Control-Change messages against a configured `MidiBinding`, and
+//! dispatches the matching `MidiAction` through a caller-supplied callback
+//! on its own thread -- the same shape as `hotkey::handle_shortcut` calling
+//! into `tray::toggle_recording`, just driven by a MIDI port instead of a
+//! global keyboard shortcut.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use midir::{Ignore, MidiInput, MidiInputConnection};
+
+/// Which kind of MIDI message a binding matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessageKind {
+    NoteOn,
+    ControlChange,
+}
+
+/// What a matched MIDI message should do to the active recorder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiAction {
+    Start,
+    Stop,
+    Toggle,
+}
+
+/// Which MIDI message triggers which recorder action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiBinding {
+    pub message: MidiMessageKind,
+    /// MIDI channel, 0-15.
+    pub channel: u8,
+    /// Note number (for `NoteOn`) or controller number (for `ControlChange`).
+    pub number: u8,
+    pub action: MidiAction,
+}
+
+/// A raw MIDI message, parsed independently of any binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedMidiMessage {
+    pub kind: MidiMessageKind,
+    pub channel: u8,
+    pub number: u8,
+    pub value: u8,
+}
+
+/// Parse a raw 3-byte MIDI message, as delivered by `midir`'s input
+/// callback, into a `ParsedMidiMessage`. Returns `None` for message types
+/// this subsystem doesn't act on (Note-Off, clock, sysex, pitch bend, ...)
+/// or malformed/short packets.
+pub fn parse_midi_message(bytes: &[u8]) -> Option<ParsedMidiMessage> {
+    let &[status, number, value] = bytes else {
+        return None;
+    };
+    let channel = status & 0x0F;
+    match status & 0xF0 {
+        // A Note-On with velocity 0 is conventionally a Note-Off.
+        0x90 if value > 0 => Some(ParsedMidiMessage {
+            kind: MidiMessageKind::NoteOn,
+            channel,
+            number,
+            value,
+        }),
+        0xB0 => Some(ParsedMidiMessage {
+            kind: MidiMessageKind::ControlChange,
+            channel,
+            number,
+            value,
+        }),
+        _ => None,
+    }
+}
+
+/// Resolve the action `binding` maps to for `message`, if it matches.
+///
+/// For `ControlChange` bindings, only a value >= 64 triggers the action --
+/// most pedals/control surfaces send 127 on press and 0 on release, and
+/// this treats anything past the halfway point as "pressed".
+pub fn resolve_action(binding: &MidiBinding, message: &ParsedMidiMessage) -> Option<MidiAction> {
+    if binding.message != message.kind
+        || binding.channel != message.channel
+        || binding.number != message.number
+    {
+        return None;
+    }
+    match message.kind {
+        MidiMessageKind::NoteOn => Some(binding.action),
+        MidiMessageKind::ControlChange if message.value >= 64 => Some(binding.action),
+        MidiMessageKind::ControlChange => None,
+    }
+}
+
+/// Errors opening or running the MIDI control subsystem.
+#[derive(Debug, Clone)]
+pub enum MidiError {
+    NoInputPorts,
+    PortNotFound(String),
+    ConnectFailed(String),
+}
+
+impl std::fmt::Display for MidiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoInputPorts => write!(f, "no MIDI input ports available"),
+            Self::PortNotFound(name) => write!(f, "MIDI input port '{name}' not found"),
+            Self::ConnectFailed(e) => write!(f, "failed to connect to MIDI input: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MidiError {}
+
+/// Minimum time between dispatched actions, to debounce a pedal's
+/// mechanical bounce or a control surface re-sending the same value.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// An open MIDI input listening for a single `MidiBinding` and dispatching
+/// matched actions through a callback, on `midir`'s own input thread. Drop
+/// to stop listening and release the port.
+pub struct MidiControl {
+    _connection: MidiInputConnection<()>,
+}
+
+impl MidiControl {
+    /// Open a MIDI input port and start listening for `binding`.
+    ///
+    /// `port_name` selects a specific port by (partial) name match, or
+    /// `None` to use the first available input port. `dispatch` runs on the
+    /// MIDI thread whenever a message matches `binding` -- it's the
+    /// caller's job to check whether a `Recorder` is actually active before
+    /// acting on it, the same way `hotkey::handle_shortcut` defers to
+    /// `tray::toggle_recording` rather than deciding here.
+    pub fn open(
+        port_name: Option<&str>,
+        binding: MidiBinding,
+        dispatch: impl Fn(MidiAction) + Send + 'static,
+    ) -> Result<Self, MidiError> {
+        let mut input = MidiInput::new("lyre-midi-control")
+            .map_err(|e| MidiError::ConnectFailed(e.to_string()))?;
+        input.ignore(Ignore::None);
+
+        let ports = input.ports();
+        let port = match port_name {
+            Some(name) => ports
+                .iter()
+                .find(|p| input.port_name(p).is_ok_and(|n| n.contains(name)))
+                .ok_or_else(|| MidiError::PortNotFound(name.to_string()))?
+                .clone(),
+            None => ports.first().ok_or(MidiError::NoInputPorts)?.clone(),
+        };
+
+        let last_dispatch: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+
+        let connection = input
+            .connect(
+                &port,
+                "lyre-midi-control-input",
+                move |_stamp, message, _| {
+                    let Some(parsed) = parse_midi_message(message) else {
+                        return;
+                    };
+                    let Some(action) = resolve_action(&binding, &parsed) else {
+                        return;
+                    };
+
+                    let mut last = last_dispatch.lock().unwrap();
+                    let now = Instant::now();
+                    if last.is_some_and(|t| now.duration_since(t) < DEBOUNCE) {
+                        return;
+                    }
+                    *last = Some(now);
+                    drop(last);
+
+                    dispatch(action);
+                },
+                (),
+            )
+            .map_err(|e| MidiError::ConnectFailed(e.to_string()))?;
+
+        Ok(Self {
+            _connection: connection,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_on() {
+        let parsed = parse_midi_message(&[0x90, 60, 127]).unwrap();
+        assert_eq!(parsed.kind, MidiMessageKind::NoteOn);
+        assert_eq!(parsed.channel, 0);
+        assert_eq!(parsed.number, 60);
+        assert_eq!(parsed.value, 127);
+    }
+
+    #[test]
+    fn test_parse_note_on_with_zero_velocity_is_ignored() {
+        // Note-On with velocity 0 is a disguised Note-Off.
+        assert!(parse_midi_message(&[0x90, 60, 0]).is_none());
+    }
+
+    #[test]
+    fn test_parse_control_change() {
+        let parsed = parse_midi_message(&[0xB3, 64, 127]).unwrap();
+        assert_eq!(parsed.kind, MidiMessageKind::ControlChange);
+        assert_eq!(parsed.channel, 3);
+        assert_eq!(parsed.number, 64);
+        assert_eq!(parsed.value, 127);
+    }
+
+    #[test]
+    fn test_parse_unrelated_status_byte_is_ignored() {
+        // Pitch bend (0xE0) isn't a message this subsystem acts on.
+        assert!(parse_midi_message(&[0xE0, 0, 64]).is_none());
+    }
+
+    #[test]
+    fn test_parse_short_message_is_ignored() {
+        assert!(parse_midi_message(&[0x90, 60]).is_none());
+    }
+
+    #[test]
+    fn test_resolve_action_matches_note_on_binding() {
+        let binding = MidiBinding {
+            message: MidiMessageKind::NoteOn,
+            channel: 0,
+            number: 60,
+            action: MidiAction::Toggle,
+        };
+        let message = parse_midi_message(&[0x90, 60, 100]).unwrap();
+        assert_eq!(resolve_action(&binding, &message), Some(MidiAction::Toggle));
+    }
+
+    #[test]
+    fn test_resolve_action_ignores_different_channel() {
+        let binding = MidiBinding {
+            message: MidiMessageKind::NoteOn,
+            channel: 0,
+            number: 60,
+            action: MidiAction::Toggle,
+        };
+        let message = parse_midi_message(&[0x91, 60, 100]).unwrap();
+        assert_eq!(resolve_action(&binding, &message), None);
+    }
+
+    #[test]
+    fn test_resolve_action_ignores_different_number() {
+        let binding = MidiBinding {
+            message: MidiMessageKind::NoteOn,
+            channel: 0,
+            number: 60,
+            action: MidiAction::Start,
+        };
+        let message = parse_midi_message(&[0x90, 61, 100]).unwrap();
+        assert_eq!(resolve_action(&binding, &message), None);
+    }
+
+    #[test]
+    fn test_resolve_action_control_change_requires_high_value() {
+        let binding = MidiBinding {
+            message: MidiMessageKind::ControlChange,
+            channel: 0,
+            number: 64,
+            action: MidiAction::Stop,
+        };
+        let pressed = parse_midi_message(&[0xB0, 64, 127]).unwrap();
+        assert_eq!(resolve_action(&binding, &pressed), Some(MidiAction::Stop));
+
+        let released = parse_midi_message(&[0xB0, 64, 0]).unwrap();
+        assert_eq!(resolve_action(&binding, &released), None);
+    }
+
+    #[test]
+    fn test_resolve_action_mismatched_message_kind_is_ignored() {
+        let binding = MidiBinding {
+            message: MidiMessageKind::ControlChange,
+            channel: 0,
+            number: 60,
+            action: MidiAction::Toggle,
+        };
+        let message = parse_midi_message(&[0x90, 60, 100]).unwrap();
+        assert_eq!(resolve_action(&binding, &message), None);
+    }
+}
@@ -0,0 +1,722 @@
+//! Playback subsystem for auditioning recordings in-app.
+//!
+//! Mirrors `Recorder`'s shape: a `PlayerState` enum, a `play`/`pause`/
+//! `stop`/`seek` control surface, and a cpal output stream resolved via
+//! `AudioDeviceManager`. Only WAV files are decoded (through `hound`), the
+//! same scope as `recordings::get_waveform` and `upload::transcode_to_mp3`;
+//! any other extension, MP3 included, is rejected with `UnsupportedFormat`.
+//!
+//! The whole file is decoded into memory up front (recordings are short),
+//! then channel-matched and resampled once to the output device's native
+//! config via the same helpers `recorder.rs` uses for capture, so the
+//! output callback only ever does cheap indexing + gain.
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{SampleFormat, Stream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use crate::audio::AudioDeviceManager;
+use crate::recorder::{downmix_to_mono_f32, downmix_to_stereo_f32, Resampler};
+
+/// Playback state, mirroring `RecorderState`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerState {
+    Idle,
+    Playing,
+    Paused,
+}
+
+/// Configuration for the player.
+#[derive(Debug, Clone)]
+pub struct PlayerConfig {
+    /// Directory recordings are played from — `play()` rejects any path
+    /// outside this directory, the same check `recordings::delete_recording`
+    /// applies.
+    pub output_dir: PathBuf,
+    /// Name of the selected output device (None = use default).
+    pub selected_device_name: Option<String>,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            output_dir: crate::recordings::default_output_dir(),
+            selected_device_name: None,
+        }
+    }
+}
+
+/// Errors that can occur during playback.
+#[derive(Debug, Clone)]
+pub enum PlayerError {
+    AlreadyPlaying,
+    NotPlaying,
+    FileNotFound(String),
+    /// The requested path resolved outside `PlayerConfig.output_dir`.
+    OutsideOutputDir,
+    UnsupportedFormat(String),
+    NoDefaultDevice,
+    ConfigError(String),
+    StreamError(String),
+    IoError(String),
+}
+
+impl std::fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyPlaying => write!(f, "already playing"),
+            Self::NotPlaying => write!(f, "not playing"),
+            Self::FileNotFound(path) => write!(f, "file not found: {path}"),
+            Self::OutsideOutputDir => write!(f, "file is outside the recordings directory"),
+            Self::UnsupportedFormat(e) => write!(f, "unsupported audio format: {e}"),
+            Self::NoDefaultDevice => write!(f, "no default output device"),
+            Self::ConfigError(e) => write!(f, "config error: {e}"),
+            Self::StreamError(e) => write!(f, "stream error: {e}"),
+            Self::IoError(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PlayerError {}
+
+/// Decoded PCM audio, already channel-matched and resampled to the output
+/// device's native config, interleaved.
+struct PlaybackShared {
+    samples: Vec<f32>,
+    channels: u16,
+    sample_rate: u32,
+    /// Index (not frame index) of the next sample to write, advanced by the
+    /// output callback and rewound by `seek()`.
+    position: AtomicUsize,
+    /// 0-100.
+    volume: AtomicU8,
+    muted: AtomicBool,
+}
+
+pub struct Player {
+    pub config: PlayerConfig,
+    state: PlayerState,
+    stream: Option<Stream>,
+    shared: Option<Arc<PlaybackShared>>,
+    current_file: Option<PathBuf>,
+}
+
+impl Player {
+    pub fn new(config: PlayerConfig) -> Self {
+        Self {
+            config,
+            state: PlayerState::Idle,
+            stream: None,
+            shared: None,
+            current_file: None,
+        }
+    }
+
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    /// Start playing `file_path` from the beginning on the configured
+    /// output device. If called while `Paused` on the same file, resumes
+    /// instead of re-decoding.
+    pub fn play(
+        &mut self,
+        device_manager: &AudioDeviceManager,
+        file_path: &str,
+    ) -> Result<(), PlayerError> {
+        if self.state == PlayerState::Playing {
+            return Err(PlayerError::AlreadyPlaying);
+        }
+
+        if self.state == PlayerState::Paused {
+            if let Some(current) = &self.current_file {
+                if current == Path::new(file_path) {
+                    if let Some(stream) = &self.stream {
+                        stream
+                            .play()
+                            .map_err(|e| PlayerError::StreamError(e.to_string()))?;
+                    }
+                    self.state = PlayerState::Playing;
+                    return Ok(());
+                }
+            }
+            // Different file while paused — fall through and load fresh,
+            // tearing down the old stream first.
+            self.stream.take();
+            self.shared = None;
+        }
+
+        let path = validate_path(file_path, &self.config.output_dir)?;
+        let decoded = decode_file(&path)?;
+
+        let device = match &self.config.selected_device_name {
+            Some(name) => device_manager
+                .output_device_by_name(name)
+                .or_else(|| device_manager.default_output_device())
+                .ok_or(PlayerError::NoDefaultDevice)?,
+            None => device_manager
+                .default_output_device()
+                .ok_or(PlayerError::NoDefaultDevice)?,
+        };
+
+        let supported_config = AudioDeviceManager::default_output_config(&device)
+            .map_err(|e| PlayerError::ConfigError(e.to_string()))?;
+        let out_channels = supported_config.channels();
+        let out_sample_rate = supported_config.sample_rate().0;
+
+        let matched = match_channels(&decoded.samples, decoded.channels, out_channels);
+        let mut resampler = Resampler::new(decoded.sample_rate, out_sample_rate, out_channels);
+        let resampled = resampler.process(&matched);
+
+        let shared = Arc::new(PlaybackShared {
+            samples: resampled,
+            channels: out_channels,
+            sample_rate: out_sample_rate,
+            position: AtomicUsize::new(0),
+            volume: AtomicU8::new(100),
+            muted: AtomicBool::new(false),
+        });
+
+        let stream = build_playback_stream(&device, supported_config.sample_format(), shared.clone())?;
+
+        self.stream = Some(stream);
+        self.shared = Some(shared);
+        self.current_file = Some(path);
+        self.state = PlayerState::Playing;
+
+        Ok(())
+    }
+
+    /// Pause playback in place. `play()` with the same path resumes.
+    pub fn pause(&mut self) -> Result<(), PlayerError> {
+        if self.state != PlayerState::Playing {
+            return Err(PlayerError::NotPlaying);
+        }
+        if let Some(stream) = &self.stream {
+            stream
+                .pause()
+                .map_err(|e| PlayerError::StreamError(e.to_string()))?;
+        }
+        self.state = PlayerState::Paused;
+        Ok(())
+    }
+
+    /// Stop playback and release the output stream.
+    pub fn stop(&mut self) -> Result<(), PlayerError> {
+        if self.state == PlayerState::Idle {
+            return Err(PlayerError::NotPlaying);
+        }
+        self.stream.take();
+        self.shared = None;
+        self.current_file = None;
+        self.state = PlayerState::Idle;
+        Ok(())
+    }
+
+    /// Seek to `secs` into the currently loaded file, clamped to its
+    /// length. Valid while `Playing` or `Paused`.
+    pub fn seek(&mut self, secs: f64) -> Result<(), PlayerError> {
+        let shared = self.shared.as_ref().ok_or(PlayerError::NotPlaying)?;
+        let channels = shared.channels.max(1) as usize;
+        let target_sample = ((secs.max(0.0) * shared.sample_rate as f64) as usize) * channels;
+        shared
+            .position
+            .store(target_sample.min(shared.samples.len()), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Current playback position in seconds, or 0.0 when nothing is loaded.
+    pub fn position_secs(&self) -> f64 {
+        let Some(shared) = &self.shared else {
+            return 0.0;
+        };
+        let channels = shared.channels.max(1) as usize;
+        let frame = shared.position.load(Ordering::Relaxed) / channels;
+        frame as f64 / shared.sample_rate as f64
+    }
+
+    /// Set output volume, 0-100. Values above 100 are clamped.
+    pub fn set_volume(&mut self, volume: u8) {
+        if let Some(shared) = &self.shared {
+            shared.volume.store(volume.min(100), Ordering::Relaxed);
+        }
+    }
+
+    /// Mute or unmute output without resetting volume.
+    pub fn set_mute(&mut self, muted: bool) {
+        if let Some(shared) = &self.shared {
+            shared.muted.store(muted, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Commands accepted by the actor thread spawned by `spawn_player_actor`.
+/// Mirrors `recorder::RecorderCommand`: the tray only ever holds a
+/// `Sender<PlayerCommand>`, so the `Player` and its `cpal::Stream` never
+/// leave the thread that owns them.
+pub enum PlayerCommand {
+    Play(PathBuf),
+    Stop,
+    Quit,
+}
+
+/// Status pushed back from the actor thread after handling a
+/// `PlayerCommand`, so the main thread can update the tray menu without
+/// ever touching the `Player` itself.
+#[derive(Debug, Clone)]
+pub enum PlayerStatus {
+    Started(PathBuf),
+    Stopped,
+    Error(String),
+    StateChanged(PlayerState),
+}
+
+/// Handle to the actor thread spawned by `spawn_player_actor`: a command
+/// sender and the matching status receiver.
+pub struct PlayerHandle {
+    pub commands: mpsc::Sender<PlayerCommand>,
+    pub status: mpsc::Receiver<PlayerStatus>,
+}
+
+/// Spawn a dedicated thread that owns a `Player` outright and drives it from
+/// `PlayerCommand`s received on a channel, reporting back over a second
+/// channel -- the same actor pattern `recorder::spawn_actor` uses so the
+/// tray never needs an `unsafe impl Send`/`Sync` to hold a `cpal::Stream`.
+///
+/// Like `recorder::spawn_actor`, every command gets exactly one reply:
+/// `Play`/`Stop` answer with their own `Started`/`Stopped`/`Error`, and
+/// `Quit` answers with a single `StateChanged`. Callers only ever need one
+/// `recv()` per command sent.
+pub fn spawn_player_actor(config: PlayerConfig, device_manager: AudioDeviceManager) -> PlayerHandle {
+    let (cmd_tx, cmd_rx) = mpsc::channel::<PlayerCommand>();
+    let (status_tx, status_rx) = mpsc::channel::<PlayerStatus>();
+
+    thread::spawn(move || {
+        let mut player = Player::new(config);
+
+        for command in cmd_rx {
+            match command {
+                PlayerCommand::Play(path) => {
+                    match player.play(&device_manager, &path.to_string_lossy()) {
+                        Ok(()) => {
+                            let _ = status_tx.send(PlayerStatus::Started(path));
+                        }
+                        Err(e) => {
+                            let _ = status_tx.send(PlayerStatus::Error(e.to_string()));
+                        }
+                    }
+                }
+                PlayerCommand::Stop => match player.stop() {
+                    Ok(()) => {
+                        let _ = status_tx.send(PlayerStatus::Stopped);
+                    }
+                    Err(e) => {
+                        let _ = status_tx.send(PlayerStatus::Error(e.to_string()));
+                    }
+                },
+                PlayerCommand::Quit => {
+                    let _ = player.stop();
+                    let _ = status_tx.send(PlayerStatus::StateChanged(player.state()));
+                    break;
+                }
+            }
+        }
+    });
+
+    PlayerHandle {
+        commands: cmd_tx,
+        status: status_rx,
+    }
+}
+
+/// Validate that `file_path` resolves to a file inside `output_dir` — the
+/// same check `recordings::delete_recording` applies.
+fn validate_path(file_path: &str, output_dir: &Path) -> Result<PathBuf, PlayerError> {
+    let path = PathBuf::from(file_path);
+
+    let canonical_output = output_dir
+        .canonicalize()
+        .map_err(|e| PlayerError::ConfigError(format!("invalid output directory: {e}")))?;
+    let canonical_file = path
+        .canonicalize()
+        .map_err(|_| PlayerError::FileNotFound(file_path.to_string()))?;
+
+    if !canonical_file.starts_with(&canonical_output) {
+        return Err(PlayerError::OutsideOutputDir);
+    }
+
+    Ok(canonical_file)
+}
+
+/// Fully decoded PCM audio read from disk, still at the file's own
+/// sample rate/channel count.
+struct DecodedAudio {
+    samples: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+/// Decode `path` based on its extension. Only WAV is decodable; every
+/// other extension returns `UnsupportedFormat`.
+fn decode_file(path: &Path) -> Result<DecodedAudio, PlayerError> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "wav" => decode_wav(path),
+        other => Err(PlayerError::UnsupportedFormat(format!(
+            "{other} decoding is not supported — only WAV files can be played"
+        ))),
+    }
+}
+
+fn decode_wav(path: &Path) -> Result<DecodedAudio, PlayerError> {
+    let mut reader = hound::WavReader::open(path).map_err(|e| PlayerError::IoError(e.to_string()))?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .filter_map(Result::ok)
+            .collect(),
+        hound::SampleFormat::Int => {
+            let peak_scale = (1i64 << spec.bits_per_sample.saturating_sub(1).max(1)) as f32;
+            reader
+                .samples::<i32>()
+                .filter_map(Result::ok)
+                .map(|s| (s as f32 / peak_scale).clamp(-1.0, 1.0))
+                .collect()
+        }
+    };
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+    })
+}
+
+/// Convert interleaved `samples` at `from_channels` to `to_channels`,
+/// reusing the recorder's downmix helpers for the common 1/2-channel
+/// output cases and falling back to a mono-duplicated signal for anything
+/// else (uncommon device channel counts aren't worth guessing a layout
+/// for).
+fn match_channels(samples: &[f32], from_channels: u16, to_channels: u16) -> Vec<f32> {
+    if from_channels == to_channels {
+        return samples.to_vec();
+    }
+    match to_channels {
+        1 => downmix_to_mono_f32(samples, from_channels),
+        2 => downmix_to_stereo_f32(samples, from_channels),
+        _ => {
+            let mono = downmix_to_mono_f32(samples, from_channels);
+            mono.iter()
+                .flat_map(|&s| std::iter::repeat(s).take(to_channels as usize))
+                .collect()
+        }
+    }
+}
+
+/// Read the next `gain`-scaled sample for the output callback, advancing
+/// `position` by one. Past the end of `samples` this reads as silence
+/// (and leaves `position` pinned at the end) rather than looping or
+/// erroring.
+fn next_sample(shared: &PlaybackShared, gain: f32) -> f32 {
+    let pos = shared.position.load(Ordering::Relaxed);
+    let sample = shared.samples.get(pos).copied().unwrap_or(0.0);
+    shared
+        .position
+        .store((pos + 1).min(shared.samples.len()), Ordering::Relaxed);
+    sample * gain
+}
+
+fn current_gain(shared: &PlaybackShared) -> f32 {
+    if shared.muted.load(Ordering::Relaxed) {
+        0.0
+    } else {
+        shared.volume.load(Ordering::Relaxed) as f32 / 100.0
+    }
+}
+
+/// Build and start a cpal output stream on `device` that streams `shared`'s
+/// pre-resampled samples, applying volume/mute per-sample.
+fn build_playback_stream(
+    device: &cpal::Device,
+    sample_format: SampleFormat,
+    shared: Arc<PlaybackShared>,
+) -> Result<Stream, PlayerError> {
+    let supported_config = AudioDeviceManager::default_output_config(device)
+        .map_err(|e| PlayerError::ConfigError(e.to_string()))?;
+    let config = supported_config.into();
+
+    let err_fn = |err: cpal::StreamError| eprintln!("playback stream error: {err}");
+
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _| {
+                let gain = current_gain(&shared);
+                for out in data.iter_mut() {
+                    *out = next_sample(&shared, gain);
+                }
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::I16 => device.build_output_stream(
+            &config,
+            move |data: &mut [i16], _| {
+                let gain = current_gain(&shared);
+                for out in data.iter_mut() {
+                    *out = (next_sample(&shared, gain) * i16::MAX as f32) as i16;
+                }
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::U16 => device.build_output_stream(
+            &config,
+            move |data: &mut [u16], _| {
+                let gain = current_gain(&shared);
+                for out in data.iter_mut() {
+                    *out = ((next_sample(&shared, gain) * 32768.0) + 32768.0) as u16;
+                }
+            },
+            err_fn,
+            None,
+        ),
+        _ => return Err(PlayerError::UnsupportedFormat(format!("{sample_format:?}"))),
+    }
+    .map_err(|e| PlayerError::StreamError(e.to_string()))?;
+
+    stream
+        .play()
+        .map_err(|e| PlayerError::StreamError(e.to_string()))?;
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_test_wav(dir: &Path, name: &str, sample_rate: u32, secs: f64) -> PathBuf {
+        let path = dir.join(name);
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        let total_samples = (sample_rate as f64 * secs) as usize;
+        for i in 0..total_samples {
+            let t = i as f32 / sample_rate as f32;
+            let sample = (t * 440.0 * std::f32::consts::TAU).sin();
+            writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    fn unique_test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "lyre-player-test-{name}-{:?}",
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_default_config_points_at_output_dir() {
+        let config = PlayerConfig::default();
+        assert!(config.selected_device_name.is_none());
+        assert_eq!(config.output_dir, crate::recordings::default_output_dir());
+    }
+
+    #[test]
+    fn test_player_initial_state() {
+        let player = Player::new(PlayerConfig::default());
+        assert_eq!(player.state(), PlayerState::Idle);
+    }
+
+    #[test]
+    fn test_stop_when_not_playing() {
+        let mut player = Player::new(PlayerConfig::default());
+        assert!(matches!(player.stop(), Err(PlayerError::NotPlaying)));
+    }
+
+    #[test]
+    fn test_pause_when_not_playing() {
+        let mut player = Player::new(PlayerConfig::default());
+        assert!(matches!(player.pause(), Err(PlayerError::NotPlaying)));
+    }
+
+    #[test]
+    fn test_seek_when_not_loaded() {
+        let mut player = Player::new(PlayerConfig::default());
+        assert!(matches!(player.seek(1.0), Err(PlayerError::NotPlaying)));
+    }
+
+    #[test]
+    fn test_play_outside_output_dir_is_rejected() {
+        let output_dir = unique_test_dir("rejected-output");
+        let outside_dir = unique_test_dir("rejected-outside");
+        let path = write_test_wav(&outside_dir, "clip.wav", 8000, 0.1);
+
+        let mut player = Player::new(PlayerConfig {
+            output_dir,
+            selected_device_name: None,
+        });
+        let device_manager = AudioDeviceManager::new();
+        let result = player.play(&device_manager, path.to_str().unwrap());
+        assert!(matches!(result, Err(PlayerError::OutsideOutputDir)));
+    }
+
+    #[test]
+    fn test_decode_wav_reads_spec_and_samples() {
+        let dir = unique_test_dir("decode");
+        let path = write_test_wav(&dir, "clip.wav", 8000, 0.5);
+        let decoded = decode_wav(&path).unwrap();
+        assert_eq!(decoded.sample_rate, 8000);
+        assert_eq!(decoded.channels, 1);
+        assert_eq!(decoded.samples.len(), 4000);
+    }
+
+    #[test]
+    fn test_decode_file_rejects_mp3() {
+        let dir = unique_test_dir("mp3-reject");
+        let path = dir.join("clip.mp3");
+        std::fs::File::create(&path).unwrap().write_all(b"not really mp3").unwrap();
+        let result = decode_file(&path);
+        assert!(matches!(result, Err(PlayerError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn test_match_channels_mono_to_stereo_duplicates() {
+        let out = match_channels(&[0.5, -0.5], 1, 2);
+        assert_eq!(out, vec![0.5, 0.5, -0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_match_channels_same_count_is_passthrough() {
+        let out = match_channels(&[0.1, 0.2, 0.3, 0.4], 2, 2);
+        assert_eq!(out, vec![0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_next_sample_applies_gain_and_advances_position() {
+        let shared = PlaybackShared {
+            samples: vec![0.5, 1.0],
+            channels: 1,
+            sample_rate: 8000,
+            position: AtomicUsize::new(0),
+            volume: AtomicU8::new(100),
+            muted: AtomicBool::new(false),
+        };
+        assert_eq!(next_sample(&shared, 0.5), 0.25);
+        assert_eq!(next_sample(&shared, 0.5), 0.5);
+        // Past the end, reads as silence rather than panicking.
+        assert_eq!(next_sample(&shared, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_current_gain_respects_mute() {
+        let shared = PlaybackShared {
+            samples: vec![],
+            channels: 1,
+            sample_rate: 8000,
+            position: AtomicUsize::new(0),
+            volume: AtomicU8::new(50),
+            muted: AtomicBool::new(false),
+        };
+        assert_eq!(current_gain(&shared), 0.5);
+        shared.muted.store(true, Ordering::Relaxed);
+        assert_eq!(current_gain(&shared), 0.0);
+    }
+
+    #[test]
+    fn test_seek_clamps_to_sample_count() {
+        let mut player = Player::new(PlayerConfig::default());
+        player.shared = Some(Arc::new(PlaybackShared {
+            samples: vec![0.0; 8000],
+            channels: 1,
+            sample_rate: 8000,
+            position: AtomicUsize::new(0),
+            volume: AtomicU8::new(100),
+            muted: AtomicBool::new(false),
+        }));
+        player.seek(0.5).unwrap();
+        assert_eq!(
+            player.shared.as_ref().unwrap().position.load(Ordering::Relaxed),
+            4000
+        );
+        // Past the end of the file clamps rather than over-seeking.
+        player.seek(10.0).unwrap();
+        assert_eq!(
+            player.shared.as_ref().unwrap().position.load(Ordering::Relaxed),
+            8000
+        );
+    }
+
+    #[test]
+    fn test_position_secs_reports_zero_when_idle() {
+        let player = Player::new(PlayerConfig::default());
+        assert_eq!(player.position_secs(), 0.0);
+    }
+
+    #[test]
+    fn test_spawn_player_actor_stop_when_idle_reports_error() {
+        let handle = spawn_player_actor(PlayerConfig::default(), AudioDeviceManager::new());
+
+        handle.commands.send(PlayerCommand::Stop).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            PlayerStatus::Error(_)
+        ));
+        // `Stop` replies with exactly one message -- confirm there's no
+        // second, unread reply still sitting in the channel before `Quit`
+        // sends its own `StateChanged`.
+        assert!(handle.status.try_recv().is_err());
+
+        handle.commands.send(PlayerCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            PlayerStatus::StateChanged(PlayerState::Idle)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_player_actor_play_outside_output_dir_reports_error() {
+        let output_dir = unique_test_dir("actor-rejected-output");
+        let outside_dir = unique_test_dir("actor-rejected-outside");
+        let path = write_test_wav(&outside_dir, "clip.wav", 8000, 0.1);
+
+        let handle = spawn_player_actor(
+            PlayerConfig {
+                output_dir,
+                selected_device_name: None,
+            },
+            AudioDeviceManager::new(),
+        );
+
+        handle.commands.send(PlayerCommand::Play(path)).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            PlayerStatus::Error(_)
+        ));
+
+        handle.commands.send(PlayerCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            PlayerStatus::StateChanged(PlayerState::Idle)
+        ));
+    }
+}
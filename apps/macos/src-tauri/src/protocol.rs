@@ -0,0 +1,236 @@
+//! Custom `lyre://recording/<file>` URI scheme that streams a local
+//! recording off disk, with HTTP Range support so the frontend can use a
+//! seekable `<audio>` element instead of loading the whole file up front.
+//!
+//! Registered on the `tauri::Builder` in `main()` via
+//! `register_asynchronous_uri_scheme_protocol` before the app starts, so it
+//! is available as soon as any webview loads. The OS-level scheme
+//! association (e.g. `tauri.conf.json`'s `bundle.macOS.entitlements` /
+//! `Info.plist` `CFBundleURLTypes`) is a packaging concern, not this module.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::UriSchemeContext;
+
+/// Register the `lyre://` scheme handler on the app builder.
+pub fn register<R: tauri::Runtime>(
+    builder: tauri::Builder<R>,
+) -> tauri::Builder<R> {
+    builder.register_asynchronous_uri_scheme_protocol("lyre", |_ctx: UriSchemeContext<R>, request, responder| {
+        responder.respond(handle_request(&request));
+    })
+}
+
+/// Resolve and stream the recording referenced by `lyre://recording/<file>`.
+/// Any other host is rejected; the path component is resolved against
+/// `config::get_output_dir()` and any attempt to escape it is rejected.
+fn handle_request(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let uri = request.uri();
+    if uri.host() != Some("recording") {
+        return error_response(StatusCode::NOT_FOUND, "unknown lyre:// host");
+    }
+
+    let requested = uri.path().trim_start_matches('/');
+    let Ok(requested) = percent_decode(requested) else {
+        return error_response(StatusCode::BAD_REQUEST, "invalid path encoding");
+    };
+
+    let output_dir = crate::config::get_output_dir();
+    let file_path = match resolve_within(&output_dir, &requested) {
+        Some(path) => path,
+        None => return error_response(StatusCode::FORBIDDEN, "path escapes recordings directory"),
+    };
+
+    let Ok(mut file) = File::open(&file_path) else {
+        return error_response(StatusCode::NOT_FOUND, "recording not found");
+    };
+    let Ok(metadata) = file.metadata() else {
+        return error_response(StatusCode::NOT_FOUND, "recording not found");
+    };
+    let total_len = metadata.len();
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_range(v, total_len));
+
+    let (start, end) = match range {
+        Some(Some(range)) => range,
+        Some(None) => return error_response(StatusCode::RANGE_NOT_SATISFIABLE, "invalid range"),
+        None => (0, total_len.saturating_sub(1)),
+    };
+
+    let chunk_len = end - start + 1;
+    let mut buf = vec![0u8; chunk_len as usize];
+    if file.seek(SeekFrom::Start(start)).is_err() || file.read_exact(&mut buf).is_err() {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "failed to read recording");
+    }
+
+    let content_type = content_type_for(&file_path);
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, chunk_len.to_string());
+
+    if request.headers().contains_key(header::RANGE) {
+        response = response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{end}/{total_len}"),
+            );
+    } else {
+        response = response.status(StatusCode::OK);
+    }
+
+    response.body(buf).unwrap_or_else(|_| {
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "failed to build response")
+    })
+}
+
+/// Resolve `requested` (a relative path from the URI) against `base`,
+/// rejecting anything that canonicalizes outside of it.
+fn resolve_within(base: &Path, requested: &str) -> Option<PathBuf> {
+    let candidate = base.join(requested);
+    let canonical_base = base.canonicalize().ok()?;
+    let canonical_candidate = candidate.canonicalize().ok()?;
+    canonical_candidate
+        .starts_with(&canonical_base)
+        .then_some(canonical_candidate)
+}
+
+/// Parse a `Range: bytes=start-end` header against a known total length.
+/// Returns `None` if there's no recognizable range, `Some(None)` if the
+/// range is unsatisfiable, or `Some(Some((start, end)))` (end inclusive).
+fn parse_range(header: &str, total_len: u64) -> Option<Option<(u64, u64)>> {
+    let spec = header.strip_prefix("bytes=")?;
+    let (start_str, end_str) = spec.split_once('-')?;
+
+    if total_len == 0 {
+        return Some(None);
+    }
+
+    let (start, end) = if start_str.is_empty() {
+        // Suffix range: last N bytes.
+        let suffix_len: u64 = end_str.parse().ok()?;
+        let start = total_len.saturating_sub(suffix_len);
+        (start, total_len - 1)
+    } else {
+        let start: u64 = start_str.parse().ok()?;
+        let end = if end_str.is_empty() {
+            total_len - 1
+        } else {
+            end_str.parse().ok()?
+        };
+        (start, end)
+    };
+
+    if start > end || end >= total_len {
+        return Some(None);
+    }
+    Some(Some((start, end)))
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        ext if ext.eq_ignore_ascii_case("mp3") => "audio/mpeg",
+        ext if ext.eq_ignore_ascii_case("wav") => "audio/wav",
+        ext if ext.eq_ignore_ascii_case("m4a") || ext.eq_ignore_ascii_case("aac") => "audio/mp4",
+        ext if ext.eq_ignore_ascii_case("ogg") => "audio/ogg",
+        ext if ext.eq_ignore_ascii_case("flac") => "audio/flac",
+        _ => "application/octet-stream",
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String, std::string::FromUtf8Error> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut chars = s.bytes();
+    while let Some(b) = chars.next() {
+        if b == b'%' {
+            let hi = chars.next();
+            let lo = chars.next();
+            if let (Some(hi), Some(lo)) = (hi, lo) {
+                if let (Some(hi), Some(lo)) = (hex_digit(hi), hex_digit(lo)) {
+                    bytes.push(hi * 16 + lo);
+                    continue;
+                }
+            }
+        } else {
+            bytes.push(b);
+            continue;
+        }
+    }
+    String::from_utf8(bytes)
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_basic() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Some((0, 99))));
+    }
+
+    #[test]
+    fn test_parse_range_open_ended() {
+        assert_eq!(parse_range("bytes=500-", 1000), Some(Some((500, 999))));
+    }
+
+    #[test]
+    fn test_parse_range_suffix() {
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Some((900, 999))));
+    }
+
+    #[test]
+    fn test_parse_range_unsatisfiable() {
+        assert_eq!(parse_range("bytes=2000-3000", 1000), Some(None));
+    }
+
+    #[test]
+    fn test_parse_range_malformed_returns_none() {
+        assert_eq!(parse_range("not-a-range", 1000), None);
+    }
+
+    #[test]
+    fn test_resolve_within_rejects_escape() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("inside.wav"), b"data").unwrap();
+        assert!(resolve_within(tmp.path(), "inside.wav").is_some());
+        assert!(resolve_within(tmp.path(), "../../etc/passwd").is_none());
+    }
+
+    #[test]
+    fn test_content_type_for_known_extensions() {
+        assert_eq!(content_type_for(Path::new("a.mp3")), "audio/mpeg");
+        assert_eq!(content_type_for(Path::new("a.wav")), "audio/wav");
+        assert_eq!(content_type_for(Path::new("a.xyz")), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_percent_decode_roundtrip() {
+        assert_eq!(percent_decode("recording%20one.wav").unwrap(), "recording one.wav");
+        assert_eq!(percent_decode("plain.wav").unwrap(), "plain.wav");
+    }
+}
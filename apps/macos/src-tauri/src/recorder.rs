@@ -1,18 +1,46 @@
 use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::{SampleFormat, Stream};
-use mp3lame_encoder::{Builder, Encoder, FlushNoGap, MonoPcm};
+use mp3lame_encoder::{Builder, DualPcm, Encoder, FlushNoGap, MonoPcm};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::audio::AudioDeviceManager;
+use crate::midi::MidiBinding;
+use crate::upload::UploadOptions;
+use crate::upload_queue;
 
 /// Recording state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RecorderState {
     Idle,
     Recording,
+    /// The input device disconnected mid-recording and `poll_interrupted`
+    /// hasn't yet resolved it (either back to `Recording` via reconnect, or
+    /// the recorder has since been stopped).
+    Interrupted,
+}
+
+/// What to do when the active input device disconnects mid-recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDeviceLost {
+    /// Rebind to the system default input device and keep recording to the
+    /// same file.
+    Reconnect,
+    /// Stop recording and close the file.
+    Stop,
+}
+
+impl Default for OnDeviceLost {
+    fn default() -> Self {
+        Self::Reconnect
+    }
 }
 
 /// Configuration for the recorder.
@@ -22,6 +50,56 @@ pub struct RecorderConfig {
     pub output_dir: PathBuf,
     /// Name of the selected input device (None = use default).
     pub selected_device_name: Option<String>,
+    /// Output encoding for new recordings.
+    pub format: RecordFormat,
+    /// Behavior when the active input device disconnects mid-recording.
+    pub on_device_lost: OnDeviceLost,
+    /// Resample captured audio to this rate before encoding (None = encode
+    /// at whatever rate the device reports). Useful for devices that report
+    /// rates LAME handles poorly (e.g. 96000), or to shrink output files.
+    pub target_sample_rate: Option<u32>,
+    /// How the device's captured channels are reduced to what's encoded.
+    pub channels: ChannelMode,
+    /// Which audio sources are captured and mixed into the recording.
+    pub capture: CaptureMode,
+    /// If set, `stop()` discards the recording (deleting the output file)
+    /// instead of saving it when the captured audio's RMS level never rose
+    /// above this threshold, in dBFS (e.g. `-50.0`). A recording with no
+    /// captured frames at all is always discarded, regardless of this
+    /// setting. Defaults to a near-silent cutoff so junk clips from an
+    /// accidental toggle don't pile up in `list_recordings` unasked.
+    pub silence_threshold_db: Option<f32>,
+    /// If set, `stop()` discards the recording when its wall-clock duration
+    /// (time between `start()` and `stop()`) fell below this many
+    /// milliseconds, on the assumption that it's an accidental tap rather
+    /// than a real recording. Defaults to ~300ms.
+    pub min_duration_ms: Option<u64>,
+    /// If set, a MIDI Note-On/Control-Change message that `midi::MidiControl`
+    /// can use to drive this recorder hands-free (e.g. from a foot pedal).
+    /// The binding itself is just data here -- wiring it up to an open MIDI
+    /// port is the caller's responsibility, the same way `selected_device_name`
+    /// only names a device rather than opening one.
+    pub midi_binding: Option<MidiBinding>,
+    /// If true, a recording kept by `stop()` (i.e. not
+    /// `StopOutcome::DiscardedSilence`) is immediately queued for background
+    /// upload via `upload_queue::enqueue_upload`. The `server_url`/`token`
+    /// from `config::load_config` are read only when the queue is drained, so
+    /// a missing server configuration does not make queueing fail. Queueing
+    /// failures (e.g. the queue file can't be written) are logged and
+    /// otherwise ignored -- `stop()`
+    /// still reports the recording as saved either way.
+    pub auto_upload: bool,
+    /// If set, `start()` records in segmented mode instead of writing one
+    /// monolithic file: a session directory is created under `output_dir`
+    /// holding a sequence of fixed-duration segment files (e.g. 10s each)
+    /// plus a `playlist.m3u8` manifest that's rewritten every time a
+    /// segment closes. Lets a long-running capture be consumed
+    /// incrementally and recovered if the process dies mid-session, since
+    /// finalized segments and the manifest covering them stay valid
+    /// regardless of what happens to the in-progress one. `auto_upload`
+    /// doesn't apply to segmented recordings today -- there's no single
+    /// file to hand `upload_queue::enqueue_upload`.
+    pub segment_duration: Option<Duration>,
 }
 
 impl Default for RecorderConfig {
@@ -33,30 +111,735 @@ impl Default for RecorderConfig {
         Self {
             output_dir,
             selected_device_name: None,
+            format: RecordFormat::default(),
+            on_device_lost: OnDeviceLost::default(),
+            target_sample_rate: None,
+            channels: ChannelMode::default(),
+            capture: CaptureMode::default(),
+            silence_threshold_db: Some(-45.0),
+            min_duration_ms: Some(300),
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
+        }
+    }
+}
+
+/// Which audio sources are captured and mixed into the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureMode {
+    /// Capture the selected input device only (the existing behavior).
+    Single,
+    /// Mix the selected input device with a second input stream opened on a
+    /// system loopback/monitor device (e.g. a virtual audio driver or macOS
+    /// aggregate device), following the aggregate-device technique used by
+    /// the cubeb-coreaudio backend: each source is captured into its own
+    /// ring buffer, and the encoder thread sums them with independent gain
+    /// before the existing encode path.
+    MixWithSystemAudio {
+        /// Name of the loopback/monitor device, resolved the same way as
+        /// `RecorderConfig.selected_device_name`.
+        system_device: String,
+        /// Gain applied to the mic source before summing.
+        mic_gain: f32,
+        /// Gain applied to the system-audio source before summing.
+        system_gain: f32,
+    },
+}
+
+impl Default for CaptureMode {
+    fn default() -> Self {
+        Self::Single
+    }
+}
+
+/// How the device's captured channels are reduced to what's actually
+/// encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Downmix everything to a single channel (voice recording default).
+    Mono,
+    /// Mix down to a stereo pair, preserving the stereo image.
+    Stereo,
+    /// Extract a single hardware input channel (0-indexed) as mono —
+    /// useful for a mic on, say, input 3 of a multi-input interface.
+    FromChannel(usize),
+}
+
+impl Default for ChannelMode {
+    fn default() -> Self {
+        Self::Mono
+    }
+}
+
+impl ChannelMode {
+    fn out_channels(&self) -> u16 {
+        match self {
+            Self::Mono | Self::FromChannel(_) => 1,
+            Self::Stereo => 2,
         }
     }
 }
 
-/// Shared MP3 writer state passed into the audio stream callback.
-struct Mp3Writer {
+/// Output encoding for new recordings.
+#[derive(Clone, Copy)]
+pub enum RecordFormat {
+    /// LAME-encoded MP3 (lossy, small files).
+    Mp3 {
+        bitrate: mp3lame_encoder::Bitrate,
+        quality: mp3lame_encoder::Quality,
+    },
+    /// Uncompressed WAV (lossless, larger files) via `hound`.
+    Wav,
+    /// FLAC (lossless, compressed) via `flacenc`, for archival-quality
+    /// capture or downstream DSP that wants exact samples without WAV's
+    /// file size.
+    Flac,
+}
+
+impl std::fmt::Debug for RecordFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mp3 { .. } => write!(f, "Mp3"),
+            Self::Wav => write!(f, "Wav"),
+            Self::Flac => write!(f, "Flac"),
+        }
+    }
+}
+
+impl Default for RecordFormat {
+    fn default() -> Self {
+        Self::Mp3 {
+            bitrate: mp3lame_encoder::Bitrate::Kbps192,
+            quality: mp3lame_encoder::Quality::Best,
+        }
+    }
+}
+
+impl RecordFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 { .. } => "mp3",
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+        }
+    }
+
+    /// Stable identifier used to persist the format choice (in `AppConfig`)
+    /// and to build tray menu item ids -- unlike `extension()`, this never
+    /// needs to change even if a future format shares an extension.
+    pub fn key(&self) -> &'static str {
+        self.extension()
+    }
+
+    /// Resolve a persisted/menu `key()` back into a `RecordFormat`, using the
+    /// default bitrate/quality for `Mp3`. Returns `None` for an unrecognized
+    /// key so callers can fall back to `RecordFormat::default()`.
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "wav" => Some(Self::Wav),
+            "flac" => Some(Self::Flac),
+            "mp3" => Some(Self::default()),
+            _ => None,
+        }
+    }
+}
+
+/// Destination for interleaved f32 PCM samples (1 or 2 channels, per the
+/// sink's configured channel count). Implemented once per `RecordFormat` so
+/// the encoder thread doesn't need to know which codec it's writing to.
+trait SampleSink: Send {
+    fn write_samples(&mut self, samples: &[f32]);
+    fn finalize(self: Box<Self>);
+}
+
+/// MP3 sink wrapping the LAME encoder — owned by the encoder thread, never
+/// touched from the audio callback.
+struct Mp3Sink {
     encoder: Encoder,
     file: BufWriter<File>,
+    channels: u16,
 }
 
-/// Core recorder that captures audio from an input device to an MP3 file.
+impl SampleSink for Mp3Sink {
+    fn write_samples(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let mut mp3_buf = Vec::new();
+        let encoded = if self.channels == 1 {
+            mp3_buf.reserve(mp3lame_encoder::max_required_buffer_size(samples.len()));
+            self.encoder
+                .encode(MonoPcm(samples), mp3_buf.spare_capacity_mut())
+        } else {
+            // LAME wants each channel as its own contiguous slice, but our
+            // pipeline carries interleaved frames end-to-end, so split here.
+            let frames = samples.len() / 2;
+            let mut left = Vec::with_capacity(frames);
+            let mut right = Vec::with_capacity(frames);
+            for frame in samples.chunks_exact(2) {
+                left.push(frame[0]);
+                right.push(frame[1]);
+            }
+            mp3_buf.reserve(mp3lame_encoder::max_required_buffer_size(frames));
+            self.encoder.encode(
+                DualPcm {
+                    left: &left,
+                    right: &right,
+                },
+                mp3_buf.spare_capacity_mut(),
+            )
+        };
+
+        match encoded {
+            Ok(encoded_size) => {
+                unsafe { mp3_buf.set_len(encoded_size) };
+                let _ = self.file.write_all(&mp3_buf);
+            }
+            Err(e) => {
+                eprintln!("mp3 encode error: {e:?}");
+            }
+        }
+    }
+
+    fn finalize(self: Box<Self>) {
+        let mut this = *self;
+        let mut flush_buf = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(0));
+        if let Ok(flush_size) = this
+            .encoder
+            .flush::<FlushNoGap>(flush_buf.spare_capacity_mut())
+        {
+            unsafe { flush_buf.set_len(flush_size) };
+            let _ = this.file.write_all(&flush_buf);
+        }
+        let _ = this.file.flush();
+    }
+}
+
+/// WAV sink writing a proper header via `hound::WavWriter`, for lossless
+/// archival recordings.
+struct WavSink {
+    writer: hound::WavWriter<BufWriter<File>>,
+}
+
+impl SampleSink for WavSink {
+    fn write_samples(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let scaled = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            if let Err(e) = self.writer.write_sample(scaled) {
+                eprintln!("wav write error: {e}");
+                break;
+            }
+        }
+    }
+
+    fn finalize(self: Box<Self>) {
+        if let Err(e) = self.writer.finalize() {
+            eprintln!("failed to finalize wav file: {e}");
+        }
+    }
+}
+
+/// FLAC sink via `flacenc`. Unlike `Mp3Sink`/`WavSink`, `flacenc` has no
+/// incremental writer that takes a file handle directly -- it encodes a
+/// whole in-memory source at once -- so this buffers converted PCM samples
+/// in memory and only does the actual encode + file write on `finalize`.
+/// Fine for a typical recording's length, but unlike the streaming codecs
+/// memory use scales with recording duration.
+struct FlacSink {
+    path: PathBuf,
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<i32>,
+}
+
+impl SampleSink for FlacSink {
+    fn write_samples(&mut self, samples: &[f32]) {
+        self.samples
+            .extend(samples.iter().map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i32));
+    }
+
+    fn finalize(self: Box<Self>) {
+        let this = *self;
+
+        let config = match flacenc::config::Encoder::default().into_verified() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("flac encoder config invalid: {e:?}");
+                return;
+            }
+        };
+
+        let source = flacenc::source::MemSource::from_samples(
+            &this.samples,
+            this.channels as usize,
+            16,
+            this.sample_rate as usize,
+        );
+
+        let stream =
+            match flacenc::encode_with_fixed_block_size(&config, source, config.block_size) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("flac encode error for {}: {e:?}", this.path.display());
+                    return;
+                }
+            };
+
+        let mut sink = flacenc::bitsink::ByteSink::new();
+        if let Err(e) = stream.write(&mut sink) {
+            eprintln!(
+                "failed to serialize flac stream for {}: {e:?}",
+                this.path.display()
+            );
+            return;
+        }
+
+        if let Err(e) = fs::write(&this.path, sink.as_slice()) {
+            eprintln!("failed to write flac file {}: {e}", this.path.display());
+        }
+    }
+}
+
+/// Build the sample sink for `format`, creating the output file at `path`.
+/// `channels` is the number of interleaved channels the sink will receive
+/// per frame (1 or 2, per `ChannelMode::out_channels`).
+fn build_sink(
+    format: &RecordFormat,
+    path: &PathBuf,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<Box<dyn SampleSink>, RecordError> {
+    match *format {
+        RecordFormat::Mp3 { bitrate, quality } => {
+            let mut builder = Builder::new()
+                .ok_or_else(|| RecordError::EncoderError("failed to create LAME builder".into()))?;
+            builder
+                .set_num_channels(channels as u8)
+                .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
+            builder
+                .set_sample_rate(sample_rate)
+                .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
+            builder
+                .set_brate(bitrate)
+                .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
+            builder
+                .set_quality(quality)
+                .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
+
+            let encoder = builder
+                .build()
+                .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
+
+            let file = File::create(path).map_err(|e| RecordError::IoError(e.to_string()))?;
+            let file = BufWriter::new(file);
+
+            Ok(Box::new(Mp3Sink {
+                encoder,
+                file,
+                channels,
+            }))
+        }
+        RecordFormat::Wav => {
+            let spec = hound::WavSpec {
+                channels,
+                sample_rate,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let writer = hound::WavWriter::create(path, spec)
+                .map_err(|e| RecordError::IoError(e.to_string()))?;
+            Ok(Box::new(WavSink { writer }))
+        }
+        RecordFormat::Flac => Ok(Box::new(FlacSink {
+            path: path.clone(),
+            sample_rate,
+            channels,
+            samples: Vec::new(),
+        })),
+    }
+}
+
+/// Name of the rolling playlist manifest written alongside a segmented
+/// recording's segment files, inside its session directory.
+const PLAYLIST_FILENAME: &str = "playlist.m3u8";
+
+/// Filename for segment `index` (1-based) of a segmented recording with
+/// basename `stem` -- the same `generate_filename` stem, a zero-padded
+/// segment index, and `format`'s extension.
+fn segment_filename(stem: &str, index: u32, format: &RecordFormat) -> String {
+    format!("{stem}-{index:04}.{}", format.extension())
+}
+
+/// A `SampleSink` decorator that rolls over to a fresh segment file every
+/// `segment_duration` worth of samples instead of writing one monolithic
+/// file, rewriting an M3U8-style playlist manifest each time a segment
+/// closes. Implemented as a `SampleSink` itself so `run_encoder_thread`
+/// and `run_mixed_encoder_thread` need no changes at all -- they already
+/// only know about `Box<dyn SampleSink>`.
+///
+/// Segment build failures mid-recording (e.g. disk full) are logged and
+/// swallowed rather than propagated, the same way `Mp3Sink`/`WavSink`
+/// handle per-chunk encode/write errors: samples simply keep landing in
+/// whatever segment is still open.
+struct SegmentedSink {
+    session_dir: PathBuf,
+    stem: String,
+    format: RecordFormat,
+    sample_rate: u32,
+    channels: u16,
+    /// Interleaved sample count (i.e. `channels` samples per frame) that
+    /// triggers a rollover.
+    segment_samples: u64,
+    samples_in_segment: u64,
+    segment_index: u32,
+    current: Box<dyn SampleSink>,
+    /// (segment filename, measured duration in seconds) for every segment
+    /// closed so far, in order -- written out as `#EXTINF` entries.
+    entries: Vec<(String, f64)>,
+}
+
+impl SegmentedSink {
+    fn new(
+        session_dir: PathBuf,
+        stem: String,
+        format: RecordFormat,
+        sample_rate: u32,
+        channels: u16,
+        segment_duration: Duration,
+    ) -> Result<Self, RecordError> {
+        fs::create_dir_all(&session_dir).map_err(|e| RecordError::IoError(e.to_string()))?;
+
+        let segment_samples =
+            ((segment_duration.as_secs_f64() * sample_rate as f64 * channels as f64) as u64).max(1);
+
+        let first_index = 1;
+        let first_path = session_dir.join(segment_filename(&stem, first_index, &format));
+        let current = build_sink(&format, &first_path, sample_rate, channels)?;
+
+        Ok(Self {
+            session_dir,
+            stem,
+            format,
+            sample_rate,
+            channels,
+            segment_samples,
+            samples_in_segment: 0,
+            segment_index: first_index,
+            current,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Record the currently-open segment's measured duration in `entries`.
+    fn record_current_entry(&mut self) {
+        let name = segment_filename(&self.stem, self.segment_index, &self.format);
+        let frames = self.samples_in_segment / self.channels.max(1) as u64;
+        let duration_secs = frames as f64 / self.sample_rate as f64;
+        self.entries.push((name, duration_secs));
+    }
+
+    /// Rewrite `playlist.m3u8` from `entries` as-is -- called after every
+    /// rollover (and on `finalize`) so the manifest only ever describes
+    /// segments that have actually finished writing.
+    fn write_manifest(&self) {
+        let mut out = String::from("#EXTM3U\n");
+        for (name, duration_secs) in &self.entries {
+            out.push_str(&format!("#EXTINF:{duration_secs:.3},\n{name}\n"));
+        }
+        let path = self.session_dir.join(PLAYLIST_FILENAME);
+        if let Err(e) = fs::write(&path, out) {
+            eprintln!(
+                "segmented recording: failed to write playlist manifest {}: {e}",
+                path.display()
+            );
+        }
+    }
+
+    fn roll_over(&mut self) {
+        self.record_current_entry();
+
+        let next_index = self.segment_index + 1;
+        let next_path = self
+            .session_dir
+            .join(segment_filename(&self.stem, next_index, &self.format));
+        match build_sink(&self.format, &next_path, self.sample_rate, self.channels) {
+            Ok(next_sink) => {
+                let finished = std::mem::replace(&mut self.current, next_sink);
+                finished.finalize();
+                self.segment_index = next_index;
+                self.samples_in_segment = 0;
+                self.write_manifest();
+            }
+            Err(e) => {
+                eprintln!(
+                    "segmented recording: failed to open segment {}: {e}",
+                    next_path.display()
+                );
+            }
+        }
+    }
+}
+
+impl SampleSink for SegmentedSink {
+    fn write_samples(&mut self, samples: &[f32]) {
+        self.current.write_samples(samples);
+        self.samples_in_segment += samples.len() as u64;
+        if self.samples_in_segment >= self.segment_samples {
+            self.roll_over();
+        }
+    }
+
+    fn finalize(self: Box<Self>) {
+        let mut this = *self;
+        this.record_current_entry();
+        this.current.finalize();
+        this.write_manifest();
+    }
+}
+
+/// Producer/consumer ends of the lock-free SPSC ring buffer carrying
+/// channel-mixed f32 samples (interleaved when stereo) from the audio
+/// callback to the encoder thread.
+type SampleProducer = HeapProd<f32>;
+type SampleConsumer = HeapCons<f32>;
+
+/// Streaming linear-interpolation resampler, run on the encoder thread
+/// between the ring-buffer consumer and the sink so a configured
+/// `target_sample_rate` doesn't have to match whatever the device reports.
+/// Operates on interleaved frames of `channels` samples (1 for mono, 2 for
+/// stereo) so it works unchanged for either `ChannelMode`. Carries the last
+/// frame and fractional read phase across calls to `process` so block
+/// boundaries don't click.
+pub(crate) struct Resampler {
+    src_rate: u32,
+    dst_rate: u32,
+    channels: u16,
+    last_frame: Vec<f32>,
+    phase: f32,
+}
+
+impl Resampler {
+    pub(crate) fn new(src_rate: u32, dst_rate: u32, channels: u16) -> Self {
+        Self {
+            src_rate,
+            dst_rate,
+            channels,
+            last_frame: vec![0.0; channels as usize],
+            phase: 0.0,
+        }
+    }
+
+    /// Resample `input` (interleaved frames of `self.channels` samples) and
+    /// return the produced output frames, interleaved the same way. For
+    /// output frame index `n`, the corresponding source position is `pos =
+    /// n * src_rate / dst_rate`; each channel is linearly interpolated
+    /// between the frames bracketing that position, stopping once we'd need
+    /// a frame beyond the end of `input` and carrying the fractional
+    /// remainder forward to the next call.
+    pub(crate) fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.src_rate == self.dst_rate {
+            return input.to_vec();
+        }
+        let ch = self.channels as usize;
+        if input.is_empty() || ch == 0 {
+            return Vec::new();
+        }
+        let frame_count = input.len() / ch;
+
+        let ratio = self.src_rate as f32 / self.dst_rate as f32;
+        let mut out = Vec::new();
+        let mut pos = self.phase;
+
+        loop {
+            let i = pos.floor() as isize;
+            if i >= frame_count as isize - 1 {
+                break;
+            }
+            let frac = pos - pos.floor();
+            for (c, last) in self.last_frame.iter().enumerate().take(ch) {
+                let s0 = if i < 0 { *last } else { input[i as usize * ch + c] };
+                let s1 = if i + 1 < 0 {
+                    *last
+                } else {
+                    input[(i as usize + 1) * ch + c]
+                };
+                out.push(s0 * (1.0 - frac) + s1 * frac);
+            }
+            pos += ratio;
+        }
+
+        self.phase = pos - frame_count as f32;
+        let last_frame_start = (frame_count - 1) * ch;
+        self.last_frame
+            .copy_from_slice(&input[last_frame_start..last_frame_start + ch]);
+        out
+    }
+}
+
+/// Seconds of channel-mixed audio the ring buffer holds before the callback
+/// starts dropping samples instead of blocking.
+const RING_BUFFER_SECONDS: f32 = 1.5;
+
+/// Samples drained from the ring buffer per encoder thread iteration.
+const ENCODE_CHUNK_SAMPLES: usize = 4096;
+
+/// Handle to the dedicated encoder thread spawned by `start()`. Dropped (via
+/// `stop()`) to signal the thread to drain, flush, and exit.
+struct EncoderHandle {
+    stop_tx: mpsc::Sender<()>,
+    join: thread::JoinHandle<()>,
+    dropped_samples: Arc<AtomicU64>,
+    /// Running peak/RMS of everything written to the sink, used by `stop()`
+    /// to detect and discard silent recordings.
+    stats: Arc<Mutex<AudioStats>>,
+    /// Peak/RMS of the most recently encoded chunk, for `current_level()`'s
+    /// live VU meter.
+    level: Arc<AudioLevelCell>,
+}
+
+/// Instantaneous peak/RMS level of the most recently captured buffer,
+/// returned by `Recorder::current_level()` to drive a live VU meter.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AudioLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+impl AudioLevel {
+    fn of(samples: &[f32]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut peak = 0.0f32;
+        let mut sum_squares = 0.0f64;
+        for &s in samples {
+            peak = peak.max(s.abs());
+            sum_squares += (s as f64) * (s as f64);
+        }
+        let rms = (sum_squares / samples.len() as f64).sqrt() as f32;
+        Self { peak, rms }
+    }
+
+    /// RMS level in dBFS, or `NEG_INFINITY` for digital silence.
+    pub fn rms_db(&self) -> f32 {
+        if self.rms <= 0.0 {
+            f32::NEG_INFINITY
+        } else {
+            20.0 * self.rms.log10()
+        }
+    }
+}
+
+/// Lock-free slot the encoder thread publishes the latest `AudioLevel` into
+/// (as bit-cast `AtomicU32`s) and `Recorder::current_level()` polls, so
+/// neither side ever blocks on a lock for it.
+#[derive(Default)]
+struct AudioLevelCell {
+    peak_bits: AtomicU32,
+    rms_bits: AtomicU32,
+}
+
+impl AudioLevelCell {
+    fn publish(&self, level: AudioLevel) {
+        self.peak_bits.store(level.peak.to_bits(), Ordering::Relaxed);
+        self.rms_bits.store(level.rms.to_bits(), Ordering::Relaxed);
+    }
+
+    fn load(&self) -> AudioLevel {
+        AudioLevel {
+            peak: f32::from_bits(self.peak_bits.load(Ordering::Relaxed)),
+            rms: f32::from_bits(self.rms_bits.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Running peak/RMS level of captured audio, accumulated on the encoder
+/// thread as samples pass through (cheap there — it's off the real-time
+/// audio callback) and read back by `stop()`.
+#[derive(Default, Clone, Copy)]
+struct AudioStats {
+    peak: f32,
+    sum_squares: f64,
+    sample_count: u64,
+}
+
+impl AudioStats {
+    fn observe(&mut self, samples: &[f32]) {
+        for &s in samples {
+            self.peak = self.peak.max(s.abs());
+            self.sum_squares += (s as f64) * (s as f64);
+        }
+        self.sample_count += samples.len() as u64;
+    }
+
+    /// RMS level in dBFS, or `NEG_INFINITY` for digital silence / no samples.
+    fn rms_db(&self) -> f32 {
+        if self.sample_count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        let rms = (self.sum_squares / self.sample_count as f64).sqrt() as f32;
+        if rms <= 0.0 {
+            f32::NEG_INFINITY
+        } else {
+            20.0 * rms.log10()
+        }
+    }
+}
+
+/// What happened to the file on `Recorder::stop()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopOutcome {
+    /// Recording saved at this path.
+    Saved(PathBuf),
+    /// The recording was empty, stayed below
+    /// `RecorderConfig.silence_threshold_db` for its whole duration, or ran
+    /// shorter than `RecorderConfig.min_duration_ms`, so the output file was
+    /// deleted instead of kept.
+    DiscardedSilence,
+}
+
+/// Core recorder that captures audio from an input device to a file, using
+/// the codec selected by `RecorderConfig.format`.
 ///
 /// NOTE: `cpal::Stream` is !Send on macOS, so this struct must stay on the
 /// thread that created it (typically the main thread). Do not put it in
 /// Tauri managed state directly — use interior mutability on the main thread.
+///
+/// The audio callback only does cheap sample conversion/downmix and pushes
+/// into a lock-free ring buffer; a separate encoder thread owns the
+/// `SampleSink` and does the encode + file I/O, keeping both off the
+/// real-time audio thread.
 pub struct Recorder {
     pub config: RecorderConfig,
     state: RecorderState,
     /// Active cpal stream (kept alive while recording). Not Send.
     active_stream: Option<Stream>,
+    /// Second input stream, only set under `CaptureMode::MixWithSystemAudio`.
+    /// Its disconnection isn't covered by `poll_interrupted` (that path only
+    /// tracks the primary mic stream); kept alive here purely so dropping
+    /// the recorder — or `stop()` — tears it down.
+    system_stream: Option<Stream>,
     /// Path of the file currently being recorded.
     current_file: Option<PathBuf>,
-    /// Shared MP3 writer for flushing on stop.
-    mp3_writer: Option<Arc<Mutex<Option<Mp3Writer>>>>,
+    /// Encoder thread handle, used to signal shutdown on `stop()`.
+    encoder: Option<EncoderHandle>,
+    /// Producer end of the ring buffer feeding the encoder thread, kept
+    /// around (behind a mutex) so `poll_interrupted` can rebind it to a
+    /// freshly built stream on reconnect.
+    producer: Option<Arc<Mutex<SampleProducer>>>,
+    /// Set by the active stream's error callback when the device
+    /// disconnects; consumed by `poll_interrupted`.
+    interrupted: Option<Arc<AtomicBool>>,
+    /// When the current recording started, used by `stop()` to discard
+    /// recordings shorter than `RecorderConfig.min_duration_ms`.
+    started_at: Option<Instant>,
+    /// Session directory of the current recording, set only when
+    /// `RecorderConfig.segment_duration` is in effect -- `stop()` discards
+    /// the whole directory (rather than a single file) when the recording
+    /// turns out to be silence.
+    current_session_dir: Option<PathBuf>,
 }
 
 impl Recorder {
@@ -65,8 +848,13 @@ impl Recorder {
             config,
             state: RecorderState::Idle,
             active_stream: None,
+            system_stream: None,
             current_file: None,
-            mp3_writer: None,
+            encoder: None,
+            producer: None,
+            interrupted: None,
+            started_at: None,
+            current_session_dir: None,
         }
     }
 
@@ -74,6 +862,30 @@ impl Recorder {
         self.state
     }
 
+    /// Cumulative count of samples dropped because the writer (encoder)
+    /// thread fell behind the ring buffer feeding it. The real-time audio
+    /// callback never blocks on this — `push_pcm` just counts whatever
+    /// doesn't fit rather than stalling — so a nonzero count here means
+    /// audio was lost, not that capture glitched. Zero when idle.
+    pub fn overruns(&self) -> u64 {
+        self.encoder
+            .as_ref()
+            .map(|e| e.dropped_samples.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    /// Peak/RMS level of the most recently captured buffer, for driving a
+    /// live VU meter -- e.g. so a UI can tell the selected device is
+    /// actually picking up sound before the user discovers a silent file.
+    /// `None` unless `state()` is `Recording`; poll this periodically (e.g.
+    /// every ~100ms from a UI timer) while recording.
+    pub fn current_level(&self) -> Option<AudioLevel> {
+        if self.state != RecorderState::Recording {
+            return None;
+        }
+        self.encoder.as_ref().map(|e| e.level.load())
+    }
+
     /// Start recording. Returns the output file path on success.
     pub fn start(&mut self, device_manager: &AudioDeviceManager) -> Result<PathBuf, RecordError> {
         if self.state == RecorderState::Recording {
@@ -104,103 +916,368 @@ impl Recorder {
         let supported_config = AudioDeviceManager::default_input_config(&device)
             .map_err(|e| RecordError::ConfigError(e.to_string()))?;
 
-        let channels = supported_config.channels();
         let sample_rate = supported_config.sample_rate().0;
-        let sample_format = supported_config.sample_format();
+
+        // Validate the configured target rate against what the device can
+        // actually supply, rather than letting an unsupported rate surface
+        // as an opaque failure later when the stream is built.
+        if let Some(target) = self.config.target_sample_rate {
+            if let Some(props) = AudioDeviceManager::properties_for(&device) {
+                if !props.supports_sample_rate(target) {
+                    return Err(RecordError::UnsupportedDeviceFormat(format!(
+                        "requested {target} Hz on '{device_name}', which only supports \
+                         {}-{} Hz",
+                        props.min_sample_rate, props.max_sample_rate
+                    )));
+                }
+            }
+        }
 
         println!(
-            "audio device: name={device_name}, channels={channels}, \
-             sample_rate={sample_rate}, format={sample_format:?}"
+            "audio device: name={device_name}, channels={}, sample_rate={sample_rate}, \
+             format={:?}",
+            supported_config.channels(),
+            supported_config.sample_format()
         );
 
         // Ensure output dir exists
         fs::create_dir_all(&self.config.output_dir)
             .map_err(|e| RecordError::IoError(e.to_string()))?;
 
-        // Generate filename with timestamp
-        let filename = generate_filename();
-        let output_path = self.config.output_dir.join(&filename);
-
-        // Build MP3 encoder — always mono (voice recording).
-        // Multi-channel input is downmixed to mono before encoding.
-        let mp3_writer = build_mp3_writer(&output_path, sample_rate)?;
-        let writer = Arc::new(Mutex::new(Some(mp3_writer)));
+        // Generate filename with timestamp, extension matching the configured
+        // format. `generate_filename`'s own sequence number already makes
+        // same-second collisions within this run vanishingly unlikely, but
+        // also guard the disk directly in case a fresh process restarts the
+        // sequence and happens to land on a name an earlier run left behind
+        // (either the file itself, or -- in segmented mode -- its session
+        // directory).
+        let mut filename = generate_filename(&self.config.format);
+        let ext_suffix = format!(".{}", self.config.format.extension());
+        while self.config.output_dir.join(&filename).exists()
+            || self
+                .config
+                .output_dir
+                .join(filename.strip_suffix(&ext_suffix).unwrap_or(&filename))
+                .exists()
+        {
+            filename = generate_filename(&self.config.format);
+        }
 
-        // Build input stream
-        let writer_clone = writer.clone();
-        let err_fn = |err: cpal::StreamError| {
-            eprintln!("audio stream error: {err}");
+        // Encode at the configured target rate if set, otherwise whatever
+        // the device reports. NOTE: if a disconnected device is replaced by
+        // a default with a different sample rate, the resampler below keeps
+        // assuming the original device's rate (rebuilding it mid-recording
+        // isn't supported), so a `Reconnect` may play back at a slightly
+        // wrong speed on such a device swap.
+        let encode_rate = self.config.target_sample_rate.unwrap_or(sample_rate);
+        let channel_mode = self.config.channels;
+        let out_channels = channel_mode.out_channels();
+        let resampler = if encode_rate == sample_rate {
+            None
+        } else {
+            Some(Resampler::new(sample_rate, encode_rate, out_channels))
         };
 
-        let config = supported_config.into();
-
-        let stream = match sample_format {
-            SampleFormat::F32 => device.build_input_stream(
-                &config,
-                move |data: &[f32], _| encode_samples_f32(&writer_clone, data, channels),
-                err_fn,
-                None,
-            ),
-            SampleFormat::I16 => device.build_input_stream(
-                &config,
-                move |data: &[i16], _| encode_samples_i16(&writer_clone, data, channels),
-                err_fn,
-                None,
-            ),
-            SampleFormat::U16 => device.build_input_stream(
-                &config,
-                move |data: &[u16], _| encode_samples_u16(&writer_clone, data, channels),
-                err_fn,
-                None,
-            ),
-            _ => return Err(RecordError::UnsupportedFormat(format!("{sample_format:?}"))),
-        }
-        .map_err(|e| RecordError::StreamError(e.to_string()))?;
+        // Build the sample sink for the configured format and channel mode
+        // — multi-channel input is mixed down to `out_channels` (see
+        // `mix_channels`) before reaching the sink. In segmented mode, the
+        // session directory (holding the segment files and the playlist
+        // manifest) stands in for the single output file everywhere below.
+        let (sink, output_path, session_dir): (Box<dyn SampleSink>, PathBuf, Option<PathBuf>) =
+            match self.config.segment_duration {
+                Some(segment_duration) => {
+                    let stem = filename
+                        .strip_suffix(&format!(".{}", self.config.format.extension()))
+                        .unwrap_or(&filename)
+                        .to_string();
+                    let session_dir = self.config.output_dir.join(&stem);
+                    let sink = SegmentedSink::new(
+                        session_dir.clone(),
+                        stem,
+                        self.config.format,
+                        encode_rate,
+                        out_channels,
+                        segment_duration,
+                    )?;
+                    let playlist_path = session_dir.join(PLAYLIST_FILENAME);
+                    (Box::new(sink), playlist_path, Some(session_dir))
+                }
+                None => {
+                    let path = self.config.output_dir.join(&filename);
+                    let sink = build_sink(&self.config.format, &path, encode_rate, out_channels)?;
+                    (sink, path, None)
+                }
+            };
+
+        // Ring buffer sized to ~1.5s of post-mix audio at the capture rate,
+        // so a momentary scheduling delay on the encoder thread doesn't
+        // cause the real-time callback to block.
+        let ring_capacity =
+            ((sample_rate as f32 * out_channels as f32 * RING_BUFFER_SECONDS) as usize).max(1);
 
-        stream
-            .play()
-            .map_err(|e| RecordError::StreamError(e.to_string()))?;
+        let dropped_samples = Arc::new(AtomicU64::new(0));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let stats = Arc::new(Mutex::new(AudioStats::default()));
+        let level = Arc::new(AudioLevelCell::default());
 
-        self.active_stream = Some(stream);
+        let (mic_stream, system_stream, producer, join) = match self.config.capture.clone() {
+            CaptureMode::Single => {
+                let rb = HeapRb::<f32>::new(ring_capacity);
+                let (producer, consumer) = rb.split();
+                // Wrapped in a mutex (rather than handed to the stream
+                // callback directly) so a `Reconnect` recovery can rebind
+                // the *same* ring buffer/encoder thread/file to a freshly
+                // built stream.
+                let producer = Arc::new(Mutex::new(producer));
+
+                let encoder_stats = stats.clone();
+                let encoder_level = level.clone();
+                let join = thread::Builder::new()
+                    .name("lyre-encoder".into())
+                    .spawn(move || {
+                        run_encoder_thread(
+                            sink,
+                            consumer,
+                            resampler,
+                            encoder_stats,
+                            encoder_level,
+                            stop_rx,
+                        )
+                    })
+                    .map_err(|e| {
+                        RecordError::EncoderError(format!("failed to spawn encoder thread: {e}"))
+                    })?;
+
+                let stream = build_stream(
+                    &device,
+                    producer.clone(),
+                    dropped_samples.clone(),
+                    interrupted.clone(),
+                    channel_mode,
+                )?;
+
+                (stream, None, producer, join)
+            }
+            CaptureMode::MixWithSystemAudio {
+                system_device,
+                mic_gain,
+                system_gain,
+            } => {
+                let sys_device = device_manager
+                    .input_device_by_name(&system_device)
+                    .ok_or_else(|| RecordError::LoopbackUnavailable(system_device.clone()))?;
+
+                let mic_rb = HeapRb::<f32>::new(ring_capacity);
+                let (mic_producer, mic_consumer) = mic_rb.split();
+                let mic_producer = Arc::new(Mutex::new(mic_producer));
+
+                let sys_rb = HeapRb::<f32>::new(ring_capacity);
+                let (sys_producer, sys_consumer) = sys_rb.split();
+                let sys_producer = Arc::new(Mutex::new(sys_producer));
+
+                let encoder_stats = stats.clone();
+                let encoder_level = level.clone();
+                let join = thread::Builder::new()
+                    .name("lyre-encoder".into())
+                    .spawn(move || {
+                        run_mixed_encoder_thread(
+                            sink,
+                            MixSource {
+                                consumer: mic_consumer,
+                                gain: mic_gain,
+                            },
+                            MixSource {
+                                consumer: sys_consumer,
+                                gain: system_gain,
+                            },
+                            resampler,
+                            encoder_stats,
+                            encoder_level,
+                            stop_rx,
+                        )
+                    })
+                    .map_err(|e| {
+                        RecordError::EncoderError(format!("failed to spawn encoder thread: {e}"))
+                    })?;
+
+                let mic_stream = build_stream(
+                    &device,
+                    mic_producer.clone(),
+                    dropped_samples.clone(),
+                    interrupted.clone(),
+                    channel_mode,
+                )?;
+
+                // The system-audio side isn't covered by `poll_interrupted`
+                // (that path only knows about the primary mic stream and
+                // `self.interrupted`), so its disconnection just starves
+                // its ring buffer — the mixer pads the gap with silence
+                // rather than stalling the whole recording.
+                let sys_stream = build_stream(
+                    &sys_device,
+                    sys_producer,
+                    Arc::new(AtomicU64::new(0)),
+                    Arc::new(AtomicBool::new(false)),
+                    channel_mode,
+                )?;
+
+                (mic_stream, Some(sys_stream), mic_producer, join)
+            }
+        };
+
+        self.active_stream = Some(mic_stream);
+        self.system_stream = system_stream;
         self.current_file = Some(output_path.clone());
-        self.mp3_writer = Some(writer);
+        self.current_session_dir = session_dir;
+        self.producer = Some(producer);
+        self.interrupted = Some(interrupted);
+        self.started_at = Some(Instant::now());
+        self.encoder = Some(EncoderHandle {
+            stop_tx,
+            join,
+            dropped_samples,
+            stats,
+            level,
+        });
         self.state = RecorderState::Recording;
 
         Ok(output_path)
     }
 
-    /// Stop recording. Returns the saved file path.
-    pub fn stop(&mut self) -> Result<PathBuf, RecordError> {
+    /// Poll for an input-device disconnection flagged by the active
+    /// stream's error callback since the last call, and apply
+    /// `RecorderConfig.on_device_lost`. The error callback runs on a
+    /// real-time thread and can't safely rebuild a stream or touch the
+    /// file itself, so this must be called periodically (e.g. from a UI
+    /// timer) while recording for disconnection recovery to take effect.
+    pub fn poll_interrupted(&mut self, device_manager: &AudioDeviceManager) -> Result<(), RecordError> {
         if self.state != RecorderState::Recording {
+            return Ok(());
+        }
+        let Some(interrupted) = self.interrupted.clone() else {
+            return Ok(());
+        };
+        if !interrupted.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        eprintln!("input device disconnected mid-recording");
+        self.state = RecorderState::Interrupted;
+        self.active_stream.take();
+
+        match self.config.on_device_lost {
+            OnDeviceLost::Stop => {
+                let _ = self.stop();
+                Err(RecordError::DeviceDisconnected)
+            }
+            OnDeviceLost::Reconnect => {
+                let device = device_manager
+                    .default_input_device()
+                    .ok_or(RecordError::NoDefaultDevice)?;
+                let producer = self.producer.clone().ok_or_else(|| {
+                    RecordError::IoError("no active ring buffer to reconnect".into())
+                })?;
+                let dropped_samples = self
+                    .encoder
+                    .as_ref()
+                    .map(|e| e.dropped_samples.clone())
+                    .ok_or_else(|| RecordError::IoError("no active encoder to reconnect".into()))?;
+                let stream = build_stream(
+                    &device,
+                    producer,
+                    dropped_samples,
+                    interrupted,
+                    self.config.channels,
+                )?;
+                self.active_stream = Some(stream);
+                self.state = RecorderState::Recording;
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop recording. Returns the saved file path (the playlist manifest
+    /// path, in segmented mode) on success, or `StopOutcome::DiscardedSilence`
+    /// if the captured audio was empty, never rose above
+    /// `RecorderConfig.silence_threshold_db`, or the recording ran shorter
+    /// than `RecorderConfig.min_duration_ms` -- in which case the output
+    /// file (or, in segmented mode, the whole session directory) is deleted
+    /// rather than kept.
+    pub fn stop(&mut self) -> Result<StopOutcome, RecordError> {
+        if self.state != RecorderState::Recording && self.state != RecorderState::Interrupted {
             return Err(RecordError::NotRecording);
         }
 
-        // Drop the stream first to stop audio callbacks
+        // Drop the stream(s) first to stop audio callbacks (and further
+        // pushes into the ring buffer(s)).
         self.active_stream.take();
+        self.system_stream.take();
+        self.producer = None;
+        self.interrupted = None;
+        let elapsed = self.started_at.take().map(|t| t.elapsed());
+        let session_dir = self.current_session_dir.take();
 
-        // Flush the MP3 encoder and close the file
-        if let Some(writer_arc) = self.mp3_writer.take() {
-            if let Ok(mut guard) = writer_arc.lock() {
-                if let Some(mut w) = guard.take() {
-                    let mut flush_buf =
-                        Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(0));
-                    if let Ok(flush_size) = w
-                        .encoder
-                        .flush::<FlushNoGap>(flush_buf.spare_capacity_mut())
-                    {
-                        unsafe { flush_buf.set_len(flush_size) };
-                        let _ = w.file.write_all(&flush_buf);
-                    }
-                    let _ = w.file.flush();
-                }
+        // Signal the encoder thread to drain the remaining buffer, flush,
+        // and close the file, then wait for it to finish.
+        let mut stats = AudioStats::default();
+        if let Some(encoder) = self.encoder.take() {
+            let _ = encoder.stop_tx.send(());
+            let dropped = encoder.dropped_samples.load(Ordering::Relaxed);
+            if dropped > 0 {
+                eprintln!("recorder: dropped {dropped} samples due to encoder overrun");
             }
+            if encoder.join.join().is_err() {
+                eprintln!("encoder thread panicked while flushing");
+            }
+            stats = *encoder.stats.lock().unwrap();
         }
 
         self.state = RecorderState::Idle;
 
-        self.current_file
+        let output_path = self
+            .current_file
             .take()
-            .ok_or(RecordError::IoError("no current file".into()))
+            .ok_or(RecordError::IoError("no current file".into()))?;
+
+        let is_too_short = self
+            .config
+            .min_duration_ms
+            .is_some_and(|min| elapsed.map(|e| e < Duration::from_millis(min)).unwrap_or(true));
+
+        let is_silent = stats.sample_count == 0
+            || is_too_short
+            || self
+                .config
+                .silence_threshold_db
+                .is_some_and(|threshold| stats.rms_db() <= threshold);
+
+        if is_silent {
+            let removed = match &session_dir {
+                Some(dir) => fs::remove_dir_all(dir),
+                None => fs::remove_file(&output_path),
+            };
+            if let Err(e) = removed {
+                eprintln!(
+                    "recorder: failed to discard silent recording {}: {e}",
+                    output_path.display()
+                );
+            }
+            return Ok(StopOutcome::DiscardedSilence);
+        }
+
+        // Segmented recordings have no single file to hand the upload
+        // queue -- `RecorderConfig.auto_upload` doesn't apply to them.
+        if self.config.auto_upload && session_dir.is_none() {
+            if let Err(e) = queue_for_upload(&output_path) {
+                eprintln!(
+                    "recorder: failed to queue {} for upload: {e}",
+                    output_path.display()
+                );
+            }
+        }
+
+        Ok(StopOutcome::Saved(output_path))
     }
 
     /// Update the output directory.
@@ -212,6 +1289,190 @@ impl Recorder {
     pub fn select_device(&mut self, name: Option<String>) {
         self.config.selected_device_name = name;
     }
+
+    /// Change the output encoding used for new recordings.
+    pub fn set_format(&mut self, format: RecordFormat) {
+        self.config.format = format;
+    }
+
+    /// Change which audio sources are captured and mixed into the recording.
+    pub fn set_capture_mode(&mut self, mode: CaptureMode) {
+        self.config.capture = mode;
+    }
+
+    /// Enable or disable queueing every kept recording for background
+    /// upload on `stop()`. See `RecorderConfig.auto_upload`.
+    pub fn set_auto_upload(&mut self, enabled: bool) {
+        self.config.auto_upload = enabled;
+    }
+}
+
+/// Commands accepted by the actor thread spawned by `spawn_actor`. The tray
+/// (and hotkey handler) only ever holds a `Sender<RecorderCommand>` -- the
+/// `Recorder` and its `cpal::Stream` never leave the thread that owns them.
+pub enum RecorderCommand {
+    Start,
+    Stop,
+    /// Select the input device at this index (as returned by
+    /// `AudioDeviceManager::list_input_devices`), or `None` for the system
+    /// default.
+    SelectDevice(Option<usize>),
+    SetOutputDir(PathBuf),
+    /// Change the output encoding used for new recordings.
+    SetFormat(RecordFormat),
+    /// Change which audio sources are captured and mixed into the recording.
+    SetCaptureMode(CaptureMode),
+    /// Poll the most recent peak/RMS level for a live VU meter. Responds
+    /// with `RecorderStatus::Level`.
+    QueryLevel,
+    /// Stop any in-progress recording (so it's flushed rather than
+    /// truncated) and exit the actor loop.
+    Quit,
+}
+
+/// Status pushed back from the actor thread after handling a
+/// `RecorderCommand`, so the main thread can update the tray icon/menu
+/// without ever touching the `Recorder` itself.
+#[derive(Debug, Clone)]
+pub enum RecorderStatus {
+    Started(PathBuf),
+    /// A recording was stopped. Carries the saved file's path, or (for a
+    /// recording discarded as silence/too-short) the configured output
+    /// directory it would have been saved into.
+    Stopped(PathBuf),
+    Error(String),
+    StateChanged(RecorderState),
+    /// Response to `RecorderCommand::QueryLevel`; `None` unless currently
+    /// recording.
+    Level(Option<AudioLevel>),
+}
+
+/// Handle to the actor thread spawned by `spawn_actor`: a command sender and
+/// the matching status receiver.
+pub struct RecorderHandle {
+    pub commands: mpsc::Sender<RecorderCommand>,
+    pub status: mpsc::Receiver<RecorderStatus>,
+}
+
+/// Spawn a dedicated thread that owns a `Recorder` outright and drives it
+/// from `RecorderCommand`s received on a channel, reporting back over a
+/// second channel.
+///
+/// This is the only place `Recorder::start`/`stop` are called outside of
+/// tests: because the `cpal::Stream` `start()` creates lives in this
+/// thread's local `recorder` variable and never crosses a thread boundary,
+/// callers never need an `unsafe impl Send`/`Sync` to hold onto it.
+///
+/// `device_manager` is moved in rather than shared, since device resolution
+/// (`Recorder::start` looking up the selected device by name) only ever
+/// needs to happen on this thread -- the tray keeps its own
+/// `AudioDeviceManager` for listing devices in the menu.
+///
+/// Every command gets exactly one reply on `status`: `Start`/`Stop` reply
+/// with their own `Started`/`Stopped`/`Error`, `QueryLevel` replies with
+/// `Level`, and everything else (including `Quit`) replies with a single
+/// `StateChanged`. The one exception: when a device disconnect is found
+/// before a command and `poll_interrupted` fails (the `Stop` policy, or a
+/// failed reconnect), an extra `Error` is sent ahead of that command's reply.
+pub fn spawn_actor(config: RecorderConfig, device_manager: AudioDeviceManager) -> RecorderHandle {
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RecorderCommand>();
+    let (status_tx, status_rx) = mpsc::channel::<RecorderStatus>();
+
+    thread::spawn(move || {
+        let mut recorder = Recorder::new(config);
+
+        for command in cmd_rx {
+            // A device disconnect can flip the recorder into `Interrupted`
+            // between commands; resolve it the same way the old
+            // menu-event-driven poll did, before acting on a fresh command.
+            if let Err(e) = recorder.poll_interrupted(&device_manager) {
+                let _ = status_tx.send(RecorderStatus::Error(format!(
+                    "recording stopped after device disconnect: {e}"
+                )));
+            }
+
+            match command {
+                RecorderCommand::Start => match recorder.start(&device_manager) {
+                    Ok(path) => {
+                        let _ = status_tx.send(RecorderStatus::Started(path));
+                    }
+                    Err(e) => {
+                        let _ = status_tx.send(RecorderStatus::Error(e.to_string()));
+                    }
+                },
+                RecorderCommand::Stop => match recorder.stop() {
+                    Ok(StopOutcome::Saved(path)) => {
+                        let _ = status_tx.send(RecorderStatus::Stopped(path));
+                    }
+                    Ok(StopOutcome::DiscardedSilence) => {
+                        let _ = status_tx
+                            .send(RecorderStatus::Stopped(recorder.config.output_dir.clone()));
+                    }
+                    Err(e) => {
+                        let _ = status_tx.send(RecorderStatus::Error(e.to_string()));
+                    }
+                },
+                RecorderCommand::SelectDevice(index) => {
+                    let name = index.and_then(|i| {
+                        device_manager
+                            .list_input_devices()
+                            .into_iter()
+                            .find(|d| d.index == i)
+                            .map(|d| d.name)
+                    });
+                    recorder.select_device(name);
+                    let _ = status_tx.send(RecorderStatus::StateChanged(recorder.state()));
+                }
+                RecorderCommand::SetOutputDir(dir) => {
+                    recorder.set_output_dir(dir);
+                    let _ = status_tx.send(RecorderStatus::StateChanged(recorder.state()));
+                }
+                RecorderCommand::SetFormat(format) => {
+                    recorder.set_format(format);
+                    let _ = status_tx.send(RecorderStatus::StateChanged(recorder.state()));
+                }
+                RecorderCommand::SetCaptureMode(mode) => {
+                    recorder.set_capture_mode(mode);
+                    let _ = status_tx.send(RecorderStatus::StateChanged(recorder.state()));
+                }
+                RecorderCommand::QueryLevel => {
+                    let _ = status_tx.send(RecorderStatus::Level(recorder.current_level()));
+                }
+                RecorderCommand::Quit => {
+                    if matches!(
+                        recorder.state(),
+                        RecorderState::Recording | RecorderState::Interrupted
+                    ) {
+                        let _ = recorder.stop();
+                    }
+                    let _ = status_tx.send(RecorderStatus::StateChanged(recorder.state()));
+                    break;
+                }
+            }
+        }
+    });
+
+    RecorderHandle {
+        commands: cmd_tx,
+        status: status_rx,
+    }
+}
+
+/// Queue `path` for background upload via the durable upload queue, using
+/// whatever title/folder/tags defaults `upload_queue::enqueue_upload`
+/// applies on its own -- `RecorderConfig.auto_upload` only says "upload
+/// this file", not how to categorize it.
+fn queue_for_upload(path: &std::path::Path) -> Result<(), String> {
+    upload_queue::enqueue_upload(UploadOptions {
+        file_path: path.to_string_lossy().into_owned(),
+        title: None,
+        folder_id: None,
+        tag_ids: None,
+        quality_preset: Default::default(),
+        upload_id: None,
+        chunked: false,
+    })
+    .map(|_| ())
 }
 
 /// Errors that can occur during recording.
@@ -227,6 +1488,16 @@ pub enum RecordError {
     IoError(String),
     UnsupportedFormat(String),
     EncoderError(String),
+    /// The input device disconnected mid-recording and
+    /// `RecorderConfig.on_device_lost` was `Stop`.
+    DeviceDisconnected,
+    /// `CaptureMode::MixWithSystemAudio` was configured with a
+    /// `system_device` name that no input device (loopback/monitor or
+    /// otherwise) currently matches.
+    LoopbackUnavailable(String),
+    /// The resolved input device can't actually supply `target_sample_rate`
+    /// -- see `AudioDeviceManager::properties_for`.
+    UnsupportedDeviceFormat(String),
 }
 
 impl std::fmt::Display for RecordError {
@@ -241,116 +1512,352 @@ impl std::fmt::Display for RecordError {
             Self::IoError(e) => write!(f, "I/O error: {e}"),
             Self::UnsupportedFormat(e) => write!(f, "unsupported sample format: {e}"),
             Self::EncoderError(e) => write!(f, "encoder error: {e}"),
+            Self::DeviceDisconnected => write!(f, "input device disconnected"),
+            Self::LoopbackUnavailable(name) => {
+                write!(f, "system audio loopback device '{name}' not found")
+            }
+            Self::UnsupportedDeviceFormat(e) => write!(f, "unsupported device format: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+// --- Internal helpers ---
+
+/// Per-process monotonic sequence appended to every generated filename so
+/// rapid start/stop cycles within the same second (or even the same
+/// millisecond) never collide -- see `generate_filename`.
+static FILENAME_SEQ: AtomicU32 = AtomicU32::new(0);
+
+/// Generate a unique recording filename: the `recording-YYYYMMDD-HHMMSS`
+/// prefix keeps filenames human-readable and sortable, while the trailing
+/// sequence number guarantees no two calls in this process ever collide
+/// regardless of how close together they land.
+pub fn generate_filename(format: &RecordFormat) -> String {
+    let now = chrono::Local::now();
+    let seq = FILENAME_SEQ.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "recording-{}-{:04}.{}",
+        now.format("%Y%m%d-%H%M%S"),
+        seq,
+        format.extension()
+    )
+}
+
+/// Downmix interleaved multi-channel f32 samples to mono by averaging channels.
+pub(crate) fn downmix_to_mono_f32(data: &[f32], channels: u16) -> Vec<f32> {
+    if channels == 1 {
+        return data.to_vec();
+    }
+    let ch = channels as usize;
+    data.chunks_exact(ch)
+        .map(|frame| {
+            let sum: f32 = frame.iter().sum();
+            (sum / channels as f32).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// Mix interleaved multi-channel f32 samples down to an interleaved stereo
+/// pair, mirroring the cubeb-coreaudio mixer: known layouts map directly
+/// (mono duplicated to both ears, a 4-channel source's front pair taken as
+/// L/R), while any other channel count folds every channel into L/R with
+/// an equal-power coefficient (`1/sqrt(channels)`) so the mix doesn't clip
+/// as channel count grows.
+pub(crate) fn downmix_to_stereo_f32(data: &[f32], channels: u16) -> Vec<f32> {
+    let ch = channels as usize;
+    match channels {
+        1 => data.iter().flat_map(|&s| [s, s]).collect(),
+        2 => data.to_vec(),
+        4 => data
+            .chunks_exact(ch)
+            .flat_map(|frame| [frame[0], frame[1]])
+            .collect(),
+        _ => {
+            let coeff = 1.0 / (channels as f32).sqrt();
+            data.chunks_exact(ch)
+                .flat_map(|frame| {
+                    let mixed: f32 = (frame.iter().map(|&s| s * coeff).sum::<f32>()).clamp(-1.0, 1.0);
+                    [mixed, mixed]
+                })
+                .collect()
         }
     }
 }
 
-impl std::error::Error for RecordError {}
+/// Extract a single hardware input channel (0-indexed) from interleaved
+/// multi-channel f32 samples, as mono. Missing channels (e.g. `channel` out
+/// of range for a device that dropped to fewer channels) read as silence.
+fn extract_channel_f32(data: &[f32], channels: u16, channel: usize) -> Vec<f32> {
+    let ch = channels as usize;
+    data.chunks_exact(ch)
+        .map(|frame| frame.get(channel).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Reduce interleaved `data` (captured at `channels` channels) to whatever
+/// `mode` encodes — mono, stereo, or a single extracted channel.
+fn mix_channels(data: &[f32], channels: u16, mode: ChannelMode) -> Vec<f32> {
+    match mode {
+        ChannelMode::Mono => downmix_to_mono_f32(data, channels),
+        ChannelMode::Stereo => downmix_to_stereo_f32(data, channels),
+        ChannelMode::FromChannel(idx) => extract_channel_f32(data, channels, idx),
+    }
+}
+
+/// Push already channel-mixed PCM frames into the ring buffer, counting any
+/// that don't fit (encoder thread falling behind) rather than blocking the
+/// real-time audio callback. Takes the producer behind a mutex (rather than
+/// owned outright by the callback closure) so a device reconnect can rebind
+/// the same ring buffer to a freshly built stream's callback.
+fn push_pcm(producer: &Arc<Mutex<SampleProducer>>, dropped: &Arc<AtomicU64>, samples: &[f32]) {
+    let written = producer
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push_slice(samples);
+    if written < samples.len() {
+        dropped.fetch_add((samples.len() - written) as u64, Ordering::Relaxed);
+    }
+}
+
+/// Mix f32 PCM samples per `mode` and push onto the ring buffer.
+fn push_samples_f32(
+    producer: &Arc<Mutex<SampleProducer>>,
+    dropped: &Arc<AtomicU64>,
+    data: &[f32],
+    channels: u16,
+    mode: ChannelMode,
+) {
+    let mixed = mix_channels(data, channels, mode);
+    push_pcm(producer, dropped, &mixed);
+}
+
+/// Convert i16 PCM samples to f32, mix per `mode`, and push onto the ring buffer.
+fn push_samples_i16(
+    producer: &Arc<Mutex<SampleProducer>>,
+    dropped: &Arc<AtomicU64>,
+    data: &[i16],
+    channels: u16,
+    mode: ChannelMode,
+) {
+    let f32_data: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+    let mixed = mix_channels(&f32_data, channels, mode);
+    push_pcm(producer, dropped, &mixed);
+}
+
+/// Convert u16 PCM samples to f32, mix per `mode`, and push onto the ring buffer.
+fn push_samples_u16(
+    producer: &Arc<Mutex<SampleProducer>>,
+    dropped: &Arc<AtomicU64>,
+    data: &[u16],
+    channels: u16,
+    mode: ChannelMode,
+) {
+    let f32_data: Vec<f32> = data
+        .iter()
+        .map(|&s| (s as f32 - 32768.0) / 32768.0)
+        .collect();
+    let mixed = mix_channels(&f32_data, channels, mode);
+    push_pcm(producer, dropped, &mixed);
+}
+
+/// Build and start a cpal input stream on `device` that mixes captured
+/// samples per `channel_mode` and pushes them into `producer`, flagging
+/// `interrupted` if the device disconnects. Used both by `start()` and by
+/// `poll_interrupted`'s reconnect path, so both bind the same
+/// error-detection behavior to the stream.
+fn build_stream(
+    device: &cpal::Device,
+    producer: Arc<Mutex<SampleProducer>>,
+    dropped_samples: Arc<AtomicU64>,
+    interrupted: Arc<AtomicBool>,
+    channel_mode: ChannelMode,
+) -> Result<Stream, RecordError> {
+    let supported_config = AudioDeviceManager::default_input_config(device)
+        .map_err(|e| RecordError::ConfigError(e.to_string()))?;
+
+    let channels = supported_config.channels();
+    let sample_format = supported_config.sample_format();
+    let config = supported_config.into();
+
+    let err_fn = move |err: cpal::StreamError| {
+        eprintln!("audio stream error: {err}");
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            interrupted.store(true, Ordering::Relaxed);
+        }
+    };
 
-// --- Internal helpers ---
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _| {
+                push_samples_f32(&producer, &dropped_samples, data, channels, channel_mode)
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _| {
+                push_samples_i16(&producer, &dropped_samples, data, channels, channel_mode)
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _| {
+                push_samples_u16(&producer, &dropped_samples, data, channels, channel_mode)
+            },
+            err_fn,
+            None,
+        ),
+        _ => return Err(RecordError::UnsupportedFormat(format!("{sample_format:?}"))),
+    }
+    .map_err(|e| RecordError::StreamError(e.to_string()))?;
 
-pub fn generate_filename() -> String {
-    let now = chrono::Local::now();
-    format!("recording-{}.mp3", now.format("%Y%m%d-%H%M%S"))
-}
+    stream
+        .play()
+        .map_err(|e| RecordError::StreamError(e.to_string()))?;
 
-/// Build an MP3 encoder configured for mono output.
-///
-/// Multi-channel input is downmixed to mono before encoding, so the encoder
-/// is always 1-channel regardless of the capture device.
-fn build_mp3_writer(path: &PathBuf, sample_rate: u32) -> Result<Mp3Writer, RecordError> {
-    let mut builder = Builder::new()
-        .ok_or_else(|| RecordError::EncoderError("failed to create LAME builder".into()))?;
-    builder
-        .set_num_channels(1)
-        .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
-    builder
-        .set_sample_rate(sample_rate)
-        .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
-    builder
-        .set_brate(mp3lame_encoder::Bitrate::Kbps192)
-        .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
-    builder
-        .set_quality(mp3lame_encoder::Quality::Best)
-        .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
-
-    let encoder = builder
-        .build()
-        .map_err(|e| RecordError::EncoderError(format!("{e:?}")))?;
-
-    let file = File::create(path).map_err(|e| RecordError::IoError(e.to_string()))?;
-    let file = BufWriter::new(file);
-
-    Ok(Mp3Writer { encoder, file })
+    Ok(stream)
 }
 
-/// Downmix interleaved multi-channel f32 samples to mono by averaging channels.
-fn downmix_to_mono_f32(data: &[f32], channels: u16) -> Vec<f32> {
-    if channels == 1 {
-        return data.to_vec();
+/// Drain the ring buffer and feed the sink from a dedicated thread, off the
+/// real-time audio callback. Runs until `stop()` signals shutdown, then
+/// drains whatever samples are left before finalizing the sink. When
+/// `resampler` is set, every chunk is resampled before reaching the sink.
+fn run_encoder_thread(
+    mut sink: Box<dyn SampleSink>,
+    mut consumer: SampleConsumer,
+    mut resampler: Option<Resampler>,
+    stats: Arc<Mutex<AudioStats>>,
+    level: Arc<AudioLevelCell>,
+    stop_rx: mpsc::Receiver<()>,
+) {
+    let mut chunk = vec![0.0f32; ENCODE_CHUNK_SAMPLES];
+    loop {
+        let n = consumer.pop_slice(&mut chunk);
+        if n > 0 {
+            write_chunk(&mut sink, &mut resampler, &stats, &level, &chunk[..n]);
+        }
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+        if n == 0 {
+            thread::sleep(Duration::from_millis(10));
+        }
     }
-    let ch = channels as usize;
-    data.chunks_exact(ch)
-        .map(|frame| {
-            let sum: f32 = frame.iter().sum();
-            (sum / channels as f32).clamp(-1.0, 1.0)
-        })
-        .collect()
-}
 
-/// Encode f32 PCM samples to MP3 (downmixed to mono).
-fn encode_samples_f32(writer: &Arc<Mutex<Option<Mp3Writer>>>, data: &[f32], channels: u16) {
-    if let Ok(mut guard) = writer.lock() {
-        if let Some(ref mut w) = *guard {
-            let mono = downmix_to_mono_f32(data, channels);
-            encode_mono_f32(w, &mono);
+    // Drain whatever arrived between the last check above and the stream
+    // actually shutting down.
+    loop {
+        let n = consumer.pop_slice(&mut chunk);
+        if n == 0 {
+            break;
         }
+        write_chunk(&mut sink, &mut resampler, &stats, &level, &chunk[..n]);
     }
+
+    sink.finalize();
+}
+
+/// One side of a `CaptureMode::MixWithSystemAudio` recording: its ring
+/// buffer consumer and the gain applied to it before summing with the
+/// other side.
+struct MixSource {
+    consumer: SampleConsumer,
+    gain: f32,
 }
 
-/// Encode i16 PCM samples to MP3 (downmixed to mono, converted to f32).
-fn encode_samples_i16(writer: &Arc<Mutex<Option<Mp3Writer>>>, data: &[i16], channels: u16) {
-    if let Ok(mut guard) = writer.lock() {
-        if let Some(ref mut w) = *guard {
-            let f32_data: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-            let mono = downmix_to_mono_f32(&f32_data, channels);
-            encode_mono_f32(w, &mono);
+/// Like `run_encoder_thread`, but sums two independently-arriving sources
+/// (mic + system loopback) before handing samples to the sink. Each side is
+/// popped for the same chunk size every iteration using however many
+/// samples have arrived so far; whichever side has fewer ready (its stream
+/// underran, or briefly disconnected) is padded with silence so the two
+/// stay aligned rather than drifting out of phase with each other.
+fn run_mixed_encoder_thread(
+    mut sink: Box<dyn SampleSink>,
+    mut mic: MixSource,
+    mut sys: MixSource,
+    mut resampler: Option<Resampler>,
+    stats: Arc<Mutex<AudioStats>>,
+    level: Arc<AudioLevelCell>,
+    stop_rx: mpsc::Receiver<()>,
+) {
+    let mut mic_buf = vec![0.0f32; ENCODE_CHUNK_SAMPLES];
+    let mut sys_buf = vec![0.0f32; ENCODE_CHUNK_SAMPLES];
+    loop {
+        let n = mix_chunk(&mut mic, &mut sys, &mut mic_buf, &mut sys_buf);
+        if n > 0 {
+            let mixed = sum_mixed(&mic, &sys, &mic_buf[..n], &sys_buf[..n]);
+            write_chunk(&mut sink, &mut resampler, &stats, &level, &mixed);
+        }
+        if stop_rx.try_recv().is_ok() {
+            break;
+        }
+        if n == 0 {
+            thread::sleep(Duration::from_millis(10));
         }
     }
-}
 
-/// Encode u16 PCM samples to MP3 (downmixed to mono, converted to f32).
-fn encode_samples_u16(writer: &Arc<Mutex<Option<Mp3Writer>>>, data: &[u16], channels: u16) {
-    if let Ok(mut guard) = writer.lock() {
-        if let Some(ref mut w) = *guard {
-            let f32_data: Vec<f32> = data
-                .iter()
-                .map(|&s| (s as f32 - 32768.0) / 32768.0)
-                .collect();
-            let mono = downmix_to_mono_f32(&f32_data, channels);
-            encode_mono_f32(w, &mono);
+    loop {
+        let n = mix_chunk(&mut mic, &mut sys, &mut mic_buf, &mut sys_buf);
+        if n == 0 {
+            break;
         }
+        let mixed = sum_mixed(&mic, &sys, &mic_buf[..n], &sys_buf[..n]);
+        write_chunk(&mut sink, &mut resampler, &stats, &level, &mixed);
     }
+
+    sink.finalize();
 }
 
-/// Encode mono f32 samples to MP3 and write to file.
-fn encode_mono_f32(w: &mut Mp3Writer, samples: &[f32]) {
-    if samples.is_empty() {
-        return;
-    }
-    let input = MonoPcm(samples);
+/// Pop as many samples as each side has ready into its buffer, padding the
+/// shorter side with silence up to the longer side's arrival count, and
+/// return that shared length.
+fn mix_chunk(
+    mic: &mut MixSource,
+    sys: &mut MixSource,
+    mic_buf: &mut [f32],
+    sys_buf: &mut [f32],
+) -> usize {
+    let n_mic = mic.consumer.pop_slice(mic_buf);
+    let n_sys = sys.consumer.pop_slice(sys_buf);
+    let n = n_mic.max(n_sys);
+    mic_buf[n_mic..n].fill(0.0);
+    sys_buf[n_sys..n].fill(0.0);
+    n
+}
 
-    let mut mp3_buf = Vec::new();
-    mp3_buf.reserve(mp3lame_encoder::max_required_buffer_size(samples.len()));
+/// Sum the two gain-scaled sources sample-for-sample, clamping to avoid
+/// clipping.
+fn sum_mixed(mic: &MixSource, sys: &MixSource, mic_buf: &[f32], sys_buf: &[f32]) -> Vec<f32> {
+    mic_buf
+        .iter()
+        .zip(sys_buf)
+        .map(|(&m, &s)| (m * mic.gain + s * sys.gain).clamp(-1.0, 1.0))
+        .collect()
+}
 
-    match w.encoder.encode(input, mp3_buf.spare_capacity_mut()) {
-        Ok(encoded_size) => {
-            unsafe { mp3_buf.set_len(encoded_size) };
-            let _ = w.file.write_all(&mp3_buf);
-        }
-        Err(e) => {
-            eprintln!("mp3 encode error: {e:?}");
-        }
+/// Resample `samples` (if `resampler` is set) and write the result to
+/// `sink`, first folding the pre-resample samples into `stats` (cumulative,
+/// for silence detection) and publishing them to `level` (latest chunk only,
+/// for the live VU meter) so both reflect what was actually captured rather
+/// than the resampled output.
+fn write_chunk(
+    sink: &mut Box<dyn SampleSink>,
+    resampler: &mut Option<Resampler>,
+    stats: &Arc<Mutex<AudioStats>>,
+    level: &Arc<AudioLevelCell>,
+    samples: &[f32],
+) {
+    stats.lock().unwrap().observe(samples);
+    level.publish(AudioLevel::of(samples));
+    match resampler {
+        Some(r) => sink.write_samples(&r.process(samples)),
+        None => sink.write_samples(samples),
     }
 }
 
@@ -366,6 +1873,9 @@ mod tests {
             .to_string_lossy()
             .contains("Lyre Recordings"));
         assert!(config.selected_device_name.is_none());
+        assert_eq!(config.silence_threshold_db, Some(-45.0));
+        assert_eq!(config.min_duration_ms, Some(300));
+        assert!(!config.auto_upload);
     }
 
     #[test]
@@ -406,14 +1916,59 @@ mod tests {
         assert!(recorder.config.selected_device_name.is_none());
     }
 
+    #[test]
+    fn test_set_auto_upload() {
+        let mut recorder = Recorder::new(RecorderConfig::default());
+        assert!(!recorder.config.auto_upload);
+        recorder.set_auto_upload(true);
+        assert!(recorder.config.auto_upload);
+        recorder.set_auto_upload(false);
+        assert!(!recorder.config.auto_upload);
+    }
+
     #[test]
     fn test_generate_filename() {
-        let filename = generate_filename();
+        let filename = generate_filename(&RecordFormat::default());
         assert!(filename.starts_with("recording-"));
         assert!(filename.ends_with(".mp3"));
         assert!(filename.len() > 20); // recording-YYYYMMDD-HHMMSS.mp3
     }
 
+    #[test]
+    fn test_generate_filename_back_to_back_calls_never_collide() {
+        let names: Vec<String> = (0..50)
+            .map(|_| generate_filename(&RecordFormat::default()))
+            .collect();
+        let unique: std::collections::HashSet<&String> = names.iter().collect();
+        assert_eq!(unique.len(), names.len(), "rapid calls must never collide");
+    }
+
+    #[test]
+    fn test_generate_filename_wav_extension() {
+        let filename = generate_filename(&RecordFormat::Wav);
+        assert!(filename.ends_with(".wav"));
+    }
+
+    #[test]
+    fn test_generate_filename_flac_extension() {
+        let filename = generate_filename(&RecordFormat::Flac);
+        assert!(filename.ends_with(".flac"));
+    }
+
+    #[test]
+    fn test_record_format_key_round_trips_through_from_key() {
+        assert_eq!(RecordFormat::Wav.key(), "wav");
+        assert_eq!(RecordFormat::Flac.key(), "flac");
+        assert!(matches!(RecordFormat::from_key("wav"), Some(RecordFormat::Wav)));
+        assert!(matches!(RecordFormat::from_key("flac"), Some(RecordFormat::Flac)));
+        assert!(matches!(RecordFormat::from_key("mp3"), Some(RecordFormat::Mp3 { .. })));
+    }
+
+    #[test]
+    fn test_record_format_from_key_rejects_unknown_key() {
+        assert!(RecordFormat::from_key("ogg").is_none());
+    }
+
     #[test]
     fn test_record_error_display() {
         assert_eq!(
@@ -440,6 +1995,16 @@ mod tests {
         let config = RecorderConfig {
             output_dir: PathBuf::from("/custom/path"),
             selected_device_name: Some("USB Mic".to_string()),
+            format: RecordFormat::default(),
+            on_device_lost: OnDeviceLost::default(),
+            target_sample_rate: None,
+            channels: ChannelMode::default(),
+            capture: CaptureMode::default(),
+            silence_threshold_db: None,
+            min_duration_ms: None,
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
         };
         let recorder = Recorder::new(config);
         assert_eq!(recorder.config.output_dir, PathBuf::from("/custom/path"));
@@ -455,6 +2020,16 @@ mod tests {
         let config = RecorderConfig {
             output_dir: PathBuf::from("/tmp/test-recordings"),
             selected_device_name: Some("Nonexistent Device XYZ".to_string()),
+            format: RecordFormat::default(),
+            on_device_lost: OnDeviceLost::default(),
+            target_sample_rate: None,
+            channels: ChannelMode::default(),
+            capture: CaptureMode::default(),
+            silence_threshold_db: None,
+            min_duration_ms: None,
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
         };
         let mut recorder = Recorder::new(config);
         let device_manager = AudioDeviceManager::new();
@@ -471,4 +2046,621 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    #[test]
+    fn test_start_rejects_unsupported_target_sample_rate() {
+        let config = RecorderConfig {
+            output_dir: PathBuf::from("/tmp/test-recordings"),
+            selected_device_name: None,
+            format: RecordFormat::default(),
+            on_device_lost: OnDeviceLost::default(),
+            // No real device offers 1 Hz -- this should fail validation
+            // rather than reach stream construction.
+            target_sample_rate: Some(1),
+            channels: ChannelMode::default(),
+            capture: CaptureMode::default(),
+            silence_threshold_db: None,
+            min_duration_ms: None,
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
+        };
+        let mut recorder = Recorder::new(config);
+        let device_manager = AudioDeviceManager::new();
+        if device_manager.default_input_device().is_some() {
+            let result = recorder.start(&device_manager);
+            assert!(matches!(
+                result,
+                Err(RecordError::UnsupportedDeviceFormat(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_spawn_actor_set_output_dir_reports_state_changed() {
+        let config = RecorderConfig {
+            output_dir: PathBuf::from("/tmp/test-recordings"),
+            ..RecorderConfig::default()
+        };
+        let handle = spawn_actor(config, AudioDeviceManager::new());
+
+        handle
+            .commands
+            .send(RecorderCommand::SetOutputDir(PathBuf::from(
+                "/tmp/test-recordings-actor",
+            )))
+            .unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+
+        handle.commands.send(RecorderCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_actor_select_device_none_reports_state_changed() {
+        let handle = spawn_actor(RecorderConfig::default(), AudioDeviceManager::new());
+
+        handle
+            .commands
+            .send(RecorderCommand::SelectDevice(None))
+            .unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+
+        handle.commands.send(RecorderCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_actor_set_capture_mode_reports_state_changed() {
+        let handle = spawn_actor(RecorderConfig::default(), AudioDeviceManager::new());
+
+        handle
+            .commands
+            .send(RecorderCommand::SetCaptureMode(
+                CaptureMode::MixWithSystemAudio {
+                    system_device: "Aggregate Device".to_string(),
+                    mic_gain: 1.0,
+                    system_gain: 1.0,
+                },
+            ))
+            .unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+
+        handle.commands.send(RecorderCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_actor_query_level_when_idle_reports_none() {
+        let handle = spawn_actor(RecorderConfig::default(), AudioDeviceManager::new());
+
+        handle.commands.send(RecorderCommand::QueryLevel).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::Level(None)
+        ));
+        // `QueryLevel` replies with exactly one message -- confirm there's no
+        // second, unread reply still sitting in the channel before `Quit`
+        // sends its own `StateChanged`.
+        assert!(handle.status.try_recv().is_err());
+
+        handle.commands.send(RecorderCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_actor_quit_ends_actor_thread() {
+        let handle = spawn_actor(RecorderConfig::default(), AudioDeviceManager::new());
+        handle.commands.send(RecorderCommand::Quit).unwrap();
+        assert!(matches!(
+            handle.status.recv().unwrap(),
+            RecorderStatus::StateChanged(RecorderState::Idle)
+        ));
+        // The actor thread has exited its command loop, so the sender side of
+        // the status channel is dropped and recv() on a second call errors
+        // instead of blocking forever.
+        assert!(handle.status.recv().is_err());
+    }
+
+    #[test]
+    fn test_record_resamples_and_downmixes_to_configured_output_format() {
+        let device_manager = AudioDeviceManager::new();
+        if device_manager.default_input_device().is_none() {
+            // No audio hardware available in this environment.
+            return;
+        }
+
+        let tmp = std::env::temp_dir().join(format!(
+            "lyre-recorder-format-test-{:?}",
+            thread::current().id()
+        ));
+        let _ = fs::create_dir_all(&tmp);
+
+        let config = RecorderConfig {
+            output_dir: tmp.clone(),
+            selected_device_name: None,
+            format: RecordFormat::Wav,
+            on_device_lost: OnDeviceLost::default(),
+            // The device's native rate/channels may well be 48000 Hz
+            // stereo -- this exercises the resample + downmix path on the
+            // way to the sink.
+            target_sample_rate: Some(22050),
+            channels: ChannelMode::Mono,
+            capture: CaptureMode::default(),
+            silence_threshold_db: None,
+            min_duration_ms: None,
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
+        };
+        let mut recorder = Recorder::new(config);
+        if recorder.start(&device_manager).is_err() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(400));
+        let outcome = recorder.stop().expect("should stop recording");
+
+        let path = match outcome {
+            StopOutcome::Saved(p) => p,
+            // Some CI input devices produce pure digital silence, which
+            // chunk5-4's discard-empty-capture check can still remove even
+            // without a configured `silence_threshold_db` if literally no
+            // frames arrived -- nothing further to assert in that case.
+            StopOutcome::DiscardedSilence => return,
+        };
+
+        let reader = hound::WavReader::open(&path).expect("should open recorded wav");
+        let spec = reader.spec();
+        assert_eq!(spec.sample_rate, 22050);
+        assert_eq!(spec.channels, 1);
+
+        let duration_secs = reader.duration() as f64 / spec.sample_rate as f64;
+        assert!(
+            (0.2..=2.0).contains(&duration_secs),
+            "expected roughly 0.4s recorded, got {duration_secs}"
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stop_discards_recording_shorter_than_min_duration() {
+        let device_manager = AudioDeviceManager::new();
+        if device_manager.default_input_device().is_none() {
+            // No audio hardware available in this environment.
+            return;
+        }
+
+        let tmp = std::env::temp_dir().join(format!(
+            "lyre-recorder-min-duration-test-{:?}",
+            thread::current().id()
+        ));
+        let _ = fs::create_dir_all(&tmp);
+
+        let config = RecorderConfig {
+            output_dir: tmp.clone(),
+            selected_device_name: None,
+            format: RecordFormat::Wav,
+            on_device_lost: OnDeviceLost::default(),
+            target_sample_rate: None,
+            channels: ChannelMode::default(),
+            capture: CaptureMode::default(),
+            silence_threshold_db: None,
+            min_duration_ms: Some(5_000),
+            midi_binding: None,
+            auto_upload: false,
+            segment_duration: None,
+        };
+        let mut recorder = Recorder::new(config);
+        if recorder.start(&device_manager).is_err() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(50));
+        let outcome = recorder.stop().expect("should stop recording");
+        assert_eq!(outcome, StopOutcome::DiscardedSilence);
+    }
+
+    #[test]
+    fn test_segment_filename_zero_pads_index() {
+        assert_eq!(
+            segment_filename("recording-20260221-143052", 1, &RecordFormat::Wav),
+            "recording-20260221-143052-0001.wav"
+        );
+        assert_eq!(
+            segment_filename("recording-20260221-143052", 23, &RecordFormat::Wav),
+            "recording-20260221-143052-0023.wav"
+        );
+    }
+
+    #[test]
+    fn test_segmented_sink_rolls_over_into_numbered_segments_and_writes_manifest() {
+        let tmp = std::env::temp_dir().join(format!(
+            "lyre-segmented-sink-test-{:?}",
+            thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let sample_rate = 8000;
+        // One segment is 100ms of mono audio = 800 samples.
+        let mut sink = SegmentedSink::new(
+            tmp.clone(),
+            "recording-20260221-143052".to_string(),
+            RecordFormat::Wav,
+            sample_rate,
+            1,
+            Duration::from_millis(100),
+        )
+        .expect("should create segmented sink");
+
+        // Write 2.5 segments worth of samples, forcing two rollovers.
+        sink.write_samples(&vec![0.0f32; 800]);
+        sink.write_samples(&vec![0.0f32; 800]);
+        sink.write_samples(&vec![0.0f32; 400]);
+        Box::new(sink).finalize();
+
+        assert!(tmp.join("recording-20260221-143052-0001.wav").exists());
+        assert!(tmp.join("recording-20260221-143052-0002.wav").exists());
+        assert!(tmp.join("recording-20260221-143052-0003.wav").exists());
+
+        let manifest = fs::read_to_string(tmp.join(PLAYLIST_FILENAME)).unwrap();
+        assert!(manifest.starts_with("#EXTM3U\n"));
+        assert_eq!(manifest.matches("#EXTINF:").count(), 3);
+        assert!(manifest.contains("recording-20260221-143052-0001.wav"));
+        assert!(manifest.contains("recording-20260221-143052-0002.wav"));
+        assert!(manifest.contains("recording-20260221-143052-0003.wav"));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_segmented_sink_manifest_valid_after_each_rollover() {
+        // Mirrors the crash-recovery guarantee the request asks for: the
+        // manifest must already list a segment as soon as it closes, not
+        // only once the whole session finishes.
+        let tmp = std::env::temp_dir().join(format!(
+            "lyre-segmented-sink-recovery-test-{:?}",
+            thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&tmp);
+
+        let mut sink = SegmentedSink::new(
+            tmp.clone(),
+            "recording-20260221-143052".to_string(),
+            RecordFormat::Wav,
+            8000,
+            1,
+            Duration::from_millis(100),
+        )
+        .expect("should create segmented sink");
+
+        // Exactly one segment's worth -- triggers a single rollover.
+        sink.write_samples(&vec![0.0f32; 800]);
+
+        let manifest = fs::read_to_string(tmp.join(PLAYLIST_FILENAME)).unwrap();
+        assert_eq!(manifest.matches("#EXTINF:").count(), 1);
+        assert!(manifest.contains("recording-20260221-143052-0001.wav"));
+        // The first segment's own file must already be finalized -- i.e. a
+        // process crash right here would still leave it playable.
+        assert!(hound::WavReader::open(tmp.join("recording-20260221-143052-0001.wav")).is_ok());
+
+        drop(sink);
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_build_sink_flac_writes_valid_stream() {
+        let tmp = std::env::temp_dir().join(format!(
+            "lyre-flac-sink-test-{:?}",
+            thread::current().id()
+        ));
+        let _ = fs::create_dir_all(&tmp);
+        let path = tmp.join("test.flac");
+
+        let mut sink = build_sink(&RecordFormat::Flac, &path, 8000, 1).unwrap();
+        sink.write_samples(&vec![0.0f32; 8000]);
+        sink.finalize();
+
+        let written = fs::read(&path).expect("flac file should exist");
+        assert_eq!(&written[..4], b"fLaC");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_audio_stats_silence_has_neg_infinity_rms() {
+        let stats = AudioStats::default();
+        assert_eq!(stats.rms_db(), f32::NEG_INFINITY);
+
+        let mut silent = AudioStats::default();
+        silent.observe(&[0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(silent.rms_db(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_audio_stats_tracks_peak_and_rms() {
+        let mut stats = AudioStats::default();
+        stats.observe(&[0.5, -0.5, 0.25, -0.25]);
+        assert_eq!(stats.peak, 0.5);
+        assert!(stats.rms_db() > f32::NEG_INFINITY);
+        assert!(stats.rms_db() < 0.0);
+    }
+
+    #[test]
+    fn test_audio_stats_accumulates_across_multiple_observe_calls() {
+        let mut stats = AudioStats::default();
+        stats.observe(&[0.1, 0.1]);
+        stats.observe(&[0.2, 0.2]);
+        assert_eq!(stats.sample_count, 4);
+        assert_eq!(stats.peak, 0.2);
+    }
+
+    #[test]
+    fn test_push_pcm_counts_drops_on_overrun() {
+        let rb = HeapRb::<f32>::new(4);
+        let (producer, _consumer) = rb.split();
+        let producer = Arc::new(Mutex::new(producer));
+        let dropped = Arc::new(AtomicU64::new(0));
+        push_pcm(&producer, &dropped, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_push_pcm_no_drops_when_room_available() {
+        let rb = HeapRb::<f32>::new(8);
+        let (producer, _consumer) = rb.split();
+        let producer = Arc::new(Mutex::new(producer));
+        let dropped = Arc::new(AtomicU64::new(0));
+        push_pcm(&producer, &dropped, &[1.0, 2.0, 3.0]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+
+    /// Simulates a slow disk/writer: nothing ever drains the ring, so a
+    /// burst of pushes overflows it repeatedly. `push_pcm` must still
+    /// return promptly (the real-time callback never blocks on the writer)
+    /// and the overrun counter must reflect everything that didn't fit.
+    #[test]
+    fn test_push_pcm_never_blocks_and_counts_overruns_when_writer_stalls() {
+        let rb = HeapRb::<f32>::new(8);
+        let (producer, mut consumer) = rb.split();
+        let producer = Arc::new(Mutex::new(producer));
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let start = std::time::Instant::now();
+        for _ in 0..50 {
+            push_pcm(&producer, &dropped, &[0.0; 16]);
+        }
+        let elapsed = start.elapsed();
+
+        assert!(
+            elapsed < Duration::from_millis(100),
+            "push_pcm blocked on a stalled writer: took {elapsed:?}"
+        );
+        assert!(dropped.load(Ordering::Relaxed) > 0);
+
+        // The ring buffer itself stays intact despite the overruns — once
+        // the writer catches up it can still drain a full chunk.
+        let mut buf = [0.0f32; 8];
+        assert_eq!(consumer.pop_slice(&mut buf), 8);
+    }
+
+    #[test]
+    fn test_overruns_accessor_reports_zero_when_idle() {
+        let recorder = Recorder::new(RecorderConfig::default());
+        assert_eq!(recorder.overruns(), 0);
+    }
+
+    #[test]
+    fn test_audio_level_of_empty_slice_is_silent() {
+        assert_eq!(AudioLevel::of(&[]), AudioLevel::default());
+    }
+
+    #[test]
+    fn test_audio_level_of_reports_peak_and_rms_of_just_that_chunk() {
+        let level = AudioLevel::of(&[0.5, -0.5, 0.25, -0.25]);
+        assert_eq!(level.peak, 0.5);
+        assert!(level.rms > 0.0 && level.rms < 0.5);
+    }
+
+    #[test]
+    fn test_audio_level_rms_db_of_silence_is_neg_infinity() {
+        assert_eq!(AudioLevel::default().rms_db(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_audio_level_rms_db_of_signal_is_negative_and_finite() {
+        let level = AudioLevel::of(&[0.5, -0.5, 0.5, -0.5]);
+        assert!(level.rms_db() > f32::NEG_INFINITY && level.rms_db() < 0.0);
+    }
+
+    #[test]
+    fn test_audio_level_cell_round_trips_published_value() {
+        let cell = AudioLevelCell::default();
+        assert_eq!(cell.load(), AudioLevel::default());
+
+        cell.publish(AudioLevel { peak: 0.8, rms: 0.3 });
+        assert_eq!(cell.load(), AudioLevel { peak: 0.8, rms: 0.3 });
+    }
+
+    #[test]
+    fn test_audio_level_cell_publish_overwrites_rather_than_accumulates() {
+        let cell = AudioLevelCell::default();
+        cell.publish(AudioLevel { peak: 0.9, rms: 0.5 });
+        cell.publish(AudioLevel { peak: 0.1, rms: 0.05 });
+        assert_eq!(cell.load(), AudioLevel { peak: 0.1, rms: 0.05 });
+    }
+
+    #[test]
+    fn test_current_level_is_none_when_idle() {
+        let recorder = Recorder::new(RecorderConfig::default());
+        assert_eq!(recorder.current_level(), None);
+    }
+
+    #[test]
+    fn test_resampler_passthrough_when_rates_equal() {
+        let mut resampler = Resampler::new(48000, 48000, 1);
+        let input = vec![0.1, 0.2, 0.3, 0.4];
+        assert_eq!(resampler.process(&input), input);
+    }
+
+    #[test]
+    fn test_resampler_halves_sample_count_for_half_rate() {
+        let mut resampler = Resampler::new(48000, 24000, 1);
+        let input: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        let out = resampler.process(&input);
+        // Downsampling by 2x should yield roughly half as many samples.
+        assert!(out.len() >= 48 && out.len() <= 50);
+        // Output should track the (linearly increasing) input closely.
+        assert!((out[0] - 0.0).abs() < 1.0);
+        assert!((out[out.len() - 1] - 98.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_resampler_carries_phase_across_calls() {
+        let mut resampler = Resampler::new(48000, 16000, 1);
+        let first = resampler.process(&[0.0, 1.0, 2.0, 3.0]);
+        let second = resampler.process(&[4.0, 5.0, 6.0, 7.0]);
+        // With a 3x downsample ratio the first call consumes more source
+        // samples than it has available, carrying leftover phase forward
+        // rather than dropping it — so the second call must still produce
+        // at least one sample once the carried phase lands inside it.
+        assert!(!first.is_empty());
+        assert!(!second.is_empty());
+    }
+
+    #[test]
+    fn test_resampler_preserves_rate_across_buffer_boundaries() {
+        // 24kHz -> 48kHz should double the sample count over many callbacks,
+        // even when fed in small chunks that don't divide evenly -- this
+        // catches an off-by-one in the carried `phase` that would otherwise
+        // silently drift the output rate over many calls.
+        let mut resampler = Resampler::new(24000, 48000, 1);
+        let mut total_out = 0;
+        for _ in 0..10 {
+            let chunk = vec![0.5_f32; 7];
+            total_out += resampler.process(&chunk).len();
+        }
+        assert!((130..=150).contains(&total_out));
+    }
+
+    #[test]
+    fn test_downmix_to_stereo_mono_duplicates_to_both_channels() {
+        let out = downmix_to_stereo_f32(&[0.5, -0.5], 1);
+        assert_eq!(out, vec![0.5, 0.5, -0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_downmix_to_stereo_passthrough() {
+        let out = downmix_to_stereo_f32(&[0.1, 0.2, 0.3, 0.4], 2);
+        assert_eq!(out, vec![0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_downmix_to_stereo_four_channel_takes_front_pair() {
+        let out = downmix_to_stereo_f32(&[0.1, 0.2, 0.3, 0.4], 4);
+        assert_eq!(out, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_downmix_to_stereo_other_channel_counts_fold_equally() {
+        let out = downmix_to_stereo_f32(&[1.0, 1.0, 1.0], 3);
+        let expected = 3.0 / (3.0_f32).sqrt();
+        assert!((out[0] - expected.min(1.0)).abs() < 1e-5);
+        assert_eq!(out[0], out[1]);
+    }
+
+    #[test]
+    fn test_extract_channel_pulls_requested_channel() {
+        let out = extract_channel_f32(&[1.0, 2.0, 3.0, 4.0], 2, 1);
+        assert_eq!(out, vec![2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_extract_channel_missing_channel_is_silence() {
+        let out = extract_channel_f32(&[1.0, 2.0], 2, 5);
+        assert_eq!(out, vec![0.0]);
+    }
+
+    #[test]
+    fn test_mix_channels_dispatches_by_mode() {
+        let data = [0.5, -0.5];
+        assert_eq!(mix_channels(&data, 2, ChannelMode::Mono), vec![0.0]);
+        assert_eq!(
+            mix_channels(&data, 2, ChannelMode::Stereo),
+            vec![0.5, -0.5]
+        );
+        assert_eq!(
+            mix_channels(&data, 2, ChannelMode::FromChannel(0)),
+            vec![0.5]
+        );
+    }
+
+    #[test]
+    fn test_loopback_unavailable_display() {
+        assert_eq!(
+            RecordError::LoopbackUnavailable("BlackHole 2ch".into()).to_string(),
+            "system audio loopback device 'BlackHole 2ch' not found"
+        );
+    }
+
+    #[test]
+    fn test_sum_mixed_applies_gain_and_clamps() {
+        let rb_a = HeapRb::<f32>::new(4);
+        let (_p, c_a) = rb_a.split();
+        let rb_b = HeapRb::<f32>::new(4);
+        let (_p, c_b) = rb_b.split();
+        let mic = MixSource {
+            consumer: c_a,
+            gain: 1.0,
+        };
+        let sys = MixSource {
+            consumer: c_b,
+            gain: 1.0,
+        };
+        let out = sum_mixed(&mic, &sys, &[0.8, -0.8], &[0.8, -0.8]);
+        assert_eq!(out, vec![1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_mix_chunk_pads_shorter_side_with_silence() {
+        let rb_a = HeapRb::<f32>::new(4);
+        let (mut p_a, c_a) = rb_a.split();
+        let rb_b = HeapRb::<f32>::new(4);
+        let (_p_b, c_b) = rb_b.split();
+        p_a.push_slice(&[1.0, 2.0]);
+        let mut mic = MixSource {
+            consumer: c_a,
+            gain: 1.0,
+        };
+        let mut sys = MixSource {
+            consumer: c_b,
+            gain: 1.0,
+        };
+        let mut mic_buf = vec![0.0f32; 4];
+        let mut sys_buf = vec![0.0f32; 4];
+        let n = mix_chunk(&mut mic, &mut sys, &mut mic_buf, &mut sys_buf);
+        assert_eq!(n, 2);
+        assert_eq!(&mic_buf[..2], &[1.0, 2.0]);
+        assert_eq!(&sys_buf[..2], &[0.0, 0.0]);
+    }
 }
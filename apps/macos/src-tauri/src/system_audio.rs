@@ -1,20 +1,27 @@
-//! System audio capture via ScreenCaptureKit.
+//! System audio capture, behind a `CaptureBackend` abstraction so the rest
+//! of the crate (`AudioDataHandler`, `ClosureAudioHandler`, downstream MP3
+//! writing) never needs to know which OS-specific implementation is active.
 //!
-//! Uses macOS 15.0+ APIs to capture both system audio and microphone input
-//! in a single stream, eliminating the need for separate cpal-based capture.
+//! On macOS, the default backend is ScreenCaptureKit (macOS 15.0+), which
+//! captures system audio and microphone input in a single stream. On other
+//! platforms -- and on macOS if explicitly requested via
+//! `CaptureConfig.backend` -- capture falls back to cpal (ALSA on Linux,
+//! WASAPI on Windows, CoreAudio on macOS), which captures only the default
+//! (or configured) input device; cpal has no cross-platform system-audio
+//! loopback API.
 //!
 //! # Permission
 //!
 //! ScreenCaptureKit requires "Screen & System Audio Recording" permission
 //! in System Settings > Privacy & Security. The permission check is done
 //! by attempting `SCShareableContent::get()` — if it fails, the user has
-//! not granted permission.
+//! not granted permission. The cpal backend has no equivalent OS permission
+//! gate to check; see `cpal_backend::CpalBackend::check_permission`.
 
-use screencapturekit::prelude::*;
-use screencapturekit::stream::configuration::SCPresenterOverlayAlertSetting;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
-/// Permission status for ScreenCaptureKit.
+/// Permission status for system audio capture.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PermissionStatus {
@@ -24,33 +31,34 @@ pub enum PermissionStatus {
     Denied,
 }
 
-/// Check whether the app has ScreenCaptureKit permission.
-///
-/// This attempts `SCShareableContent::get()`, which fails if the user
-/// has not granted "Screen & System Audio Recording" permission.
-pub fn check_permission() -> PermissionStatus {
-    match SCShareableContent::get() {
-        Ok(_) => PermissionStatus::Granted,
-        Err(_) => PermissionStatus::Denied,
-    }
-}
-
-/// Information about an audio input device (from ScreenCaptureKit).
+/// Information about an audio input device.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AudioInputDeviceInfo {
     pub id: String,
     pub name: String,
 }
 
-/// List available audio input devices via ScreenCaptureKit.
+/// Which `CaptureBackend` to use. `Auto` (the default) picks
+/// ScreenCaptureKit on macOS and cpal everywhere else; the explicit variants
+/// let a caller opt into cpal on macOS too (e.g. to test that path), or fail
+/// fast if ScreenCaptureKit is requested on a non-macOS build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureBackendKind {
+    #[default]
+    Auto,
+    ScreenCaptureKit,
+    Cpal,
+}
+
+/// Check whether system audio capture is permitted, using the
+/// platform-default backend (see `CaptureBackendKind::Auto`).
+pub fn check_permission() -> PermissionStatus {
+    default_backend().check_permission()
+}
+
+/// List available audio input devices, using the platform-default backend.
 pub fn list_audio_input_devices() -> Vec<AudioInputDeviceInfo> {
-    screencapturekit::audio_devices::AudioInputDevice::list()
-        .into_iter()
-        .map(|d| AudioInputDeviceInfo {
-            id: d.id,
-            name: d.name,
-        })
-        .collect()
+    default_backend().list_audio_input_devices()
 }
 
 /// Callback trait for receiving mixed audio PCM data.
@@ -84,17 +92,50 @@ impl<F: Fn(&[f32]) + Send + 'static> AudioDataHandler for ClosureAudioHandler<F>
     }
 }
 
+/// Identifies one of the two sources in multitrack capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackId {
+    System,
+    Microphone,
+}
+
+/// Callback trait for receiving timestamp-aligned per-source audio, as an
+/// alternative to `AudioDataHandler`'s single pre-mixed mono stream. See
+/// `start_multitrack_capture`.
+///
+/// Each track is delivered as mono f32 PCM at the configured sample rate,
+/// the same as `AudioDataHandler`; the two tracks stay the same length and
+/// in sync with each other, with silence filled in for any gap in either
+/// source.
+pub trait MultiTrackHandler: Send + 'static {
+    fn on_track_data(&self, track: TrackId, samples: &[f32]);
+    #[allow(dead_code)]
+    fn on_error(&self, error: String);
+}
+
 /// Configuration for system audio capture.
 #[derive(Debug, Clone)]
 pub struct CaptureConfig {
     /// Sample rate in Hz (default: 48000).
     pub sample_rate: u32,
-    /// Whether to capture system audio (default: true).
+    /// Whether to capture system audio (default: true). Ignored by the cpal
+    /// backend, which can only capture the microphone.
     pub capture_system_audio: bool,
     /// Whether to capture microphone (default: true).
     pub capture_microphone: bool,
     /// Specific microphone device ID. None = system default.
     pub microphone_device_id: Option<String>,
+    /// Which `CaptureBackend` to use (default: `Auto`).
+    pub backend: CaptureBackendKind,
+    /// Capacity, in milliseconds, of each source's ring buffer in the
+    /// ScreenCaptureKit backend's `AudioMixer` (default: 200ms). Only used
+    /// when `capture_microphone` is set.
+    pub buffer_ms: u32,
+    /// Initial per-source gains and makeup gain for the `AudioMixer`
+    /// (default: unity). Only used by `start_capture` when
+    /// `capture_microphone` is set; see `SystemAudioCapture::mix_control`
+    /// to adjust these live, after capture has started.
+    pub mix_settings: MixSettings,
 }
 
 impl Default for CaptureConfig {
@@ -104,336 +145,1517 @@ impl Default for CaptureConfig {
             capture_system_audio: true,
             capture_microphone: true,
             microphone_device_id: None,
+            backend: CaptureBackendKind::default(),
+            buffer_ms: 200,
+            mix_settings: MixSettings::default(),
+        }
+    }
+}
+
+/// Initial gains for the `AudioMixer`, applied before the two sources are
+/// summed and soft-clipped. There's no stereo pan control -- the capture
+/// pipeline is mono end-to-end, from ScreenCaptureKit's single-channel
+/// stream configuration through to the mixer -- so panning isn't something
+/// there's a channel to apply it to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixSettings {
+    /// Gain applied to system audio before mixing (default: 1.0).
+    pub gain_system: f32,
+    /// Gain applied to the microphone before mixing (default: 1.0). Set to
+    /// 0.0 to monitor-mute the mic without tearing down the session.
+    pub gain_mic: f32,
+    /// Gain applied to the summed signal before soft-clipping (default: 1.0).
+    pub makeup_gain: f32,
+}
+
+impl Default for MixSettings {
+    fn default() -> Self {
+        Self {
+            gain_system: 1.0,
+            gain_mic: 1.0,
+            makeup_gain: 1.0,
+        }
+    }
+}
+
+/// Lock-free handle to a running `AudioMixer`'s gains, so callers can adjust
+/// them (e.g. from a volume slider) without tearing down and restarting
+/// capture. Stores each `f32` as bit-cast `AtomicU32`, the same convention
+/// `recorder::AudioLevelCell` uses for its live level readout.
+pub struct MixControl {
+    gain_system: AtomicU32,
+    gain_mic: AtomicU32,
+    makeup_gain: AtomicU32,
+}
+
+impl MixControl {
+    fn new(settings: MixSettings) -> Self {
+        Self {
+            gain_system: AtomicU32::new(settings.gain_system.to_bits()),
+            gain_mic: AtomicU32::new(settings.gain_mic.to_bits()),
+            makeup_gain: AtomicU32::new(settings.makeup_gain.to_bits()),
+        }
+    }
+
+    pub fn gain_system(&self) -> f32 {
+        f32::from_bits(self.gain_system.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain_system(&self, gain: f32) {
+        self.gain_system.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn gain_mic(&self) -> f32 {
+        f32::from_bits(self.gain_mic.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain_mic(&self, gain: f32) {
+        self.gain_mic.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn makeup_gain(&self) -> f32 {
+        f32::from_bits(self.makeup_gain.load(Ordering::Relaxed))
+    }
+
+    pub fn set_makeup_gain(&self, gain: f32) {
+        self.makeup_gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// An OS-specific audio capture implementation. The existing
+/// ScreenCaptureKit implementation and the new cpal-based one both
+/// implement this, so `check_permission`/`list_audio_input_devices`/
+/// `start_capture` can dispatch to whichever is active without the rest of
+/// the crate caring which it is.
+trait CaptureBackend {
+    fn check_permission(&self) -> PermissionStatus;
+    fn list_audio_input_devices(&self) -> Vec<AudioInputDeviceInfo>;
+    fn start_capture(
+        &self,
+        config: &CaptureConfig,
+        handler: Box<dyn AudioDataHandler>,
+    ) -> Result<Box<dyn CaptureSession>, CaptureError>;
+
+    /// Start capture delivering system audio and microphone as separate
+    /// synchronized tracks instead of one pre-mixed mono stream. Only the
+    /// ScreenCaptureKit backend has two independent sources to align; every
+    /// other backend keeps the default, which reports itself unavailable.
+    fn start_multitrack_capture(
+        &self,
+        _config: &CaptureConfig,
+        _handler: Box<dyn MultiTrackHandler>,
+    ) -> Result<Box<dyn CaptureSession>, CaptureError> {
+        Err(CaptureError::BackendUnavailable(
+            "this backend does not support multitrack capture".into(),
+        ))
+    }
+}
+
+/// A running capture session, regardless of which `CaptureBackend` started
+/// it. Not `Send` -- like the underlying OS stream types it wraps, a
+/// session must stay on the thread that created it (cpal's `Stream` is
+/// !Send on macOS; see `recorder::Recorder`'s equivalent note).
+trait CaptureSession {
+    fn stop(self: Box<Self>) -> Result<(), CaptureError>;
+
+    /// A live handle to this session's mixer gains, if it has one. Only the
+    /// ScreenCaptureKit backend's two-source (mixer) mode has gains to
+    /// control; every other session returns `None`.
+    fn mix_control(&self) -> Option<Arc<MixControl>> {
+        None
+    }
+}
+
+/// Resolve `kind` to a concrete backend, `Auto` resolving to the
+/// platform default (ScreenCaptureKit on macOS, cpal elsewhere).
+fn resolve_backend(kind: CaptureBackendKind) -> Result<Box<dyn CaptureBackend>, CaptureError> {
+    match kind {
+        CaptureBackendKind::Auto => Ok(default_backend()),
+        CaptureBackendKind::Cpal => Ok(Box::new(cpal_backend::CpalBackend)),
+        #[cfg(target_os = "macos")]
+        CaptureBackendKind::ScreenCaptureKit => {
+            Ok(Box::new(screencapturekit_backend::ScreenCaptureKitBackend))
         }
+        #[cfg(not(target_os = "macos"))]
+        CaptureBackendKind::ScreenCaptureKit => Err(CaptureError::BackendUnavailable(
+            "ScreenCaptureKit is only available on macOS".into(),
+        )),
+    }
+}
+
+/// The platform-default backend: ScreenCaptureKit on macOS, cpal elsewhere.
+fn default_backend() -> Box<dyn CaptureBackend> {
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(screencapturekit_backend::ScreenCaptureKitBackend)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Box::new(cpal_backend::CpalBackend)
     }
 }
 
-/// Active system audio capture session.
+/// Active system audio capture session, backed by whichever
+/// `CaptureBackend` `start_capture` selected.
 ///
-/// Wraps an `SCStream` that captures system audio and/or microphone.
 /// Audio data is delivered as mono f32 PCM via the provided handler.
-///
 /// Drop this struct to stop capture.
 pub struct SystemAudioCapture {
-    stream: SCStream,
+    session: Box<dyn CaptureSession>,
 }
 
-/// Real-time mixer for two audio streams (system audio + microphone).
-///
-/// ScreenCaptureKit delivers system audio and microphone as **separate**
-/// `CMSampleBuffer` streams.  Simply concatenating them doubles the duration.
-/// This mixer accumulates samples from each source into independent buffers,
-/// and whenever both have data it mixes them (sample-by-sample average) and
-/// flushes the result to the downstream handler.
+impl SystemAudioCapture {
+    /// Stop the capture.
+    pub fn stop(self) -> Result<(), CaptureError> {
+        self.session.stop()
+    }
+
+    /// A live handle to this session's mixer gains, if it has one -- i.e.
+    /// a ScreenCaptureKit `start_capture` session with the microphone
+    /// enabled. `None` for system-audio-only and multitrack sessions and
+    /// the cpal backend, none of which mix anything.
+    pub fn mix_control(&self) -> Option<Arc<MixControl>> {
+        self.session.mix_control()
+    }
+}
+
+/// Start system audio capture using the backend selected by
+/// `config.backend` (or the platform default, if `Auto`).
 ///
-/// When only one source delivers data (e.g. mic permission not granted),
-/// the mixer drains that source directly after a configurable timeout
-/// (`max_pending_samples`) to avoid unbounded buffering.
-struct AudioMixer {
-    /// Downstream handler that receives mixed PCM.
-    handler: Arc<Mutex<Box<dyn AudioDataHandler>>>,
-    /// Pending system audio samples.
-    system_buf: Vec<f32>,
-    /// Pending microphone samples.
-    mic_buf: Vec<f32>,
-    /// Maximum samples to buffer before draining a single source.
-    /// At 48 kHz mono this is ~100 ms (4800 samples).
-    max_pending_samples: usize,
+/// Returns a `SystemAudioCapture` handle. The capture runs until the
+/// handle is dropped or `stop()` is called.
+pub fn start_capture(
+    config: &CaptureConfig,
+    handler: Box<dyn AudioDataHandler>,
+) -> Result<SystemAudioCapture, CaptureError> {
+    let backend = resolve_backend(config.backend)?;
+    let session = backend.start_capture(config, handler)?;
+    Ok(SystemAudioCapture { session })
 }
 
-impl AudioMixer {
-    fn new(handler: Arc<Mutex<Box<dyn AudioDataHandler>>>) -> Self {
-        Self {
-            handler,
-            system_buf: Vec::with_capacity(4800),
-            mic_buf: Vec::with_capacity(4800),
-            // ~100 ms at 48 kHz — generous enough to absorb scheduling jitter
-            // between the two callback queues, short enough to keep latency low.
-            max_pending_samples: 4800,
+/// Start system audio capture with system audio and microphone delivered
+/// as separate synchronized tracks, instead of pre-mixed into one mono
+/// stream -- e.g. for speaker-attributed transcription or a two-channel
+/// recording. Requires a backend with two independent sources; currently
+/// only the ScreenCaptureKit backend supports it (with both
+/// `capture_system_audio` and `capture_microphone` set), so `config.backend`
+/// resolving to cpal returns `CaptureError::BackendUnavailable`.
+pub fn start_multitrack_capture(
+    config: &CaptureConfig,
+    handler: Box<dyn MultiTrackHandler>,
+) -> Result<SystemAudioCapture, CaptureError> {
+    let backend = resolve_backend(config.backend)?;
+    let session = backend.start_multitrack_capture(config, handler)?;
+    Ok(SystemAudioCapture { session })
+}
+
+// Mono resampling used when a capture device delivers audio at a rate other
+// than `CaptureConfig.sample_rate`, so the mixer and downstream encoder
+// always see a consistent rate regardless of hardware. This used to be a
+// second, independent linear-interpolation resampler living here; it's now
+// just `recorder::Resampler` called with `channels: 1`, so there's a single
+// implementation of the phase-carrying math to get right.
+use crate::recorder::Resampler;
+
+/// ScreenCaptureKit-backed `CaptureBackend`, the default on macOS 15.0+.
+/// Captures system audio and microphone input in a single stream.
+#[cfg(target_os = "macos")]
+mod screencapturekit_backend {
+    use super::{
+        AudioDataHandler, CaptureBackend, CaptureConfig, CaptureError, CaptureSession,
+        PermissionStatus, Resampler,
+    };
+    use screencapturekit::prelude::*;
+    use screencapturekit::stream::configuration::SCPresenterOverlayAlertSetting;
+    use std::sync::{Arc, Mutex};
+
+    pub(super) struct ScreenCaptureKitBackend;
+
+    impl CaptureBackend for ScreenCaptureKitBackend {
+        /// Check whether the app has ScreenCaptureKit permission.
+        ///
+        /// This attempts `SCShareableContent::get()`, which fails if the user
+        /// has not granted "Screen & System Audio Recording" permission.
+        fn check_permission(&self) -> PermissionStatus {
+            match SCShareableContent::get() {
+                Ok(_) => PermissionStatus::Granted,
+                Err(_) => PermissionStatus::Denied,
+            }
+        }
+
+        /// List available audio input devices via ScreenCaptureKit.
+        fn list_audio_input_devices(&self) -> Vec<super::AudioInputDeviceInfo> {
+            screencapturekit::audio_devices::AudioInputDevice::list()
+                .into_iter()
+                .map(|d| super::AudioInputDeviceInfo {
+                    id: d.id,
+                    name: d.name,
+                })
+                .collect()
+        }
+
+        /// Start system audio capture.
+        ///
+        /// Returns a session that runs until dropped or `stop()` is called.
+        fn start_capture(
+            &self,
+            config: &CaptureConfig,
+            handler: Box<dyn AudioDataHandler>,
+        ) -> Result<Box<dyn CaptureSession>, CaptureError> {
+            let filter_config = build_configured_stream(config)?;
+            let mut stream = SCStream::new(&filter_config.filter, &filter_config.stream_config);
+
+            // Create stream and register output handlers.
+            //
+            // Apple's SCStream requires a separate `addStreamOutput(_:type:)` call for
+            // each output type you want to receive.  Without registering for
+            // `SCStreamOutputType::Microphone`, the system never delivers mic buffers
+            // even when `set_captures_microphone(true)` is set.
+            //
+            // CRATE BUG (screencapturekit v1.5): The crate's `sample_handler` callback
+            // dispatches every buffer to ALL registered handlers, ignoring the output
+            // type they were registered for.  To prevent double-processing, each
+            // `AudioOutputHandler` carries an `expected_type` field and silently drops
+            // buffers that don't match.  This is forward-compatible: if the crate fixes
+            // the bug, each handler only receives its own type and the filter is a
+            // harmless no-op.
+            //
+            // When both system audio and microphone are enabled, an `AudioMixer` sits
+            // between the two handlers and the downstream `AudioDataHandler`.  The
+            // mixer accumulates samples from each source and outputs one gain-weighted,
+            // soft-clipped sum per timeline position, so that the final MP3 has the
+            // correct duration (not 2×).
+            let handler = Arc::new(Mutex::new(handler));
+
+            let mix_control = if config.capture_microphone {
+                // Two-source mode: route both through AudioMixer
+                let mix_control = Arc::new(super::MixControl::new(config.mix_settings));
+                let mixer = Arc::new(Mutex::new(AudioMixer::new(
+                    Arc::clone(&handler),
+                    config.sample_rate,
+                    config.buffer_ms,
+                    Arc::clone(&mix_control),
+                )));
+
+                let audio_handler = AudioOutputHandler {
+                    target: OutputTarget::Mixer(Arc::clone(&mixer)),
+                    channels: 1,
+                    expected_type: SCStreamOutputType::Audio,
+                    target_sample_rate: config.sample_rate,
+                    resampler: Mutex::new(None),
+                };
+                stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+
+                let mic_handler = AudioOutputHandler {
+                    target: OutputTarget::Mixer(mixer),
+                    channels: 1,
+                    expected_type: SCStreamOutputType::Microphone,
+                    target_sample_rate: config.sample_rate,
+                    resampler: Mutex::new(None),
+                };
+                stream.add_output_handler(mic_handler, SCStreamOutputType::Microphone);
+                Some(mix_control)
+            } else {
+                // Single-source mode: direct passthrough, no mixer overhead
+                let audio_handler = AudioOutputHandler {
+                    target: OutputTarget::Direct(handler),
+                    channels: 1,
+                    expected_type: SCStreamOutputType::Audio,
+                    target_sample_rate: config.sample_rate,
+                    resampler: Mutex::new(None),
+                };
+                stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+                None
+            };
+
+            stream
+                .start_capture()
+                .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+
+            Ok(Box::new(ScreenCaptureKitSession {
+                stream,
+                mix_control,
+            }))
+        }
+
+        /// Multitrack capture requires both sources -- there's nothing to
+        /// align if only one is enabled, so callers wanting a single
+        /// passthrough track should use `start_capture` instead.
+        fn start_multitrack_capture(
+            &self,
+            config: &CaptureConfig,
+            handler: Box<dyn super::MultiTrackHandler>,
+        ) -> Result<Box<dyn CaptureSession>, CaptureError> {
+            if !(config.capture_system_audio && config.capture_microphone) {
+                return Err(CaptureError::BackendUnavailable(
+                    "multitrack capture requires both system audio and microphone".into(),
+                ));
+            }
+
+            let filter_config = build_configured_stream(config)?;
+            let mut stream = SCStream::new(&filter_config.filter, &filter_config.stream_config);
+
+            let handler = Arc::new(Mutex::new(handler));
+            let aligner = Arc::new(Mutex::new(MultiTrackAligner::new(
+                Arc::clone(&handler),
+                config.sample_rate,
+                config.buffer_ms,
+            )));
+
+            let audio_handler = AudioOutputHandler {
+                target: OutputTarget::MultiTrack(Arc::clone(&aligner)),
+                channels: 1,
+                expected_type: SCStreamOutputType::Audio,
+                target_sample_rate: config.sample_rate,
+                resampler: Mutex::new(None),
+            };
+            stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+
+            let mic_handler = AudioOutputHandler {
+                target: OutputTarget::MultiTrack(aligner),
+                channels: 1,
+                expected_type: SCStreamOutputType::Microphone,
+                target_sample_rate: config.sample_rate,
+                resampler: Mutex::new(None),
+            };
+            stream.add_output_handler(mic_handler, SCStreamOutputType::Microphone);
+
+            stream
+                .start_capture()
+                .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+
+            Ok(Box::new(ScreenCaptureKitSession {
+                stream,
+                mix_control: None,
+            }))
         }
     }
 
-    /// Push system audio samples and attempt to mix + flush.
-    fn push_system(&mut self, samples: &[f32]) {
-        self.system_buf.extend_from_slice(samples);
-        self.try_mix();
+    /// Shared display lookup + stream configuration for `start_capture` and
+    /// `start_multitrack_capture`, which differ only in which output targets
+    /// they wire up.
+    struct ConfiguredStream {
+        filter: SCContentFilter,
+        stream_config: SCStreamConfiguration,
     }
 
-    /// Push microphone samples and attempt to mix + flush.
-    fn push_mic(&mut self, samples: &[f32]) {
-        self.mic_buf.extend_from_slice(samples);
-        self.try_mix();
+    fn build_configured_stream(config: &CaptureConfig) -> Result<ConfiguredStream, CaptureError> {
+        // 1. Get shareable content (also serves as permission check)
+        let content = SCShareableContent::get().map_err(|e| {
+            CaptureError::PermissionDenied(format!(
+                "screen capture permission denied or unavailable: {e}"
+            ))
+        })?;
+
+        // 2. Get the first display for the content filter.
+        //    Even for audio-only capture, ScreenCaptureKit requires a display filter.
+        let display = content
+            .displays()
+            .into_iter()
+            .next()
+            .ok_or(CaptureError::NoDisplay)?;
+
+        // 3. Build content filter
+        let filter = SCContentFilter::create()
+            .with_display(&display)
+            .with_excluding_windows(&[])
+            .build();
+
+        // 4. Configure stream — audio-only, no video
+        let mut stream_config = SCStreamConfiguration::new()
+            // Minimal video config (required but not used)
+            .with_width(2)
+            .with_height(2)
+            .with_minimum_frame_interval(&CMTime::new(1, 1)); // 1 FPS minimum
+
+        // System audio
+        if config.capture_system_audio {
+            stream_config = stream_config
+                .with_captures_audio(true)
+                .with_sample_rate(config.sample_rate as i32)
+                .with_channel_count(1); // Mono for voice recording
+        }
+
+        // Microphone (macOS 15.0+)
+        if config.capture_microphone {
+            stream_config.set_captures_microphone(true);
+
+            if let Some(ref device_id) = config.microphone_device_id {
+                stream_config.set_microphone_capture_device_id(device_id);
+            }
+        }
+
+        // Suppress presenter overlay privacy alert
+        stream_config
+            .set_presenter_overlay_privacy_alert_setting(SCPresenterOverlayAlertSetting::Never);
+
+        Ok(ConfiguredStream {
+            filter,
+            stream_config,
+        })
+    }
+
+    /// Capture session backed by an active `SCStream`.
+    struct ScreenCaptureKitSession {
+        stream: SCStream,
+        /// `Some` only in two-source (mixer) mode.
+        mix_control: Option<Arc<super::MixControl>>,
+    }
+
+    impl CaptureSession for ScreenCaptureKitSession {
+        fn stop(self: Box<Self>) -> Result<(), CaptureError> {
+            self.stream
+                .stop_capture()
+                .map_err(|e| CaptureError::StopFailed(format!("{e}")))
+        }
+
+        fn mix_control(&self) -> Option<Arc<super::MixControl>> {
+            self.mix_control.clone()
+        }
     }
 
-    /// Mix overlapping samples from both buffers and flush to downstream.
+    /// Fixed-capacity circular buffer over one source's span of the mixer's
+    /// shared sample timeline. Slot `index % capacity` holds the sample for
+    /// absolute `index`, once written -- writing never reallocates or shifts
+    /// existing data, unlike a `Vec` you `drain` from the front.
     ///
-    /// Takes the minimum length of the two buffers, mixes those samples,
-    /// and drains them.  If only one buffer has accumulated beyond
-    /// `max_pending_samples` (the other source is silent / not delivering),
-    /// drain that buffer directly so we don't block indefinitely.
-    fn try_mix(&mut self) {
-        let overlap = self.system_buf.len().min(self.mic_buf.len());
+    /// If the producer writes samples faster than the mixer reads them, the
+    /// ring wraps around and overwrites not-yet-read samples; `write` reports
+    /// how many samples this cost so the caller can surface it as an overrun
+    /// instead of letting the buffer grow unbounded.
+    struct SampleRing {
+        buf: Vec<f32>,
+        capacity: u64,
+        /// Absolute index one past the last sample written. Zero until the
+        /// first write, which anchors the ring to that write's start index.
+        write_cursor: u64,
+        /// Oldest absolute index still considered valid; reads before this
+        /// return `None` because that data has been overwritten.
+        valid_from: u64,
+        started: bool,
+    }
 
-        if overlap > 0 {
-            // Mix overlapping region: simple average, clamped to [-1, 1].
-            let mixed: Vec<f32> = self.system_buf[..overlap]
-                .iter()
-                .zip(&self.mic_buf[..overlap])
-                .map(|(&a, &b)| ((a + b) * 0.5).clamp(-1.0, 1.0))
-                .collect();
+    impl SampleRing {
+        fn new(capacity: usize) -> Self {
+            Self {
+                buf: vec![0.0; capacity.max(1)],
+                capacity: capacity.max(1) as u64,
+                write_cursor: 0,
+                valid_from: 0,
+                started: false,
+            }
+        }
 
-            self.system_buf.drain(..overlap);
-            self.mic_buf.drain(..overlap);
+        /// Write `samples` starting at `start_index`. Returns the number of
+        /// samples that overran the ring (overwritten before ever being
+        /// read) -- non-zero only when the producer has outpaced the reader.
+        fn write(&mut self, start_index: u64, samples: &[f32]) -> u64 {
+            if !self.started {
+                self.started = true;
+                self.write_cursor = start_index;
+                self.valid_from = start_index;
+            } else if start_index > self.write_cursor {
+                // Gap since the last write (late buffer or dropped packet):
+                // jump the cursor forward, leaving the gap as silence.
+                self.write_cursor = start_index;
+            }
 
-            self.emit(&mixed);
-            return;
+            let mut overrun = 0u64;
+            for &sample in samples {
+                let index = self.write_cursor;
+                if index - self.valid_from >= self.capacity {
+                    overrun += 1;
+                    self.valid_from = index - self.capacity + 1;
+                }
+                let slot = (index % self.capacity) as usize;
+                self.buf[slot] = sample;
+                self.write_cursor += 1;
+            }
+            overrun
         }
 
-        // Drain whichever single source exceeds the threshold (the other
-        // source is presumably not delivering, e.g. no mic permission).
-        if self.system_buf.len() >= self.max_pending_samples {
-            let drained: Vec<f32> = self.system_buf.drain(..).collect();
-            self.emit(&drained);
-        } else if self.mic_buf.len() >= self.max_pending_samples {
-            let drained: Vec<f32> = self.mic_buf.drain(..).collect();
-            self.emit(&drained);
+        /// Read the sample at absolute `index`. Returns `None` if `index`
+        /// hasn't been written yet (a gap) or has already been overwritten.
+        fn read(&self, index: u64) -> Option<f32> {
+            if !self.started || index < self.valid_from || index >= self.write_cursor {
+                return None;
+            }
+            Some(self.buf[(index % self.capacity) as usize])
         }
-    }
 
-    fn emit(&self, samples: &[f32]) {
-        if let Ok(h) = self.handler.lock() {
-            h.on_audio_data(samples);
+        /// The absolute index one past the last sample written.
+        fn available_until(&self) -> u64 {
+            self.write_cursor
+        }
+
+        /// Tell the ring that everything before `index` has been consumed,
+        /// so capacity checks measure how far the producer has run ahead of
+        /// the consumer rather than of the ring's lifetime start.
+        fn release_until(&mut self, index: u64) {
+            if index > self.valid_from {
+                self.valid_from = index;
+            }
         }
     }
-}
 
-/// Output handler that receives CMSampleBuffers and extracts PCM data.
-///
-/// Each instance is registered for a specific `SCStreamOutputType` and only
-/// processes buffers matching that type.  This design is forward-compatible
-/// with the `screencapturekit` crate: the current v1.5 has a bug where every
-/// buffer is broadcast to ALL registered handlers regardless of their
-/// registered output type, so the `expected_type` filter prevents double
-/// processing.  If the crate ever fixes this bug, each handler will only
-/// receive its own type and the filter becomes a harmless no-op.
-///
-/// When microphone capture is enabled, samples are routed through an
-/// `AudioMixer` that combines system audio + mic in real time.  When only
-/// system audio is captured, samples go directly to the downstream handler.
-enum OutputTarget {
-    /// Direct passthrough — only system audio, no mixing needed.
-    Direct(Arc<Mutex<Box<dyn AudioDataHandler>>>),
-    /// Two-source mixer — system audio + microphone.
-    Mixer(Arc<Mutex<AudioMixer>>),
-}
+    /// Real-time mixer for two audio streams (system audio + microphone).
+    ///
+    /// ScreenCaptureKit delivers system audio and microphone as **separate**
+    /// `CMSampleBuffer` streams, each carrying its own presentation timestamp
+    /// (PTS). Rather than aligning them purely by buffer length -- which
+    /// drifts permanently out of sync the moment either source drops a frame
+    /// or starts late -- each incoming buffer is converted to a sample index
+    /// on a shared timeline (via its PTS and the configured sample rate) and
+    /// written into a fixed-capacity `SampleRing`. `try_mix` then advances a
+    /// monotonically increasing `output_cursor` and, for each output
+    /// position, mixes whichever sources have a sample at that index; a
+    /// source with no sample at the cursor (a gap, or a late start)
+    /// contributes silence rather than shifting the rest of the stream.
+    ///
+    /// When only one source delivers data (e.g. mic permission not granted),
+    /// the mixer drains that source directly once it has pulled ahead of the
+    /// other by more than `max_pending_samples`, so we don't buffer forever.
+    struct AudioMixer {
+        /// Downstream handler that receives mixed PCM, and overrun reports.
+        handler: Arc<Mutex<Box<dyn AudioDataHandler>>>,
+        /// Sample rate of the shared timeline that sample indices are
+        /// expressed in.
+        sample_rate: u32,
+        /// Pending system audio samples.
+        system_ring: SampleRing,
+        /// Pending microphone samples.
+        mic_ring: SampleRing,
+        /// Next sample index to emit.
+        output_cursor: u64,
+        /// How far one source may lead the other before it's drained anyway.
+        /// At 48 kHz mono this is ~100 ms (4800 samples).
+        max_pending_samples: usize,
+        /// Live per-source gains, readable/settable from outside the mixer
+        /// via `SystemAudioCapture::mix_control`.
+        mix: Arc<super::MixControl>,
+    }
 
-struct AudioOutputHandler {
-    /// Where to send extracted PCM samples.
-    target: OutputTarget,
-    /// Number of channels configured for this stream.
-    channels: u32,
-    /// The output type this handler is responsible for.
-    expected_type: SCStreamOutputType,
-}
+    impl AudioMixer {
+        /// `ring_capacity_ms` sizes each source's `SampleRing` -- see
+        /// `CaptureConfig::buffer_ms`.
+        fn new(
+            handler: Arc<Mutex<Box<dyn AudioDataHandler>>>,
+            sample_rate: u32,
+            ring_capacity_ms: u32,
+            mix: Arc<super::MixControl>,
+        ) -> Self {
+            let capacity = (sample_rate as u64 * ring_capacity_ms as u64 / 1000).max(1) as usize;
+            Self {
+                handler,
+                sample_rate,
+                system_ring: SampleRing::new(capacity),
+                mic_ring: SampleRing::new(capacity),
+                output_cursor: 0,
+                // ~100 ms at 48 kHz — generous enough to absorb scheduling jitter
+                // between the two callback queues, short enough to keep latency low.
+                max_pending_samples: 4800,
+                mix,
+            }
+        }
 
-impl SCStreamOutputTrait for AudioOutputHandler {
-    fn did_output_sample_buffer(&self, sample: CMSampleBuffer, output_type: SCStreamOutputType) {
-        // Only process buffers matching our registered type.
-        // See struct-level doc comment for rationale.
-        if output_type != self.expected_type {
-            return;
+        /// Apply live gains to `system`/`mic` (each `None` if that source has
+        /// no sample at this position, treated as silence), sum, apply makeup
+        /// gain, and soft-clip the result.
+        fn combine(&self, system: Option<f32>, mic: Option<f32>) -> f32 {
+            let gain_system = self.mix.gain_system();
+            let gain_mic = self.mix.gain_mic();
+            let makeup_gain = self.mix.makeup_gain();
+            let sum = system.unwrap_or(0.0) * gain_system + mic.unwrap_or(0.0) * gain_mic;
+            soft_clip(sum * makeup_gain)
         }
 
-        let Some(pcm) = extract_mono_f32_samples(&sample, self.channels) else {
-            return;
-        };
+        /// Convert a `CMSampleBuffer` presentation timestamp to a sample
+        /// index on this mixer's shared timeline.
+        fn sample_index_for(&self, pts: CMTime) -> u64 {
+            pts_to_sample_index(pts, self.sample_rate)
+        }
+
+        /// Write a system audio frame starting at `pts` and attempt to mix + flush.
+        fn push_system(&mut self, pts: CMTime, samples: Vec<f32>) {
+            let start_index = self.sample_index_for(pts);
+            let overrun = self.system_ring.write(start_index, &samples);
+            self.report_overrun(overrun, "system audio");
+            self.try_mix();
+        }
+
+        /// Write a microphone frame starting at `pts` and attempt to mix + flush.
+        fn push_mic(&mut self, pts: CMTime, samples: Vec<f32>) {
+            let start_index = self.sample_index_for(pts);
+            let overrun = self.mic_ring.write(start_index, &samples);
+            self.report_overrun(overrun, "microphone");
+            self.try_mix();
+        }
+
+        fn report_overrun(&self, dropped: u64, source: &str) {
+            if dropped == 0 {
+                return;
+            }
+            if let Ok(h) = self.handler.lock() {
+                h.on_error(format!(
+                    "audio mixer buffer overrun: dropped {dropped} {source} sample(s)"
+                ));
+            }
+        }
 
-        match &self.target {
-            OutputTarget::Direct(handler) => {
-                if let Ok(h) = handler.lock() {
-                    h.on_audio_data(&pcm);
+        /// Advance `output_cursor`, mixing both sources up to the minimum of
+        /// their latest available index. If one source has pulled more than
+        /// `max_pending_samples` ahead of the other, drain it directly
+        /// (treating the lagging source as silent) instead of waiting.
+        fn try_mix(&mut self) {
+            let system_until = self.system_ring.available_until();
+            let mic_until = self.mic_ring.available_until();
+            let target = system_until.min(mic_until);
+
+            if target > self.output_cursor {
+                let mut mixed = Vec::with_capacity((target - self.output_cursor) as usize);
+                while self.output_cursor < target {
+                    let s = self.system_ring.read(self.output_cursor);
+                    let m = self.mic_ring.read(self.output_cursor);
+                    mixed.push(self.combine(s, m));
+                    self.output_cursor += 1;
                 }
+                self.system_ring.release_until(self.output_cursor);
+                self.mic_ring.release_until(self.output_cursor);
+                self.emit(&mixed);
+                return;
             }
-            OutputTarget::Mixer(mixer) => {
-                if let Ok(mut m) = mixer.lock() {
-                    match self.expected_type {
-                        SCStreamOutputType::Audio => m.push_system(&pcm),
-                        SCStreamOutputType::Microphone => m.push_mic(&pcm),
-                        SCStreamOutputType::Screen => {}
-                    }
+
+            let lead_until = system_until.max(mic_until);
+            if lead_until.saturating_sub(self.output_cursor) >= self.max_pending_samples as u64 {
+                let from_system = system_until >= mic_until;
+                let mut drained = Vec::with_capacity((lead_until - self.output_cursor) as usize);
+                while self.output_cursor < lead_until {
+                    let sample = if from_system {
+                        self.system_ring.read(self.output_cursor)
+                    } else {
+                        self.mic_ring.read(self.output_cursor)
+                    };
+                    let combined = if from_system {
+                        self.combine(sample, None)
+                    } else {
+                        self.combine(None, sample)
+                    };
+                    drained.push(combined);
+                    self.output_cursor += 1;
                 }
+                self.system_ring.release_until(self.output_cursor);
+                self.mic_ring.release_until(self.output_cursor);
+                self.emit(&drained);
+            }
+        }
+
+        fn emit(&self, samples: &[f32]) {
+            if let Ok(h) = self.handler.lock() {
+                h.on_audio_data(samples);
             }
         }
     }
-}
 
-/// Start system audio capture.
-///
-/// Returns a `SystemAudioCapture` handle. The capture runs until the
-/// handle is dropped or `stop()` is called.
-pub fn start_capture(
-    config: &CaptureConfig,
-    handler: Box<dyn AudioDataHandler>,
-) -> Result<SystemAudioCapture, CaptureError> {
-    // 1. Get shareable content (also serves as permission check)
-    let content = SCShareableContent::get().map_err(|e| {
-        CaptureError::PermissionDenied(format!(
-            "screen capture permission denied or unavailable: {e}"
-        ))
-    })?;
-
-    // 2. Get the first display for the content filter.
-    //    Even for audio-only capture, ScreenCaptureKit requires a display filter.
-    let display = content
-        .displays()
-        .into_iter()
-        .next()
-        .ok_or(CaptureError::NoDisplay)?;
-
-    // 3. Build content filter
-    let filter = SCContentFilter::create()
-        .with_display(&display)
-        .with_excluding_windows(&[])
-        .build();
-
-    // 4. Configure stream — audio-only, no video
-    let mut stream_config = SCStreamConfiguration::new()
-        // Minimal video config (required but not used)
-        .with_width(2)
-        .with_height(2)
-        .with_minimum_frame_interval(&CMTime::new(1, 1)); // 1 FPS minimum
-
-    // System audio
-    if config.capture_system_audio {
-        stream_config = stream_config
-            .with_captures_audio(true)
-            .with_sample_rate(config.sample_rate as i32)
-            .with_channel_count(1); // Mono for voice recording
-    }
-
-    // Microphone (macOS 15.0+)
-    if config.capture_microphone {
-        stream_config.set_captures_microphone(true);
-
-        if let Some(ref device_id) = config.microphone_device_id {
-            stream_config.set_microphone_capture_device_id(device_id);
-        }
-    }
-
-    // Suppress presenter overlay privacy alert
-    stream_config
-        .set_presenter_overlay_privacy_alert_setting(SCPresenterOverlayAlertSetting::Never);
-
-    // 5. Create stream and register output handlers.
-    //
-    // Apple's SCStream requires a separate `addStreamOutput(_:type:)` call for
-    // each output type you want to receive.  Without registering for
-    // `SCStreamOutputType::Microphone`, the system never delivers mic buffers
-    // even when `set_captures_microphone(true)` is set.
-    //
-    // CRATE BUG (screencapturekit v1.5): The crate's `sample_handler` callback
-    // dispatches every buffer to ALL registered handlers, ignoring the output
-    // type they were registered for.  To prevent double-processing, each
-    // `AudioOutputHandler` carries an `expected_type` field and silently drops
-    // buffers that don't match.  This is forward-compatible: if the crate fixes
-    // the bug, each handler only receives its own type and the filter is a
-    // harmless no-op.
-    //
-    // When both system audio and microphone are enabled, an `AudioMixer` sits
-    // between the two handlers and the downstream `AudioDataHandler`.  The
-    // mixer accumulates samples from each source and outputs their average
-    // so that the final MP3 has the correct duration (not 2×).
-    let handler = Arc::new(Mutex::new(handler));
-
-    let mut stream = SCStream::new(&filter, &stream_config);
-
-    if config.capture_microphone {
-        // Two-source mode: route both through AudioMixer
-        let mixer = Arc::new(Mutex::new(AudioMixer::new(Arc::clone(&handler))));
-
-        let audio_handler = AudioOutputHandler {
-            target: OutputTarget::Mixer(Arc::clone(&mixer)),
-            channels: 1,
-            expected_type: SCStreamOutputType::Audio,
-        };
-        stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+    /// Soft-knee limiter: passes `x` through unchanged below `THRESHOLD`,
+    /// then eases it toward (but never reaching) +/-1.0 above that, so
+    /// summing two full-scale sources doesn't produce a harsh digital clip.
+    fn soft_clip(x: f32) -> f32 {
+        const THRESHOLD: f32 = 0.8;
+        let sign = x.signum();
+        let mag = x.abs();
+        if mag <= THRESHOLD {
+            x
+        } else {
+            sign * (THRESHOLD + (1.0 - THRESHOLD) * (mag - THRESHOLD).tanh())
+        }
+    }
 
-        let mic_handler = AudioOutputHandler {
-            target: OutputTarget::Mixer(mixer),
-            channels: 1,
-            expected_type: SCStreamOutputType::Microphone,
-        };
-        stream.add_output_handler(mic_handler, SCStreamOutputType::Microphone);
-    } else {
-        // Single-source mode: direct passthrough, no mixer overhead
-        let audio_handler = AudioOutputHandler {
-            target: OutputTarget::Direct(handler),
-            channels: 1,
-            expected_type: SCStreamOutputType::Audio,
-        };
-        stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+    /// Convert a `CMSampleBuffer` presentation timestamp to a sample index
+    /// on a shared timeline at `sample_rate`. Shared by `AudioMixer` and
+    /// `MultiTrackAligner`, which both need to place incoming buffers on
+    /// the same kind of timeline but do different things once placed.
+    fn pts_to_sample_index(pts: CMTime, sample_rate: u32) -> u64 {
+        if pts.timescale == 0 {
+            return 0;
+        }
+        ((pts.value as f64 / pts.timescale as f64) * sample_rate as f64).round() as u64
     }
 
-    // 6. Start capture
-    stream
-        .start_capture()
-        .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+    /// Timestamp-aligns system audio + microphone onto a shared sample
+    /// timeline like `AudioMixer`, but emits each source as its own track
+    /// via `MultiTrackHandler::on_track_data` instead of summing them
+    /// into one mono stream. Used by `start_multitrack_capture`.
+    struct MultiTrackAligner {
+        handler: Arc<Mutex<Box<dyn super::MultiTrackHandler>>>,
+        sample_rate: u32,
+        system_ring: SampleRing,
+        mic_ring: SampleRing,
+        output_cursor: u64,
+        max_pending_samples: usize,
+    }
 
-    Ok(SystemAudioCapture { stream })
-}
+    impl MultiTrackAligner {
+        /// `ring_capacity_ms` sizes each source's `SampleRing` -- see
+        /// `CaptureConfig::buffer_ms`.
+        fn new(
+            handler: Arc<Mutex<Box<dyn super::MultiTrackHandler>>>,
+            sample_rate: u32,
+            ring_capacity_ms: u32,
+        ) -> Self {
+            let capacity = (sample_rate as u64 * ring_capacity_ms as u64 / 1000).max(1) as usize;
+            Self {
+                handler,
+                sample_rate,
+                system_ring: SampleRing::new(capacity),
+                mic_ring: SampleRing::new(capacity),
+                output_cursor: 0,
+                max_pending_samples: 4800,
+            }
+        }
 
-impl SystemAudioCapture {
-    /// Stop the capture.
-    pub fn stop(self) -> Result<(), CaptureError> {
-        self.stream
-            .stop_capture()
-            .map_err(|e| CaptureError::StopFailed(format!("{e}")))
+        /// Write a system audio frame starting at `pts` and attempt to emit.
+        fn push_system(&mut self, pts: CMTime, samples: Vec<f32>) {
+            let start_index = pts_to_sample_index(pts, self.sample_rate);
+            let overrun = self.system_ring.write(start_index, &samples);
+            self.report_overrun(overrun, "system audio");
+            self.try_emit();
+        }
+
+        /// Write a microphone frame starting at `pts` and attempt to emit.
+        fn push_mic(&mut self, pts: CMTime, samples: Vec<f32>) {
+            let start_index = pts_to_sample_index(pts, self.sample_rate);
+            let overrun = self.mic_ring.write(start_index, &samples);
+            self.report_overrun(overrun, "microphone");
+            self.try_emit();
+        }
+
+        fn report_overrun(&self, dropped: u64, source: &str) {
+            if dropped == 0 {
+                return;
+            }
+            if let Ok(h) = self.handler.lock() {
+                h.on_error(format!(
+                    "audio track aligner buffer overrun: dropped {dropped} {source} sample(s)"
+                ));
+            }
+        }
+
+        /// Advance `output_cursor` up to the minimum of both sources' latest
+        /// available index, emitting each source's span as its own track
+        /// (gaps fill with silence, keeping the two tracks the same length
+        /// and in sync). Same drain-on-stall fallback as `AudioMixer::try_mix`.
+        fn try_emit(&mut self) {
+            let system_until = self.system_ring.available_until();
+            let mic_until = self.mic_ring.available_until();
+            let target = system_until.min(mic_until);
+
+            if target > self.output_cursor {
+                self.emit_range(target);
+                return;
+            }
+
+            let lead_until = system_until.max(mic_until);
+            if lead_until.saturating_sub(self.output_cursor) >= self.max_pending_samples as u64 {
+                self.emit_range(lead_until);
+            }
+        }
+
+        /// Emit both tracks' samples for `[output_cursor, to)`, reading
+        /// silence for whichever source has no data at a given index.
+        fn emit_range(&mut self, to: u64) {
+            let mut system_track = Vec::with_capacity((to - self.output_cursor) as usize);
+            let mut mic_track = Vec::with_capacity((to - self.output_cursor) as usize);
+            while self.output_cursor < to {
+                system_track.push(self.system_ring.read(self.output_cursor).unwrap_or(0.0));
+                mic_track.push(self.mic_ring.read(self.output_cursor).unwrap_or(0.0));
+                self.output_cursor += 1;
+            }
+            self.system_ring.release_until(self.output_cursor);
+            self.mic_ring.release_until(self.output_cursor);
+            if let Ok(h) = self.handler.lock() {
+                h.on_track_data(super::TrackId::System, &system_track);
+                h.on_track_data(super::TrackId::Microphone, &mic_track);
+            }
+        }
     }
-}
 
-/// Extract mono f32 PCM samples from a CMSampleBuffer.
-///
-/// ScreenCaptureKit delivers audio as interleaved PCM in `AudioBufferList`.
-/// We extract the raw bytes, reinterpret as f32, and downmix to mono if needed.
-fn extract_mono_f32_samples(sample: &CMSampleBuffer, expected_channels: u32) -> Option<Vec<f32>> {
-    let buffer_list = sample.audio_buffer_list()?;
+    /// Output handler that receives CMSampleBuffers and extracts PCM data.
+    ///
+    /// Each instance is registered for a specific `SCStreamOutputType` and only
+    /// processes buffers matching that type.  This design is forward-compatible
+    /// with the `screencapturekit` crate: the current v1.5 has a bug where every
+    /// buffer is broadcast to ALL registered handlers regardless of their
+    /// registered output type, so the `expected_type` filter prevents double
+    /// processing.  If the crate ever fixes this bug, each handler will only
+    /// receive its own type and the filter becomes a harmless no-op.
+    ///
+    /// When microphone capture is enabled, samples are routed through an
+    /// `AudioMixer` that combines system audio + mic in real time.  When only
+    /// system audio is captured, samples go directly to the downstream handler.
+    enum OutputTarget {
+        /// Direct passthrough — only system audio, no mixing needed.
+        Direct(Arc<Mutex<Box<dyn AudioDataHandler>>>),
+        /// Two-source mixer — system audio + microphone.
+        Mixer(Arc<Mutex<AudioMixer>>),
+        /// Two-source aligner — system audio + microphone delivered as
+        /// separate synchronized tracks instead of mixed into one.
+        MultiTrack(Arc<Mutex<MultiTrackAligner>>),
+    }
+
+    struct AudioOutputHandler {
+        /// Where to send extracted PCM samples.
+        target: OutputTarget,
+        /// Number of channels configured for this stream.
+        channels: u32,
+        /// The output type this handler is responsible for.
+        expected_type: SCStreamOutputType,
+        /// Sample rate downstream (the mixer, aligner, or direct handler)
+        /// expects to see -- `CaptureConfig.sample_rate`.
+        target_sample_rate: u32,
+        /// Lazily created once this handler has seen a buffer and learned
+        /// its actual rate; `None` means either no buffer has arrived yet or
+        /// the actual rate matches `target_sample_rate` (no resampling
+        /// needed). Behind a `Mutex` because `did_output_sample_buffer`
+        /// only gets `&self`.
+        resampler: Mutex<Option<Resampler>>,
+    }
+
+    impl SCStreamOutputTrait for AudioOutputHandler {
+        fn did_output_sample_buffer(
+            &self,
+            sample: CMSampleBuffer,
+            output_type: SCStreamOutputType,
+        ) {
+            // Only process buffers matching our registered type.
+            // See struct-level doc comment for rationale.
+            if output_type != self.expected_type {
+                return;
+            }
+
+            let pts = sample.presentation_timestamp();
 
-    let mut all_samples: Vec<f32> = Vec::new();
+            let Some(pcm) = extract_mono_f32_samples(&sample, self.channels) else {
+                return;
+            };
+            let pcm = self.resample(&sample, pcm);
 
-    for buf in buffer_list.iter() {
-        let data = buf.data();
-        if data.is_empty() {
-            continue;
+            match &self.target {
+                OutputTarget::Direct(handler) => {
+                    if let Ok(h) = handler.lock() {
+                        h.on_audio_data(&pcm);
+                    }
+                }
+                OutputTarget::Mixer(mixer) => {
+                    if let Ok(mut m) = mixer.lock() {
+                        match self.expected_type {
+                            SCStreamOutputType::Audio => m.push_system(pts, pcm),
+                            SCStreamOutputType::Microphone => m.push_mic(pts, pcm),
+                            SCStreamOutputType::Screen => {}
+                        }
+                    }
+                }
+                OutputTarget::MultiTrack(aligner) => {
+                    if let Ok(mut a) = aligner.lock() {
+                        match self.expected_type {
+                            SCStreamOutputType::Audio => a.push_system(pts, pcm),
+                            SCStreamOutputType::Microphone => a.push_mic(pts, pcm),
+                            SCStreamOutputType::Screen => {}
+                        }
+                    }
+                }
+            }
         }
+    }
 
-        let channels = buf.number_channels;
+    impl AudioOutputHandler {
+        /// Resample `pcm` to `target_sample_rate` if `sample`'s actual rate
+        /// differs from it -- e.g. hardware that doesn't honor the rate
+        /// requested via `CaptureConfig.sample_rate`. A `None` actual rate
+        /// (format description unavailable) is assumed to already match.
+        fn resample(&self, sample: &CMSampleBuffer, pcm: Vec<f32>) -> Vec<f32> {
+            let actual_rate = actual_sample_rate(sample)
+                .map(|r| r.round() as u32)
+                .unwrap_or(self.target_sample_rate);
+            if actual_rate == self.target_sample_rate {
+                return pcm;
+            }
+            let Ok(mut guard) = self.resampler.lock() else {
+                return pcm;
+            };
+            let resampler = guard
+                .get_or_insert_with(|| Resampler::new(actual_rate, self.target_sample_rate, 1));
+            resampler.process(&pcm)
+        }
+    }
+
+    /// Best-effort extraction of a sample buffer's actual sample rate from
+    /// its format description. Returns `None` if the format description or
+    /// its audio stream basic description isn't available, in which case
+    /// the caller assumes the stream's requested rate was honored.
+    fn actual_sample_rate(sample: &CMSampleBuffer) -> Option<f64> {
+        let format = sample.format_description()?;
+        let asbd = format.audio_stream_basic_description()?;
+        Some(asbd.sample_rate)
+    }
+
+    /// Extract mono f32 PCM samples from a CMSampleBuffer.
+    ///
+    /// ScreenCaptureKit delivers audio as interleaved PCM in `AudioBufferList`.
+    /// We extract the raw bytes, reinterpret as f32, and downmix to mono if needed.
+    fn extract_mono_f32_samples(
+        sample: &CMSampleBuffer,
+        expected_channels: u32,
+    ) -> Option<Vec<f32>> {
+        let buffer_list = sample.audio_buffer_list()?;
+
+        let mut all_samples: Vec<f32> = Vec::new();
+
+        for buf in buffer_list.iter() {
+            let data = buf.data();
+            if data.is_empty() {
+                continue;
+            }
+
+            let channels = buf.number_channels;
+
+            // Reinterpret raw bytes as f32 samples
+            // Safety: ScreenCaptureKit outputs 32-bit float PCM
+            let (prefix, f32_data, suffix) = unsafe { data.align_to::<f32>() };
+            if !prefix.is_empty() || !suffix.is_empty() {
+                // Data is not properly aligned — skip this buffer
+                eprintln!("audio buffer alignment issue, skipping");
+                continue;
+            }
 
-        // Reinterpret raw bytes as f32 samples
-        // Safety: ScreenCaptureKit outputs 32-bit float PCM
-        let (prefix, f32_data, suffix) = unsafe { data.align_to::<f32>() };
-        if !prefix.is_empty() || !suffix.is_empty() {
-            // Data is not properly aligned — skip this buffer
-            eprintln!("audio buffer alignment issue, skipping");
-            continue;
+            if channels <= 1 || expected_channels == 1 {
+                // Already mono or configured for mono
+                all_samples.extend_from_slice(f32_data);
+            } else {
+                // Downmix interleaved multi-channel to mono
+                let ch = channels as usize;
+                for frame in f32_data.chunks_exact(ch) {
+                    let sum: f32 = frame.iter().sum();
+                    all_samples.push((sum / channels as f32).clamp(-1.0, 1.0));
+                }
+            }
         }
 
-        if channels <= 1 || expected_channels == 1 {
-            // Already mono or configured for mono
-            all_samples.extend_from_slice(f32_data);
+        if all_samples.is_empty() {
+            None
         } else {
-            // Downmix interleaved multi-channel to mono
-            let ch = channels as usize;
-            for frame in f32_data.chunks_exact(ch) {
-                let sum: f32 = frame.iter().sum();
-                all_samples.push((sum / channels as f32).clamp(-1.0, 1.0));
+            Some(all_samples)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use super::super::{MixControl, MixSettings};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Test helper: collects samples emitted by the mixer.
+        struct CollectingHandler {
+            samples: Arc<Mutex<Vec<f32>>>,
+            call_count: Arc<AtomicUsize>,
+        }
+
+        impl CollectingHandler {
+            fn new() -> (Self, Arc<Mutex<Vec<f32>>>, Arc<AtomicUsize>) {
+                let samples = Arc::new(Mutex::new(Vec::new()));
+                let count = Arc::new(AtomicUsize::new(0));
+                (
+                    Self {
+                        samples: Arc::clone(&samples),
+                        call_count: Arc::clone(&count),
+                    },
+                    samples,
+                    count,
+                )
+            }
+        }
+
+        impl AudioDataHandler for CollectingHandler {
+            fn on_audio_data(&self, data: &[f32]) {
+                self.samples.lock().unwrap().extend_from_slice(data);
+                self.call_count.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_error(&self, _error: String) {}
+        }
+
+        /// Build a `CMTime` for sample `index` on a 48 kHz timeline.
+        fn pts_for(index: u64) -> CMTime {
+            CMTime::new(index as i64, 48000)
+        }
+
+        fn new_test_mixer(handler: Arc<Mutex<Box<dyn AudioDataHandler>>>) -> AudioMixer {
+            // Generous ring capacity (1s) so tests exercise mixing logic
+            // without tripping the overrun path; see `test_ring_overrun_*`
+            // for that behavior in isolation.
+            AudioMixer::new(
+                handler,
+                48000,
+                1000,
+                Arc::new(MixControl::new(MixSettings::default())),
+            )
+        }
+
+        // 0.8 + 0.2 * tanh(1.0 - 0.8), the soft-clipped value of two unity-gain
+        // sources that each contribute 0.5 of a combined sum of 1.0.
+        const SOFT_CLIPPED_UNITY_SUM: f32 = 0.8394751;
+
+        #[test]
+        fn test_mixer_both_sources_equal_length() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+
+            mixer.push_system(pts_for(0), vec![1.0, 0.5, 0.0]);
+            // Nothing emitted yet — mic hasn't delivered
+            assert!(output.lock().unwrap().is_empty());
+
+            mixer.push_mic(pts_for(0), vec![0.0, 0.5, 1.0]);
+            // Now both have 3 samples — should mix and emit. Each pair sums
+            // to 1.0 (1.0+0.0, 0.5+0.5, 0.0+1.0), so all three soft-clip the
+            // same way.
+            let out = output.lock().unwrap();
+            assert_eq!(out.len(), 3);
+            assert!((out[0] - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4);
+            assert!((out[1] - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4);
+            assert!((out[2] - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4);
+        }
+
+        #[test]
+        fn test_mixer_unequal_lengths() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+
+            mixer.push_system(pts_for(0), vec![0.8, 0.6, 0.4, 0.2]);
+            mixer.push_mic(pts_for(0), vec![0.2, 0.4]);
+            // Should mix 2 samples, leaving 2 pending in system_ring. Both
+            // pairs sum to 1.0 (0.8+0.2, 0.6+0.4).
+            let out = output.lock().unwrap();
+            assert_eq!(out.len(), 2);
+            assert!((out[0] - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4);
+            assert!((out[1] - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4);
+        }
+
+        #[test]
+        fn test_mixer_single_source_drains_at_threshold() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+            mixer.max_pending_samples = 10; // Lower threshold for testing
+
+            // Push 10 system samples with no mic — should drain
+            mixer.push_system(pts_for(0), vec![0.5; 10]);
+            let out = output.lock().unwrap();
+            assert_eq!(out.len(), 10);
+            assert!(out.iter().all(|&s| (s - 0.5).abs() < f32::EPSILON));
+        }
+
+        #[test]
+        fn test_mixer_clamps_output() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+
+            // Both near max → the summed ±2.0 should still be soft-clipped
+            mixer.push_system(pts_for(0), vec![1.0, -1.0]);
+            mixer.push_mic(pts_for(0), vec![1.0, -1.0]);
+            let out = output.lock().unwrap();
+            assert_eq!(out.len(), 2);
+            assert!(out[0] <= 1.0);
+            assert!(out[1] >= -1.0);
+        }
+
+        #[test]
+        fn test_mixer_preserves_sample_count() {
+            // Simulates a 2-second recording at 48kHz with both sources
+            // delivering 960-sample buffers (20ms frames).
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+
+            let frame = vec![0.1_f32; 960];
+            let frames_per_second = 50; // 48000 / 960
+            let total_frames = frames_per_second * 2;
+
+            let mut index = 0u64;
+            for _ in 0..total_frames {
+                mixer.push_system(pts_for(index), frame.clone());
+                mixer.push_mic(pts_for(index), frame.clone());
+                index += frame.len() as u64;
+            }
+
+            let out = output.lock().unwrap();
+            // Total output should equal 2 seconds worth of samples (96000),
+            // NOT 2× that (which was the bug before the mixer).
+            assert_eq!(out.len(), 96000);
+        }
+
+        #[test]
+        fn test_mixer_late_start_contributes_silence_without_shifting() {
+            // Mic starts on time; system audio starts 5 samples late (e.g. a
+            // slow-starting capture). The gap should be filled with silence
+            // rather than shifting the mic samples earlier.
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut mixer = new_test_mixer(handler);
+            mixer.max_pending_samples = 100;
+
+            mixer.push_mic(pts_for(0), vec![1.0; 5]);
+            mixer.push_system(pts_for(5), vec![1.0; 5]);
+
+            {
+                let out = output.lock().unwrap();
+                assert_eq!(out.len(), 5);
+                // system contributes silence for [0, 5) -> soft_clip(1.0 + 0.0)
+                assert!(out.iter().all(|&s| (s - SOFT_CLIPPED_UNITY_SUM).abs() < 1e-4));
+            }
+
+            mixer.push_mic(pts_for(5), vec![1.0; 5]);
+            let out = output.lock().unwrap();
+            // Second run: both sources present for [5, 10) -> soft_clip(1.0 + 1.0),
+            // which approaches but never reaches 1.0. The handler accumulates,
+            // so these follow the first run's 5 samples.
+            assert_eq!(out.len(), 10);
+            assert!(out[5..].iter().all(|&s| s < 1.0 && (s - 1.0).abs() < 0.05));
+        }
+
+        #[test]
+        fn test_sample_ring_reports_overrun_when_writer_outpaces_reader() {
+            let mut ring = SampleRing::new(4);
+            // Nothing reads between writes, so the second write overruns
+            // the first sample still pending.
+            assert_eq!(ring.write(0, &[1.0, 2.0, 3.0, 4.0]), 0);
+            assert_eq!(ring.write(4, &[5.0]), 1);
+            // index 0 was overwritten; it's no longer valid to read.
+            assert_eq!(ring.read(0), None);
+            assert_eq!(ring.read(4), Some(5.0));
+        }
+
+        #[test]
+        fn test_sample_ring_no_overrun_once_reader_keeps_up() {
+            let mut ring = SampleRing::new(4);
+            assert_eq!(ring.write(0, &[1.0, 2.0, 3.0, 4.0]), 0);
+            ring.release_until(4); // consumer has read [0, 4)
+            // Capacity is free again, so this doesn't overrun.
+            assert_eq!(ring.write(4, &[5.0, 6.0, 7.0, 8.0]), 0);
+            assert_eq!(ring.read(7), Some(8.0));
+        }
+
+        #[test]
+        fn test_mixer_reports_overrun_via_on_error() {
+            struct ErrorCapturingHandler {
+                errors: Arc<Mutex<Vec<String>>>,
+            }
+            impl AudioDataHandler for ErrorCapturingHandler {
+                fn on_audio_data(&self, _data: &[f32]) {}
+                fn on_error(&self, error: String) {
+                    self.errors.lock().unwrap().push(error);
+                }
+            }
+
+            let errors = Arc::new(Mutex::new(Vec::new()));
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(ErrorCapturingHandler {
+                    errors: Arc::clone(&errors),
+                })));
+            // A 1-sample ring capacity means any write beyond the first
+            // overruns, since nothing is reading it back in this test.
+            let mut mixer = AudioMixer::new(
+                handler,
+                1000,
+                1,
+                Arc::new(MixControl::new(MixSettings::default())),
+            );
+
+            mixer.push_system(pts_for(0), vec![0.1; 10]);
+
+            assert!(!errors.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_mixer_gain_mic_zero_mutes_microphone() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mix = Arc::new(MixControl::new(MixSettings::default()));
+            mix.set_gain_mic(0.0);
+            let mut mixer = AudioMixer::new(handler, 48000, 1000, Arc::clone(&mix));
+
+            mixer.push_system(pts_for(0), vec![0.5, 0.5]);
+            mixer.push_mic(pts_for(0), vec![1.0, 1.0]);
+
+            let out = output.lock().unwrap();
+            assert_eq!(out.len(), 2);
+            // Mic is muted, so only system audio (below the soft-clip
+            // threshold) comes through unchanged.
+            assert!(out.iter().all(|&s| (s - 0.5).abs() < 1e-4));
+        }
+
+        #[test]
+        fn test_mixer_picks_up_gain_changes_without_reconstruction() {
+            let (handler, output, _) = CollectingHandler::new();
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mix = Arc::new(MixControl::new(MixSettings::default()));
+            let mut mixer = AudioMixer::new(handler, 48000, 1000, Arc::clone(&mix));
+
+            mixer.push_system(pts_for(0), vec![0.2]);
+            mixer.push_mic(pts_for(0), vec![0.2]);
+            assert!((output.lock().unwrap()[0] - 0.4).abs() < 1e-4);
+
+            mix.set_makeup_gain(2.0);
+            mixer.push_system(pts_for(1), vec![0.2]);
+            mixer.push_mic(pts_for(1), vec![0.2]);
+            // (0.2 + 0.2) * 2.0 = 0.8, still at the soft-clip threshold.
+            assert!((output.lock().unwrap()[1] - 0.8).abs() < 1e-4);
+        }
+
+        /// Test helper: collects per-track samples emitted by a `MultiTrackAligner`.
+        struct CollectingTrackHandler {
+            system: Arc<Mutex<Vec<f32>>>,
+            mic: Arc<Mutex<Vec<f32>>>,
+        }
+
+        impl CollectingTrackHandler {
+            fn new() -> (Self, Arc<Mutex<Vec<f32>>>, Arc<Mutex<Vec<f32>>>) {
+                let system = Arc::new(Mutex::new(Vec::new()));
+                let mic = Arc::new(Mutex::new(Vec::new()));
+                (
+                    Self {
+                        system: Arc::clone(&system),
+                        mic: Arc::clone(&mic),
+                    },
+                    system,
+                    mic,
+                )
+            }
+        }
+
+        impl super::super::MultiTrackHandler for CollectingTrackHandler {
+            fn on_track_data(&self, track: super::super::TrackId, samples: &[f32]) {
+                let dest = match track {
+                    super::super::TrackId::System => &self.system,
+                    super::super::TrackId::Microphone => &self.mic,
+                };
+                dest.lock().unwrap().extend_from_slice(samples);
+            }
+            fn on_error(&self, _error: String) {}
+        }
+
+        #[test]
+        fn test_aligner_keeps_tracks_separate_and_in_sync() {
+            let (handler, system_out, mic_out) = CollectingTrackHandler::new();
+            let handler: Arc<Mutex<Box<dyn super::super::MultiTrackHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut aligner = MultiTrackAligner::new(handler, 48000, 1000);
+
+            aligner.push_system(pts_for(0), vec![1.0, 0.5, 0.0]);
+            aligner.push_mic(pts_for(0), vec![0.0, 0.5, 1.0]);
+
+            assert_eq!(&*system_out.lock().unwrap(), &[1.0, 0.5, 0.0]);
+            assert_eq!(&*mic_out.lock().unwrap(), &[0.0, 0.5, 1.0]);
+        }
+
+        #[test]
+        fn test_aligner_fills_late_start_with_silence() {
+            let (handler, system_out, mic_out) = CollectingTrackHandler::new();
+            let handler: Arc<Mutex<Box<dyn super::super::MultiTrackHandler>>> =
+                Arc::new(Mutex::new(Box::new(handler)));
+            let mut aligner = MultiTrackAligner::new(handler, 48000, 1000);
+
+            aligner.push_mic(pts_for(0), vec![1.0; 5]);
+            aligner.push_system(pts_for(5), vec![1.0; 5]);
+
+            // System is silent for [0, 5) -- it hadn't started yet.
+            assert_eq!(&*system_out.lock().unwrap(), &[0.0; 5]);
+            assert_eq!(&*mic_out.lock().unwrap(), &[1.0; 5]);
+        }
+    }
+}
+
+/// cpal-backed `CaptureBackend`, used on non-macOS platforms (and on macOS
+/// if explicitly requested via `CaptureConfig.backend`). Captures the
+/// default -- or a specifically named -- input device directly; it has no
+/// way to loop back system audio, so `capture_system_audio` is ignored.
+mod cpal_backend {
+    use super::{
+        AudioDataHandler, AudioInputDeviceInfo, CaptureBackend, CaptureConfig, CaptureError,
+        CaptureSession, PermissionStatus, Resampler,
+    };
+    use crate::recorder::downmix_to_mono_f32;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::SampleFormat;
+    use std::sync::{Arc, Mutex};
+
+    pub(super) struct CpalBackend;
+
+    impl CpalBackend {
+        fn resolve_device(&self, device_id: &Option<String>) -> Option<cpal::Device> {
+            let host = cpal::default_host();
+            match device_id {
+                Some(id) => host
+                    .input_devices()
+                    .ok()?
+                    .find(|d| d.name().map(|n| &n == id).unwrap_or(false)),
+                None => host.default_input_device(),
             }
         }
     }
 
-    if all_samples.is_empty() {
-        None
-    } else {
-        Some(all_samples)
+    impl CaptureBackend for CpalBackend {
+        /// cpal has no OS permission-prompt API to query; we treat a
+        /// resolvable default input device as "granted".
+        fn check_permission(&self) -> PermissionStatus {
+            match cpal::default_host()
+                .default_input_device()
+                .and_then(|d| d.default_input_config().ok())
+            {
+                Some(_) => PermissionStatus::Granted,
+                None => PermissionStatus::Denied,
+            }
+        }
+
+        fn list_audio_input_devices(&self) -> Vec<AudioInputDeviceInfo> {
+            let host = cpal::default_host();
+            let Ok(devices) = host.input_devices() else {
+                return Vec::new();
+            };
+            devices
+                .filter_map(|d| {
+                    let name = d.name().ok()?;
+                    Some(AudioInputDeviceInfo {
+                        id: name.clone(),
+                        name,
+                    })
+                })
+                .collect()
+        }
+
+        fn start_capture(
+            &self,
+            config: &CaptureConfig,
+            handler: Box<dyn AudioDataHandler>,
+        ) -> Result<Box<dyn CaptureSession>, CaptureError> {
+            if !config.capture_microphone {
+                return Err(CaptureError::BackendUnavailable(
+                    "the cpal backend can only capture the microphone".into(),
+                ));
+            }
+
+            let device = self
+                .resolve_device(&config.microphone_device_id)
+                .ok_or_else(|| {
+                    CaptureError::StartFailed("no matching input device found".into())
+                })?;
+
+            let supported_config = device
+                .default_input_config()
+                .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+
+            let sample_format = supported_config.sample_format();
+            let stream_config: cpal::StreamConfig = supported_config.into();
+            let channels = stream_config.channels;
+
+            let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> = Arc::new(Mutex::new(handler));
+            let err_handler = Arc::clone(&handler);
+            let err_fn = move |err: cpal::StreamError| {
+                if let Ok(h) = err_handler.lock() {
+                    h.on_error(format!("{err}"));
+                }
+            };
+
+            let data_handler = Arc::clone(&handler);
+            // cpal negotiates its own device rate, which often doesn't match
+            // `config.sample_rate`; resample so the mixer always sees the
+            // configured rate regardless of hardware.
+            let device_rate = stream_config.sample_rate.0;
+            let target_rate = config.sample_rate;
+            let mut resampler_f32 = Resampler::new(device_rate, target_rate, 1);
+            let mut resampler_i16 = Resampler::new(device_rate, target_rate, 1);
+            let mut resampler_u16 = Resampler::new(device_rate, target_rate, 1);
+            let stream = match sample_format {
+                SampleFormat::F32 => device.build_input_stream(
+                    &stream_config,
+                    move |data: &[f32], _| {
+                        let mono = downmix_to_mono_f32(data, channels);
+                        let mono = resampler_f32.process(&mono);
+                        if let Ok(h) = data_handler.lock() {
+                            h.on_audio_data(&mono);
+                        }
+                    },
+                    err_fn,
+                    None,
+                ),
+                SampleFormat::I16 => device.build_input_stream(
+                    &stream_config,
+                    move |data: &[i16], _| {
+                        let samples: Vec<f32> =
+                            data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                        let mono = downmix_to_mono_f32(&samples, channels);
+                        let mono = resampler_i16.process(&mono);
+                        if let Ok(h) = data_handler.lock() {
+                            h.on_audio_data(&mono);
+                        }
+                    },
+                    err_fn,
+                    None,
+                ),
+                SampleFormat::U16 => device.build_input_stream(
+                    &stream_config,
+                    move |data: &[u16], _| {
+                        let samples: Vec<f32> = data
+                            .iter()
+                            .map(|&s| (s as f32 - 32768.0) / 32768.0)
+                            .collect();
+                        let mono = downmix_to_mono_f32(&samples, channels);
+                        let mono = resampler_u16.process(&mono);
+                        if let Ok(h) = data_handler.lock() {
+                            h.on_audio_data(&mono);
+                        }
+                    },
+                    err_fn,
+                    None,
+                ),
+                other => {
+                    return Err(CaptureError::StartFailed(format!(
+                        "unsupported sample format: {other:?}"
+                    )))
+                }
+            }
+            .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+
+            stream
+                .play()
+                .map_err(|e| CaptureError::StartFailed(format!("{e}")))?;
+
+            Ok(Box::new(CpalCaptureSession {
+                stream,
+                _handler: handler,
+            }))
+        }
+    }
+
+    struct CpalCaptureSession {
+        stream: cpal::Stream,
+        _handler: Arc<Mutex<Box<dyn AudioDataHandler>>>,
+    }
+
+    impl CaptureSession for CpalCaptureSession {
+        fn stop(self: Box<Self>) -> Result<(), CaptureError> {
+            self.stream
+                .pause()
+                .map_err(|e| CaptureError::StopFailed(format!("{e}")))
+        }
     }
 }
 
@@ -444,6 +1666,11 @@ pub enum CaptureError {
     NoDisplay,
     StartFailed(String),
     StopFailed(String),
+    /// The requested `CaptureBackendKind` isn't usable -- either it doesn't
+    /// exist on this platform, or the requested `CaptureConfig` isn't
+    /// something it can satisfy (e.g. the cpal backend with
+    /// `capture_microphone` off).
+    BackendUnavailable(String),
 }
 
 impl std::fmt::Display for CaptureError {
@@ -453,6 +1680,7 @@ impl std::fmt::Display for CaptureError {
             Self::NoDisplay => write!(f, "no display found"),
             Self::StartFailed(e) => write!(f, "failed to start capture: {e}"),
             Self::StopFailed(e) => write!(f, "failed to stop capture: {e}"),
+            Self::BackendUnavailable(e) => write!(f, "backend unavailable: {e}"),
         }
     }
 }
@@ -462,7 +1690,6 @@ impl std::error::Error for CaptureError {}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_capture_config_default() {
@@ -471,6 +1698,59 @@ mod tests {
         assert!(config.capture_system_audio);
         assert!(config.capture_microphone);
         assert!(config.microphone_device_id.is_none());
+        assert_eq!(config.backend, CaptureBackendKind::Auto);
+        assert_eq!(config.buffer_ms, 200);
+        assert_eq!(config.mix_settings, MixSettings::default());
+    }
+
+    #[test]
+    fn test_mix_settings_default_is_unity() {
+        let settings = MixSettings::default();
+        assert_eq!(settings.gain_system, 1.0);
+        assert_eq!(settings.gain_mic, 1.0);
+        assert_eq!(settings.makeup_gain, 1.0);
+    }
+
+    #[test]
+    fn test_mix_control_reflects_sets() {
+        let control = MixControl::new(MixSettings::default());
+        control.set_gain_system(0.5);
+        control.set_gain_mic(0.0);
+        control.set_makeup_gain(1.5);
+        assert_eq!(control.gain_system(), 0.5);
+        assert_eq!(control.gain_mic(), 0.0);
+        assert_eq!(control.makeup_gain(), 1.5);
+    }
+
+    #[test]
+    fn test_resampler_identity_when_rates_match() {
+        let mut resampler = Resampler::new(48000, 48000, 1);
+        let input = vec![0.1, 0.2, 0.3, 0.4];
+        assert_eq!(resampler.process(&input), input);
+    }
+
+    #[test]
+    fn test_resampler_preserves_rate_across_buffer_boundaries() {
+        // 24kHz -> 48kHz should double the sample count over many callbacks,
+        // even when fed in small chunks that don't divide evenly.
+        let mut resampler = Resampler::new(24000, 48000, 1);
+        let mut total_out = 0;
+        for _ in 0..10 {
+            let chunk = vec![0.5_f32; 7];
+            total_out += resampler.process(&chunk).len();
+        }
+        assert!((130..=150).contains(&total_out));
+    }
+
+    #[test]
+    fn test_resampler_downsamples_without_dropping_trailing_samples() {
+        let mut resampler = Resampler::new(48000, 24000, 1);
+        let mut total_out = 0;
+        for _ in 0..10 {
+            let chunk = vec![0.5_f32; 8];
+            total_out += resampler.process(&chunk).len();
+        }
+        assert!((35..=45).contains(&total_out));
     }
 
     #[test]
@@ -488,6 +1768,10 @@ mod tests {
             CaptureError::StopFailed("test".into()).to_string(),
             "failed to stop capture: test"
         );
+        assert_eq!(
+            CaptureError::BackendUnavailable("test".into()).to_string(),
+            "backend unavailable: test"
+        );
     }
 
     #[test]
@@ -530,125 +1814,30 @@ mod tests {
         }
     }
 
-    // --- AudioMixer tests ---
-
-    /// Test helper: collects samples emitted by the mixer.
-    struct CollectingHandler {
-        samples: Arc<Mutex<Vec<f32>>>,
-        call_count: Arc<AtomicUsize>,
-    }
-
-    impl CollectingHandler {
-        fn new() -> (Self, Arc<Mutex<Vec<f32>>>, Arc<AtomicUsize>) {
-            let samples = Arc::new(Mutex::new(Vec::new()));
-            let count = Arc::new(AtomicUsize::new(0));
-            (
-                Self {
-                    samples: Arc::clone(&samples),
-                    call_count: Arc::clone(&count),
-                },
-                samples,
-                count,
-            )
-        }
-    }
-
-    impl AudioDataHandler for CollectingHandler {
-        fn on_audio_data(&self, data: &[f32]) {
-            self.samples.lock().unwrap().extend_from_slice(data);
-            self.call_count.fetch_add(1, Ordering::Relaxed);
-        }
-        fn on_error(&self, _error: String) {}
-    }
-
+    #[cfg(not(target_os = "macos"))]
     #[test]
-    fn test_mixer_both_sources_equal_length() {
-        let (handler, output, _) = CollectingHandler::new();
-        let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
-            Arc::new(Mutex::new(Box::new(handler)));
-        let mut mixer = AudioMixer::new(handler);
-
-        mixer.push_system(&[1.0, 0.5, 0.0]);
-        // Nothing emitted yet — mic hasn't delivered
-        assert!(output.lock().unwrap().is_empty());
-
-        mixer.push_mic(&[0.0, 0.5, 1.0]);
-        // Now both have 3 samples — should mix and emit
-        let out = output.lock().unwrap();
-        assert_eq!(out.len(), 3);
-        assert!((out[0] - 0.5).abs() < f32::EPSILON); // (1.0 + 0.0) / 2
-        assert!((out[1] - 0.5).abs() < f32::EPSILON); // (0.5 + 0.5) / 2
-        assert!((out[2] - 0.5).abs() < f32::EPSILON); // (0.0 + 1.0) / 2
-    }
-
-    #[test]
-    fn test_mixer_unequal_lengths() {
-        let (handler, output, _) = CollectingHandler::new();
-        let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
-            Arc::new(Mutex::new(Box::new(handler)));
-        let mut mixer = AudioMixer::new(handler);
-
-        mixer.push_system(&[0.8, 0.6, 0.4, 0.2]);
-        mixer.push_mic(&[0.2, 0.4]);
-        // Should mix 2 samples, leaving 2 in system_buf
-        let out = output.lock().unwrap();
-        assert_eq!(out.len(), 2);
-        assert!((out[0] - 0.5).abs() < f32::EPSILON); // (0.8 + 0.2) / 2
-        assert!((out[1] - 0.5).abs() < f32::EPSILON); // (0.6 + 0.4) / 2
+    fn test_resolve_backend_screencapturekit_unavailable_off_macos() {
+        let err = resolve_backend(CaptureBackendKind::ScreenCaptureKit).unwrap_err();
+        assert!(matches!(err, CaptureError::BackendUnavailable(_)));
     }
 
     #[test]
-    fn test_mixer_single_source_drains_at_threshold() {
-        let (handler, output, _) = CollectingHandler::new();
-        let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
-            Arc::new(Mutex::new(Box::new(handler)));
-        let mut mixer = AudioMixer::new(handler);
-        mixer.max_pending_samples = 10; // Lower threshold for testing
-
-        // Push 10 system samples with no mic — should drain
-        mixer.push_system(&[0.5; 10]);
-        let out = output.lock().unwrap();
-        assert_eq!(out.len(), 10);
-        assert!(out.iter().all(|&s| (s - 0.5).abs() < f32::EPSILON));
+    fn test_resolve_backend_cpal_always_available() {
+        assert!(resolve_backend(CaptureBackendKind::Cpal).is_ok());
     }
 
-    #[test]
-    fn test_mixer_clamps_output() {
-        let (handler, output, _) = CollectingHandler::new();
-        let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
-            Arc::new(Mutex::new(Box::new(handler)));
-        let mut mixer = AudioMixer::new(handler);
-
-        // Both near max → average should still be clamped
-        mixer.push_system(&[1.0, -1.0]);
-        mixer.push_mic(&[1.0, -1.0]);
-        let out = output.lock().unwrap();
-        assert_eq!(out.len(), 2);
-        assert!(out[0] <= 1.0);
-        assert!(out[1] >= -1.0);
+    struct NoopTrackHandler;
+    impl MultiTrackHandler for NoopTrackHandler {
+        fn on_track_data(&self, _track: TrackId, _samples: &[f32]) {}
+        fn on_error(&self, _error: String) {}
     }
 
     #[test]
-    fn test_mixer_preserves_sample_count() {
-        // Simulates a 2-second recording at 48kHz with both sources
-        // delivering 960-sample buffers (20ms frames).
-        let (handler, output, _) = CollectingHandler::new();
-        let handler: Arc<Mutex<Box<dyn AudioDataHandler>>> =
-            Arc::new(Mutex::new(Box::new(handler)));
-        let mut mixer = AudioMixer::new(handler);
-
-        let frame = vec![0.1_f32; 960];
-        let frames_per_second = 50; // 48000 / 960
-        let total_frames = frames_per_second * 2;
-
-        for _ in 0..total_frames {
-            mixer.push_system(&frame);
-            mixer.push_mic(&frame);
-        }
-
-        let out = output.lock().unwrap();
-        // Total output should equal 2 seconds worth of samples (96000),
-        // NOT 2× that (which was the bug before the mixer).
-        assert_eq!(out.len(), 96000);
+    fn test_cpal_backend_does_not_support_multitrack_capture() {
+        let backend = resolve_backend(CaptureBackendKind::Cpal).unwrap();
+        let err = backend
+            .start_multitrack_capture(&CaptureConfig::default(), Box::new(NoopTrackHandler))
+            .unwrap_err();
+        assert!(matches!(err, CaptureError::BackendUnavailable(_)));
     }
 }
@@ -1,11 +1,43 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tauri::image::Image;
 use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
 use tauri::tray::TrayIconBuilder;
-use tauri::{App, AppHandle, Wry};
+use tauri::{App, AppHandle, Manager, Wry};
 
-use crate::audio::AudioDeviceManager;
-use crate::recorder::{Recorder, RecorderConfig, RecorderState};
+use crate::audio::{AudioDeviceInfo, AudioDeviceManager};
+use crate::player::{spawn_player_actor, PlayerCommand, PlayerConfig, PlayerState, PlayerStatus};
+use crate::recorder::{
+    spawn_actor, CaptureMode, RecordFormat, RecorderCommand, RecorderConfig, RecorderState,
+    RecorderStatus,
+};
+
+/// How often the tooltip's live level meter polls `RecorderCommand::QueryLevel`
+/// while recording -- a few times per second is enough to look "live"
+/// without saturating the actor's command channel.
+const LEVEL_METER_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Number of recent levels the tooltip meter keeps, each rendered as one
+/// bar character -- a short scrolling history rather than a single static
+/// reading, so a user can tell signal is *continuing* to arrive.
+const LEVEL_METER_HISTORY: usize = 4;
+
+/// Output formats offered in the tray's "Format" section, in display order.
+/// Mirrors `RecordFormat`'s variants except `Mp3`, whose `key()` ("mp3") is
+/// used here rather than constructing one (it carries bitrate/quality that
+/// don't matter for menu purposes).
+const FORMAT_CHOICES: &[(&str, &str)] = &[
+    ("wav", "WAV (Lossless)"),
+    ("flac", "FLAC (Lossless, smaller)"),
+    ("mp3", "MP3 (Compressed)"),
+];
+
+/// How often the background hotplug watcher re-enumerates input devices.
+/// Low-frequency polling rather than CoreAudio's
+/// `AudioObjectAddPropertyListener` -- good enough to catch a plug/unplug
+/// within a couple seconds without pulling in platform-specific FFI.
+const DEVICE_WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 // Tray icons embedded at compile time.
 // Idle icon: pure black foreground + alpha (macOS template image).
@@ -13,32 +45,144 @@ use crate::recorder::{Recorder, RecorderConfig, RecorderState};
 const TRAY_ICON_IDLE: &[u8] = include_bytes!("../icons/tray-icon.png");
 const TRAY_ICON_RECORDING: &[u8] = include_bytes!("../icons/tray-icon-recording.png");
 
-/// Shared state that is Send+Sync safe.
-struct SendableState {
-    recorder: Recorder,
+/// Main-thread view of the recorder, backed by the actor thread spawned by
+/// `recorder::spawn_actor`. The `Recorder` (and its `cpal::Stream`) never
+/// leaves that thread, so this struct only ever holds a command sender, a
+/// status receiver, and mirrored fields updated from `RecorderStatus`
+/// messages -- no `unsafe impl Send`/`Sync` required.
+///
+/// `pub(crate)` (rather than private) so `hotkey` can fetch the same managed
+/// instance via `app.state::<Mutex<TrayState>>()` and drive the same toggle
+/// logic as the tray menu.
+pub(crate) struct TrayState {
+    commands: mpsc::Sender<RecorderCommand>,
+    status: mpsc::Receiver<RecorderStatus>,
+    /// Lists devices for the menu only -- `AudioDeviceManager` has no
+    /// interior mutable state, so it's safe to keep a second instance here
+    /// independent of the one the actor thread owns.
     device_manager: AudioDeviceManager,
+    recorder_state: RecorderState,
+    output_dir: std::path::PathBuf,
+    selected_device_index: Option<usize>,
+    selected_format_key: &'static str,
+    /// Whether `CaptureMode::MixWithSystemAudio` is selected instead of the
+    /// single selected input device. Mutually exclusive with
+    /// `selected_device_index` in the device section's checkmarks.
+    mix_system_audio: bool,
+    player_commands: mpsc::Sender<PlayerCommand>,
+    player_status: mpsc::Receiver<PlayerStatus>,
+    player_state: PlayerState,
+    /// Path of the most recently saved recording, or `None` before any
+    /// recording has been saved. The "Play Last Recording" item is omitted
+    /// from the menu entirely until this is set.
+    last_recording: Option<std::path::PathBuf>,
+    /// Recent RMS levels (dBFS) polled while recording, oldest first, capped
+    /// at `LEVEL_METER_HISTORY` -- the tooltip meter's scrolling history.
+    level_history: Vec<f32>,
 }
 
-// Safety: On macOS, Tauri menu events are dispatched on the main thread.
-// The cpal::Stream inside Recorder is only accessed from menu event handlers,
-// which all run on the same (main) thread.
-unsafe impl Send for SendableState {}
-unsafe impl Sync for SendableState {}
+impl TrayState {
+    /// Block until the actor reports the next status update, applying it to
+    /// our mirrored fields as it goes.
+    fn recv_status(&mut self) -> RecorderStatus {
+        let status = self.status.recv().unwrap_or(RecorderStatus::Error(
+            "recorder actor thread is gone".to_string(),
+        ));
+        if let RecorderStatus::StateChanged(state) = status {
+            self.recorder_state = state;
+        }
+        status
+    }
+
+    /// Block until the player actor reports the next status update, applying
+    /// it to `player_state` as it goes.
+    fn recv_player_status(&mut self) -> PlayerStatus {
+        let status = self.player_status.recv().unwrap_or(PlayerStatus::Error(
+            "player actor thread is gone".to_string(),
+        ));
+        if let PlayerStatus::StateChanged(state) = status {
+            self.player_state = state;
+        }
+        status
+    }
+}
 
 /// Set up the system tray with menus. Called once during app setup.
 pub fn setup_tray(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
-    let state = Arc::new(Mutex::new(SendableState {
-        recorder: Recorder::new(RecorderConfig::default()),
+    let format_key = crate::config::get_output_format()
+        .and_then(|key| FORMAT_CHOICES.iter().find(|(k, _)| *k == key))
+        .map(|(key, _)| *key)
+        .unwrap_or(RecordFormat::default().key());
+
+    // Only honor a persisted "mix system audio" choice if a suitable
+    // loopback/aggregate device is still actually plugged in.
+    let startup_devices = AudioDeviceManager::new().list_input_devices();
+    let mix_system_audio =
+        crate::config::get_mix_system_audio() && find_aggregate_device(&startup_devices).is_some();
+    let capture = if mix_system_audio {
+        let system_device = find_aggregate_device(&startup_devices)
+            .expect("checked above")
+            .name
+            .clone();
+        CaptureMode::MixWithSystemAudio {
+            system_device,
+            mic_gain: 1.0,
+            system_gain: 1.0,
+        }
+    } else {
+        CaptureMode::default()
+    };
+
+    let config = RecorderConfig {
+        format: RecordFormat::from_key(format_key).unwrap_or_default(),
+        capture,
+        ..RecorderConfig::default()
+    };
+    let output_dir = config.output_dir.clone();
+    let last_recording = crate::recordings::list_recordings(&output_dir)
+        .unwrap_or_default()
+        .into_iter()
+        .max_by(|a, b| a.name.cmp(&b.name))
+        .map(|r| std::path::PathBuf::from(r.path));
+
+    let handle = spawn_actor(config, AudioDeviceManager::new());
+    let player_handle = spawn_player_actor(
+        PlayerConfig {
+            output_dir: output_dir.clone(),
+            selected_device_name: None,
+        },
+        AudioDeviceManager::new(),
+    );
+
+    let state = Mutex::new(TrayState {
+        commands: handle.commands,
+        status: handle.status,
         device_manager: AudioDeviceManager::new(),
-    }));
+        recorder_state: RecorderState::Idle,
+        output_dir,
+        selected_device_index: None,
+        selected_format_key: format_key,
+        mix_system_audio,
+        player_commands: player_handle.commands,
+        player_status: player_handle.status,
+        player_state: PlayerState::Idle,
+        last_recording,
+        level_history: Vec::new(),
+    });
 
     let tray_menu = {
         let s = state.lock().unwrap();
         build_tray_menu(app.handle(), &s)?
     };
 
-    let state_for_event = state.clone();
+    // Managed so `hotkey::register_hotkey`'s handler can reach the same
+    // recorder state as the tray menu's "toggle_recording" item.
+    app.manage(state);
 
+    spawn_device_watcher(app.handle().clone());
+    spawn_level_meter(app.handle().clone());
+
+    let app_for_event = app.handle().clone();
     let _tray = TrayIconBuilder::with_id("main-tray")
         .icon(Image::from_bytes(TRAY_ICON_IDLE)?)
         .icon_as_template(true)
@@ -46,18 +190,160 @@ pub fn setup_tray(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
         .show_menu_on_left_click(true)
         .tooltip("Lyre Recorder")
         .on_menu_event(move |app, event| {
-            handle_menu_event(app, &event.id().0, &state_for_event);
+            let state = app.state::<Mutex<TrayState>>();
+            handle_menu_event(app, &event.id().0, &state);
         })
-        .build(app)?;
+        .build(&app_for_event)?;
 
     Ok(())
 }
 
+/// Poll `list_input_devices` on a background thread so a plugged-in or
+/// removed device refreshes the tray menu even when the user hasn't clicked
+/// it, and so a mid-recording disconnect of the *selected* device falls back
+/// to the default rather than silently failing on the next `start`.
+///
+/// Owns its own `AudioDeviceManager` (stateless enumeration, safe to
+/// duplicate) and hops back onto the main thread via `run_on_main_thread` to
+/// touch `TrayState`/the tray menu, same as Tauri's dialog callbacks do.
+fn spawn_device_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let manager = AudioDeviceManager::new();
+        let mut last = manager.list_input_devices();
+        loop {
+            thread::sleep(DEVICE_WATCH_INTERVAL);
+            let current = manager.list_input_devices();
+            if AudioDeviceManager::devices_changed(&last, &current) {
+                last = current.clone();
+                let app_for_main = app.clone();
+                let _ = app.run_on_main_thread(move || {
+                    on_devices_changed(&app_for_main, &current);
+                });
+            }
+        }
+    });
+}
+
+/// Runs on the main thread (see `spawn_device_watcher`) whenever the input
+/// device list changes. Falls back to the default device if the one
+/// currently selected just vanished, then rebuilds the menu either way so
+/// the device list reflects what's actually plugged in.
+fn on_devices_changed(app: &AppHandle, current: &[AudioDeviceInfo]) {
+    let Some(state) = app.try_state::<Mutex<TrayState>>() else {
+        return;
+    };
+    let mut s = state.lock().unwrap();
+
+    if let Some(idx) = s.selected_device_index {
+        let still_present = current.iter().any(|d| d.index == idx);
+        if !still_present {
+            eprintln!("selected input device disconnected; falling back to default");
+            s.commands.send(RecorderCommand::SelectDevice(None)).unwrap();
+            s.recv_status();
+            s.selected_device_index = None;
+        }
+    }
+
+    if s.mix_system_audio && find_aggregate_device(current).is_none() {
+        eprintln!("system audio loopback device disconnected; falling back to mic only");
+        s.commands
+            .send(RecorderCommand::SetCaptureMode(CaptureMode::Single))
+            .unwrap();
+        s.recv_status();
+        s.mix_system_audio = false;
+        let _ = crate::config::save_mix_system_audio(false);
+    }
+
+    rebuild_tray_menu(app, &s);
+}
+
+/// Tick a timer on a background thread so the tooltip's live level meter
+/// updates a few times per second without depending on any capture
+/// callback -- the actual level still comes from the recorder actor via
+/// `RecorderCommand::QueryLevel`, this thread just drives the polling
+/// schedule and hops onto the main thread to apply it, same pattern as
+/// `spawn_device_watcher`.
+fn spawn_level_meter(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(LEVEL_METER_INTERVAL);
+        let app_for_main = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            on_level_tick(&app_for_main);
+        });
+    });
+}
+
+/// Runs on the main thread (see `spawn_level_meter`). Polls the recorder's
+/// current level while recording and rewrites the tray tooltip with a
+/// scrolling meter; a no-op while idle so the tooltip stays put at "Lyre
+/// Recorder (Recording...)" between samples -- `toggle_recording` already
+/// resets it on start/stop.
+fn on_level_tick(app: &AppHandle) {
+    let Some(state) = app.try_state::<Mutex<TrayState>>() else {
+        return;
+    };
+    let mut s = state.lock().unwrap();
+    if s.recorder_state != RecorderState::Recording {
+        return;
+    }
+
+    // `QueryLevel` replies with exactly one `Level` (see `spawn_actor`); the
+    // only message that can arrive ahead of it is the device-disconnect
+    // `Error`, so after an `Error` the `Level` is read as well -- there's no
+    // trailing reply left behind to desync a later command's read.
+    s.commands.send(RecorderCommand::QueryLevel).unwrap();
+    let mut reply = s.recv_status();
+    if matches!(reply, RecorderStatus::Error(_)) {
+        reply = s.recv_status();
+    }
+    let level = match reply {
+        RecorderStatus::Level(level) => level,
+        _ => None,
+    };
+    let Some(level) = level else {
+        return;
+    };
+
+    let db = level.rms_db();
+    s.level_history.push(db);
+    if s.level_history.len() > LEVEL_METER_HISTORY {
+        s.level_history.remove(0);
+    }
+
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_tooltip(Some(format_level_tooltip(&s.level_history)));
+    }
+}
+
+/// Quantize an RMS level in dBFS to one of 8 Unicode block-height
+/// characters, clamping to a -60..0 dB display range (below -60 dB reads as
+/// silence for meter purposes).
+fn level_bar(db: f32) -> char {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let clamped = db.clamp(-60.0, 0.0);
+    let frac = (clamped + 60.0) / 60.0;
+    let idx = (frac * (BLOCKS.len() - 1) as f32).round() as usize;
+    BLOCKS[idx.min(BLOCKS.len() - 1)]
+}
+
+/// Render the tooltip string for the tray's live level meter, e.g.
+/// "Lyre Recorder (Recording ▂▅▇▆ -18 dB)".
+fn format_level_tooltip(history: &[f32]) -> String {
+    let bars: String = history.iter().map(|db| level_bar(*db)).collect();
+    let current_db = history.last().copied().unwrap_or(f32::NEG_INFINITY);
+    let db_label = if current_db.is_finite() {
+        format!("{:.0} dB", current_db)
+    } else {
+        "-inf dB".to_string()
+    };
+    format!("Lyre Recorder (Recording {bars} {db_label})")
+}
+
 fn build_tray_menu(
     handle: &AppHandle,
-    state: &SendableState,
+    state: &TrayState,
 ) -> Result<Menu<Wry>, Box<dyn std::error::Error>> {
-    let is_recording = state.recorder.state() == RecorderState::Recording;
+    let is_recording = state.recorder_state == RecorderState::Recording;
 
     // Toggle recording
     let toggle_label = if is_recording {
@@ -74,14 +360,19 @@ fn build_tray_menu(
     // Section header (disabled menu item as label).
     let device_header =
         MenuItem::with_id(handle, "device_header", "Input Device", false, None::<&str>)?;
-    let device_items = build_device_items(handle, state)?;
+    let device_items = build_device_items(handle, state, is_recording)?;
 
     let sep2 = PredefinedMenuItem::separator(handle)?;
 
+    // Flat format list (mirrors the device list's flat-not-submenu pattern).
+    let format_header =
+        MenuItem::with_id(handle, "format_header", "Format", false, None::<&str>)?;
+    let format_items = build_format_items(handle, state, is_recording)?;
+
+    let sep2b = PredefinedMenuItem::separator(handle)?;
+
     // Output folder display
     let output_dir_display = state
-        .recorder
-        .config
         .output_dir
         .file_name()
         .and_then(|n| n.to_str())
@@ -103,6 +394,26 @@ fn build_tray_menu(
         None::<&str>,
     )?;
 
+    // "Play Last Recording" / "Stop Playback" -- only once a recording
+    // exists to play, mirroring the optional-item pattern device/format
+    // sections use for "nothing to show yet" states.
+    let playback_item = if state.last_recording.is_some() {
+        let label = if state.player_state == PlayerState::Playing {
+            "⏹ Stop Playback"
+        } else {
+            "▶ Play Last Recording"
+        };
+        Some(MenuItem::with_id(
+            handle,
+            "toggle_playback",
+            label,
+            true,
+            None::<&str>,
+        )?)
+    } else {
+        None
+    };
+
     let sep3 = PredefinedMenuItem::separator(handle)?;
 
     let quit = MenuItem::with_id(handle, "quit", "Quit Lyre Recorder", true, None::<&str>)?;
@@ -116,8 +427,16 @@ fn build_tray_menu(
         items.push(Box::new(item));
     }
     items.push(Box::new(sep2));
+    items.push(Box::new(format_header));
+    for item in format_items {
+        items.push(Box::new(item));
+    }
+    items.push(Box::new(sep2b));
     items.push(Box::new(output_item));
     items.push(Box::new(open_folder));
+    if let Some(playback_item) = playback_item {
+        items.push(Box::new(playback_item));
+    }
     items.push(Box::new(sep3));
     items.push(Box::new(quit));
 
@@ -131,15 +450,16 @@ fn build_tray_menu(
 /// Build flat list of device check-menu-items (no submenu).
 fn build_device_items(
     handle: &AppHandle,
-    state: &SendableState,
+    state: &TrayState,
+    is_recording: bool,
 ) -> Result<Vec<CheckMenuItem<Wry>>, Box<dyn std::error::Error>> {
     let devices = state.device_manager.list_input_devices();
-    let selected_idx = state.recorder.config.selected_device_index;
+    let selected_idx = state.selected_device_index;
 
     let mut items: Vec<CheckMenuItem<Wry>> = Vec::new();
 
     // "Auto (Default)" option
-    let auto_checked = selected_idx.is_none();
+    let auto_checked = selected_idx.is_none() && !state.mix_system_audio;
     let auto_item = CheckMenuItem::with_id(
         handle,
         "device_auto",
@@ -157,58 +477,115 @@ fn build_device_items(
             format!("  {}", dev.name)
         };
         let id = format!("device_{}", dev.index);
-        let checked = selected_idx == Some(dev.index);
+        let checked = !state.mix_system_audio && selected_idx == Some(dev.index);
         let item = CheckMenuItem::with_id(handle, &id, &label, true, checked, None::<&str>)?;
         items.push(item);
     }
 
+    // "Mic + System Audio" aggregate capture -- reuses CaptureMode::MixWithSystemAudio
+    // (mixing via a second input stream opened on a loopback/monitor device,
+    // resampled and gain-summed with the mic; see `recorder::CaptureMode`),
+    // only enabled once such a device is actually plugged in.
+    let aggregate_device = find_aggregate_device(&devices);
+    let mix_item = CheckMenuItem::with_id(
+        handle,
+        "device_mix_system",
+        "  🎙+🔊 Mic + System Audio",
+        aggregate_device.is_some() && !is_recording,
+        state.mix_system_audio,
+        None::<&str>,
+    )?;
+    items.push(mix_item);
+
     Ok(items)
 }
 
-fn handle_menu_event(app: &AppHandle, id: &str, state: &Arc<Mutex<SendableState>>) {
-    match id {
-        "toggle_recording" => {
-            let mut s = state.lock().unwrap();
-            let current_state = s.recorder.state();
-            match current_state {
-                RecorderState::Idle => {
-                    // Borrow device_manager via raw pointer to avoid
-                    // simultaneous mutable + immutable borrow of `s`.
-                    let dm_ptr = &s.device_manager as *const AudioDeviceManager;
-                    // Safety: dm_ptr points into the same MutexGuard we hold,
-                    // and `start` does not modify device_manager.
-                    match s.recorder.start(unsafe { &*dm_ptr }) {
-                        Ok(path) => {
-                            println!("recording started: {}", path.display());
-                            update_tray_icon(app, true);
-                        }
-                        Err(e) => {
-                            eprintln!("failed to start recording: {e}");
-                        }
-                    }
-                }
-                RecorderState::Recording => match s.recorder.stop() {
-                    Ok(path) => {
-                        println!("recording saved: {}", path.display());
-                        update_tray_icon(app, false);
-                    }
-                    Err(e) => {
-                        eprintln!("failed to stop recording: {e}");
-                    }
-                },
+/// Find an aggregate/multi-output device to use as the "system audio" source
+/// for `CaptureMode::MixWithSystemAudio` -- see `audio::is_aggregate_name`.
+fn find_aggregate_device(devices: &[AudioDeviceInfo]) -> Option<&AudioDeviceInfo> {
+    devices.iter().find(|d| crate::audio::is_aggregate_name(&d.name))
+}
+
+/// Build flat list of format check-menu-items (no submenu), disabled while
+/// recording -- same reasoning as the output-dir item: switching encoders
+/// mid-stream would mean re-opening the sink, which `Recorder` doesn't
+/// support.
+fn build_format_items(
+    handle: &AppHandle,
+    state: &TrayState,
+    is_recording: bool,
+) -> Result<Vec<CheckMenuItem<Wry>>, Box<dyn std::error::Error>> {
+    FORMAT_CHOICES
+        .iter()
+        .map(|(key, label)| {
+            let id = format!("format_{key}");
+            let checked = state.selected_format_key == *key;
+            CheckMenuItem::with_id(handle, &id, *label, !is_recording, checked, None::<&str>)
+                .map_err(Into::into)
+        })
+        .collect()
+}
+
+/// Toggle recording on/off, reflecting the new state in the tray icon and
+/// menu. Shared by the tray menu's "toggle_recording" item and
+/// `hotkey::register_hotkey`'s global shortcut handler, so both trigger the
+/// exact same start/stop path.
+pub(crate) fn toggle_recording(app: &AppHandle, state: &Mutex<TrayState>) {
+    let mut s = state.lock().unwrap();
+
+    let starting = s.recorder_state == RecorderState::Idle;
+    let command = if starting {
+        RecorderCommand::Start
+    } else {
+        RecorderCommand::Stop
+    };
+    s.commands.send(command).unwrap();
+
+    match s.recv_status() {
+        RecorderStatus::Started(path) => {
+            s.recorder_state = RecorderState::Recording;
+            s.level_history.clear();
+            println!("recording started: {}", path.display());
+            update_tray_icon(app, true);
+        }
+        RecorderStatus::Stopped(path) => {
+            s.recorder_state = RecorderState::Idle;
+            // `RecorderStatus::Stopped` doesn't distinguish a saved file from
+            // one discarded as silence (see `spawn_actor`) -- both carry a
+            // path, but only a real file should become "last recording".
+            if path.is_file() {
+                s.last_recording = Some(path.clone());
             }
-            // Rebuild menu so "Start/Stop Recording" label updates
-            rebuild_tray_menu(app, &s);
+            s.level_history.clear();
+            println!("recording stopped: {}", path.display());
+            update_tray_icon(app, false);
         }
+        RecorderStatus::Error(e) => {
+            eprintln!("recording command failed: {e}");
+        }
+        RecorderStatus::StateChanged(_) | RecorderStatus::Level(_) => {}
+    }
+
+    // Rebuild menu so "Start/Stop Recording" label updates
+    rebuild_tray_menu(app, &s);
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str, state: &Mutex<TrayState>) {
+    match id {
+        "toggle_recording" => toggle_recording(app, state),
         "set_output_dir" => {
             use tauri_plugin_dialog::DialogExt;
-            let state_clone = state.clone();
             let app_handle = app.clone();
             app.dialog().file().pick_folder(move |folder| {
                 if let Some(path) = folder {
-                    let mut s = state_clone.lock().unwrap();
+                    let state = app_handle.state::<Mutex<TrayState>>();
+                    let mut s = state.lock().unwrap();
                     if let Some(path_buf) = path.as_path() {
-                        s.recorder.set_output_dir(path_buf.to_path_buf());
+                        s.commands
+                            .send(RecorderCommand::SetOutputDir(path_buf.to_path_buf()))
+                            .unwrap();
+                        s.recv_status();
+                        s.output_dir = path_buf.to_path_buf();
                         println!("output dir set to: {path}");
                     }
                     rebuild_tray_menu(&app_handle, &s);
@@ -217,26 +594,92 @@ fn handle_menu_event(app: &AppHandle, id: &str, state: &Arc<Mutex<SendableState>
         }
         "open_output_dir" => {
             let s = state.lock().unwrap();
-            let dir = s.recorder.config.output_dir.clone();
+            let dir = s.output_dir.clone();
             drop(s);
             let _ = std::process::Command::new("open").arg(&dir).spawn();
         }
-        "quit" => {
+        "toggle_playback" => {
             let mut s = state.lock().unwrap();
-            if s.recorder.state() == RecorderState::Recording {
-                let _ = s.recorder.stop();
+            if s.player_state == PlayerState::Playing {
+                s.player_commands.send(PlayerCommand::Stop).unwrap();
+                match s.recv_player_status() {
+                    PlayerStatus::Stopped => {
+                        s.player_state = PlayerState::Idle;
+                    }
+                    PlayerStatus::Error(e) => eprintln!("playback command failed: {e}"),
+                    PlayerStatus::Started(_) | PlayerStatus::StateChanged(_) => {}
+                }
+            } else if let Some(path) = s.last_recording.clone() {
+                s.player_commands.send(PlayerCommand::Play(path)).unwrap();
+                match s.recv_player_status() {
+                    PlayerStatus::Started(path) => {
+                        s.player_state = PlayerState::Playing;
+                        println!("playing: {}", path.display());
+                    }
+                    PlayerStatus::Error(e) => eprintln!("playback error: {e}"),
+                    PlayerStatus::Stopped | PlayerStatus::StateChanged(_) => {}
+                }
             }
+            rebuild_tray_menu(app, &s);
+        }
+        "quit" => {
+            let mut s = state.lock().unwrap();
+            s.commands.send(RecorderCommand::Quit).unwrap();
+            s.recv_status();
+            s.player_commands.send(PlayerCommand::Quit).unwrap();
+            s.recv_player_status();
             drop(s);
             app.exit(0);
         }
+        "device_mix_system" => {
+            let mut s = state.lock().unwrap();
+            if s.mix_system_audio {
+                // Toggling off falls back to the single selected device
+                // (or default), same as any other device choice.
+                s.commands
+                    .send(RecorderCommand::SetCaptureMode(CaptureMode::Single))
+                    .unwrap();
+                s.recv_status();
+                s.mix_system_audio = false;
+                let _ = crate::config::save_mix_system_audio(false);
+                println!("capture mode set to: single device");
+            } else {
+                let devices = s.device_manager.list_input_devices();
+                if let Some(aggregate) = find_aggregate_device(&devices) {
+                    let system_device = aggregate.name.clone();
+                    s.commands
+                        .send(RecorderCommand::SetCaptureMode(
+                            CaptureMode::MixWithSystemAudio {
+                                system_device: system_device.clone(),
+                                mic_gain: 1.0,
+                                system_gain: 1.0,
+                            },
+                        ))
+                        .unwrap();
+                    s.recv_status();
+                    s.mix_system_audio = true;
+                    let _ = crate::config::save_mix_system_audio(true);
+                    println!("capture mode set to: mic + system audio ({system_device})");
+                }
+            }
+            rebuild_tray_menu(app, &s);
+        }
         id if id.starts_with("device_") => {
             let mut s = state.lock().unwrap();
             if id == "device_auto" {
-                s.recorder.select_device(None);
+                s.commands.send(RecorderCommand::SelectDevice(None)).unwrap();
+                s.recv_status();
+                s.selected_device_index = None;
+                s.mix_system_audio = false;
                 println!("device set to auto (default)");
             } else if let Some(idx_str) = id.strip_prefix("device_") {
                 if let Ok(idx) = idx_str.parse::<usize>() {
-                    s.recorder.select_device(Some(idx));
+                    s.commands
+                        .send(RecorderCommand::SelectDevice(Some(idx)))
+                        .unwrap();
+                    s.recv_status();
+                    s.selected_device_index = Some(idx);
+                    s.mix_system_audio = false;
                     let devices = s.device_manager.list_input_devices();
                     let name = devices
                         .iter()
@@ -248,6 +691,24 @@ fn handle_menu_event(app: &AppHandle, id: &str, state: &Arc<Mutex<SendableState>
             }
             rebuild_tray_menu(app, &s);
         }
+        id if id.starts_with("format_") => {
+            let mut s = state.lock().unwrap();
+            if s.recorder_state != RecorderState::Idle {
+                // Menu items are disabled while recording, but guard anyway
+                // in case of a stray event delivered during the transition.
+                return;
+            }
+            if let Some(key_str) = id.strip_prefix("format_") {
+                if let Some(format) = RecordFormat::from_key(key_str) {
+                    s.commands.send(RecorderCommand::SetFormat(format)).unwrap();
+                    s.recv_status();
+                    s.selected_format_key = format.key();
+                    let _ = crate::config::save_output_format(Some(key_str));
+                    println!("output format set to: {key_str}");
+                }
+            }
+            rebuild_tray_menu(app, &s);
+        }
         _ => {}
     }
 }
@@ -275,7 +736,7 @@ fn update_tray_icon(app: &AppHandle, recording: bool) {
 }
 
 /// Rebuild the tray menu to reflect current state (recording label, device selection, etc.)
-fn rebuild_tray_menu(app: &AppHandle, state: &SendableState) {
+fn rebuild_tray_menu(app: &AppHandle, state: &TrayState) {
     let menu = match build_tray_menu(app, state) {
         Ok(m) => m,
         Err(e) => {
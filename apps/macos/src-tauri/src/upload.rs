@@ -5,23 +5,46 @@
 //! 2. PUT file bytes to OSS URL with byte-level progress events
 //! 3. POST /api/recordings -> create DB record with custom metadata
 
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 use crate::http_client::normalize_url;
 
 /// Response from POST /api/upload/presign.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct PresignResponse {
-    upload_url: String,
-    oss_key: String,
-    recording_id: String,
+pub(crate) struct PresignResponse {
+    pub(crate) upload_url: String,
+    pub(crate) oss_key: String,
+    pub(crate) recording_id: String,
+}
+
+/// Response from POST /api/upload/multipart/presign: one presigned PUT URL
+/// per part, plus the OSS multipart upload id needed to complete or abort it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MultipartPresignResponse {
+    pub(crate) recording_id: String,
+    pub(crate) oss_key: String,
+    pub(crate) upload_id: String,
+    pub(crate) part_urls: Vec<String>,
+}
+
+/// A successfully-uploaded part, as required by OSS's CompleteMultipartUpload
+/// call and persisted in the on-disk checkpoint so a resume knows what's
+/// already done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CompletedPart {
+    pub(crate) part_number: u32,
+    pub(crate) etag: String,
 }
 
 /// Request body for POST /api/recordings.
@@ -40,9 +63,32 @@ struct CreateRecordingRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     sample_rate: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    channels: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bitrate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    album: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     folder_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tag_ids: Option<Vec<String>>,
+    /// ReplayGain-style gain (dB) to apply at playback to reach
+    /// `reference_lufs`, from `loudness::analyze_loudness`. `None` when
+    /// analysis failed (e.g. too short, or an undecodable format).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    track_gain_db: Option<f64>,
+    /// The LUFS reference `track_gain_db` was computed against -- see
+    /// `loudness::DEFAULT_REFERENCE_LUFS`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference_lufs: Option<f64>,
+    /// Base64 of the little-endian `u32` acoustic fingerprint from
+    /// `recordings::recording_fingerprint`, so the server (or a future
+    /// local cache) can flag this as a near-duplicate of something already
+    /// uploaded under a different file name, before the next full upload.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
 }
 
 /// Result of a successful upload.
@@ -51,17 +97,22 @@ struct CreateRecordingRequest {
 pub struct UploadResult {
     pub recording_id: String,
     pub oss_key: String,
+    /// The handle this upload ran under -- see `UploadOptions::upload_id`.
+    pub upload_id: String,
 }
 
 /// Upload progress event emitted to the frontend.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadProgress {
-    /// Current phase: "presigning", "uploading", "creating", "completed", "cancelled", "error"
+    /// Handle of the upload this event belongs to, so a batch of concurrent
+    /// uploads (see `upload_batch`) can be told apart on one shared event.
+    pub upload_id: String,
+    /// Current phase: "validating", "transcoding", "presigning", "resuming", "uploading", "creating", "retrying", "completed", "cancelled", "error"
     pub phase: String,
-    /// Bytes uploaded so far (only meaningful during "uploading" phase)
+    /// Progress so far: bytes uploaded during "uploading", samples encoded during "transcoding"
     pub bytes_sent: u64,
-    /// Total file size in bytes
+    /// Total for the current phase: file size in bytes, or total samples to transcode
     pub bytes_total: u64,
     /// Error message if phase is "error"
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -82,6 +133,47 @@ pub struct UploadOptions {
     /// Tag IDs to assign to the recording.
     #[serde(default)]
     pub tag_ids: Option<Vec<String>>,
+    /// Re-encode the source to MP3 at this quality before uploading.
+    /// Defaults to `Original` (upload the source file unchanged).
+    #[serde(default)]
+    pub quality_preset: QualityPreset,
+    /// Caller-supplied handle used to tag this upload's `upload-progress`
+    /// events and target it with `cancel_upload`. Generated from the file
+    /// name and a timestamp (see `generate_upload_handle`) when omitted.
+    #[serde(default)]
+    pub upload_id: Option<String>,
+    /// Upload in fixed-size parts via OSS multipart, checkpointing progress
+    /// to a sidecar file so a retry or a cancellation can resume from the
+    /// first missing part instead of restarting from byte zero. See
+    /// `upload_to_oss_chunked_with_progress`.
+    #[serde(default)]
+    pub chunked: bool,
+}
+
+/// Quality preset controlling optional client-side MP3 transcoding before
+/// upload. Re-encoding an uncompressed WAV source trades a little local CPU
+/// time for a much smaller upload and less OSS storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QualityPreset {
+    /// Upload the source file as-is; no transcoding.
+    #[default]
+    Original,
+    Mp3_320,
+    Mp3_192,
+    Mp3_128,
+}
+
+impl QualityPreset {
+    /// LAME bitrate for this preset, or `None` for `Original` (no transcode).
+    fn lame_bitrate(self) -> Option<mp3lame_encoder::Bitrate> {
+        match self {
+            QualityPreset::Original => None,
+            QualityPreset::Mp3_320 => Some(mp3lame_encoder::Bitrate::Kbps320),
+            QualityPreset::Mp3_192 => Some(mp3lame_encoder::Bitrate::Kbps192),
+            QualityPreset::Mp3_128 => Some(mp3lame_encoder::Bitrate::Kbps128),
+        }
+    }
 }
 
 /// A folder from the server.
@@ -101,27 +193,51 @@ pub struct ServerTag {
     pub name: String,
 }
 
-/// Shared cancellation flag for the active upload.
-/// Uses AtomicBool so it can be checked from both the upload task and the frontend.
-static CANCEL_FLAG: AtomicBool = AtomicBool::new(false);
+/// Registry of cancellation tokens for in-flight uploads, keyed by upload
+/// handle (see `UploadOptions::upload_id`). Replaces a single global flag so
+/// concurrent uploads -- see `upload_batch` -- can be cancelled individually
+/// instead of one `cancel_upload` call aborting every transfer at once.
+fn cancel_registry() -> &'static Mutex<HashMap<String, CancellationToken>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, CancellationToken>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Register a fresh cancellation token for `handle` and return it to thread
+/// through that upload's steps. Replaces any stale token left under the same
+/// handle (e.g. a retried queue entry reusing its id).
+pub(crate) fn register_upload(handle: &str) -> CancellationToken {
+    let token = CancellationToken::new();
+    cancel_registry()
+        .lock()
+        .unwrap()
+        .insert(handle.to_string(), token.clone());
+    token
+}
 
-/// Request cancellation of the current upload.
-pub fn cancel_upload() {
-    CANCEL_FLAG.store(true, Ordering::SeqCst);
+/// Drop `handle`'s cancellation token once its upload finishes, so the
+/// registry doesn't grow unbounded across the app's lifetime.
+pub(crate) fn unregister_upload(handle: &str) {
+    cancel_registry().lock().unwrap().remove(handle);
 }
 
-/// Check if cancellation has been requested.
-fn is_cancelled() -> bool {
-    CANCEL_FLAG.load(Ordering::SeqCst)
+/// Request cancellation of a single in-flight upload by its handle.
+/// A no-op if the handle is unknown -- already finished, or never started.
+pub fn cancel_upload(handle: &str) {
+    if let Some(token) = cancel_registry().lock().unwrap().get(handle) {
+        token.cancel();
+    }
 }
 
-/// Reset the cancellation flag (called at the start of each upload).
-fn reset_cancel() {
-    CANCEL_FLAG.store(false, Ordering::SeqCst);
+/// Whether `handle` has a live cancellation token registered -- i.e. its
+/// upload is currently between `register_upload` and `unregister_upload`.
+/// Used by `upload_queue::upload_state_for` to report a dynamic "Uploading"
+/// state without any extra persisted bookkeeping.
+pub(crate) fn is_upload_active(handle: &str) -> bool {
+    cancel_registry().lock().unwrap().contains_key(handle)
 }
 
 /// Emit an upload progress event to the frontend.
-fn emit_progress(app: &tauri::AppHandle, progress: &UploadProgress) {
+pub(crate) fn emit_progress(app: &tauri::AppHandle, progress: &UploadProgress) {
     let _ = app.emit("upload-progress", progress);
 }
 
@@ -173,6 +289,13 @@ pub async fn fetch_tags() -> Result<Vec<ServerTag>, String> {
 
     let base_url = normalize_url(&config.server_url);
     let client = build_client(&config.token)?;
+    fetch_tags_with_client(&client, &base_url).await
+}
+
+async fn fetch_tags_with_client(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> Result<Vec<ServerTag>, String> {
     let url = format!("{base_url}/api/tags");
 
     let response = client
@@ -203,21 +326,82 @@ pub async fn fetch_tags() -> Result<Vec<ServerTag>, String> {
     Ok(body.items)
 }
 
+/// Resolve a source file's embedded genre/comment tag to matching
+/// `ServerTag` ids (case-insensitive name match), for auto-tagging uploads
+/// that didn't specify `tag_ids` explicitly. Returns an empty vec rather
+/// than an error if the server call fails, so a tagging hiccup never blocks
+/// the upload itself.
+pub(crate) async fn resolve_tag_ids_from_metadata(
+    client: &reqwest::Client,
+    base_url: &str,
+    metadata: &AudioMetadata,
+) -> Vec<String> {
+    let Some(genre) = metadata.genre.as_ref().filter(|g| !g.trim().is_empty()) else {
+        return Vec::new();
+    };
+
+    let tags = match fetch_tags_with_client(client, base_url).await {
+        Ok(tags) => tags,
+        Err(_) => return Vec::new(),
+    };
+
+    let matched = tags
+        .into_iter()
+        .find(|t| t.name.eq_ignore_ascii_case(genre.trim()))
+        .map(|t| vec![t.id])
+        .unwrap_or_default();
+    debug!(%genre, matched = matched.len(), "resolved tags from genre");
+    matched
+}
+
+/// Generate a stable upload handle when the caller didn't supply one via
+/// `UploadOptions::upload_id`. Mirrors `upload_queue::enqueue_upload`'s id
+/// scheme (timestamp + file stem) so handles stay recognizable in logs.
+fn generate_upload_handle(file_path: &str) -> String {
+    let file_stem = Path::new(file_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "upload".to_string());
+    format!("{}-{file_stem}", chrono::Local::now().timestamp_millis())
+}
+
 /// Upload a local audio file to the Lyre web app with progress and cancellation support.
 ///
 /// Reads config (server_url, token) from the config file, then performs
 /// the 3-step upload: presign -> PUT to OSS -> create recording record.
 ///
-/// Emits `upload-progress` events to the frontend throughout the process.
-/// Checks the cancellation flag between each step and during the byte upload.
+/// Emits `upload-progress` events to the frontend throughout the process,
+/// each tagged with this upload's handle (`options.upload_id`, or a
+/// generated one if omitted) so a batch of concurrent uploads (see
+/// `upload_batch`) can be told apart on the shared `upload-progress` event.
+/// Call `cancel_upload` with that same handle to abort just this transfer.
 ///
 /// Supported formats: MP3, WAV, M4A, AAC, OGG, FLAC, WebM.
 pub async fn upload_recording_with_progress(
     app: tauri::AppHandle,
     options: UploadOptions,
 ) -> Result<UploadResult, String> {
-    reset_cancel();
+    let handle = options
+        .upload_id
+        .clone()
+        .unwrap_or_else(|| generate_upload_handle(&options.file_path));
+    let token = register_upload(&handle);
+
+    let result = upload_recording_inner(&app, options, &handle, &token).await;
+    unregister_upload(&handle);
+    result
+}
 
+#[tracing::instrument(
+    skip(app, options, token),
+    fields(upload_id = %handle, file = %options.file_path, recording_id = tracing::field::Empty, oss_key = tracing::field::Empty)
+)]
+async fn upload_recording_inner(
+    app: &tauri::AppHandle,
+    options: UploadOptions,
+    handle: &str,
+    token: &CancellationToken,
+) -> Result<UploadResult, String> {
     let file_path = &options.file_path;
     let config = crate::config::load_config()?;
     if config.server_url.is_empty() || config.token.is_empty() {
@@ -238,19 +422,23 @@ pub async fn upload_recording_with_progress(
     // Detect audio format from extension
     let (content_type, format) = detect_audio_format(path)?;
 
-    // Read file bytes
-    let file_bytes = tokio::fs::read(path)
+    // Size comes from metadata only — the file itself is streamed from disk
+    // during the OSS PUT below so peak memory stays at one chunk regardless
+    // of file size (see `upload_to_oss_with_progress`).
+    let file_size = tokio::fs::metadata(path)
         .await
-        .map_err(|e| format!("failed to read file: {e}"))?;
-    let file_size = file_bytes.len() as u64;
+        .map_err(|e| format!("failed to stat file: {e}"))?
+        .len();
 
-    // Read audio metadata (duration, sample rate)
-    let (duration, sample_rate) = audio_metadata(path, &format);
+    // Read audio properties and embedded tags (duration, sample rate, etc.)
+    let metadata = audio_metadata_with_ffprobe_fallback(path, &format, config.ffprobe_fallback);
+    debug!(duration = ?metadata.duration, sample_rate = ?metadata.sample_rate, "probed audio metadata");
 
-    // Title: use custom title from options, or derive from filename
+    // Title: custom title from options, then embedded tag title, then filename
     let title = options
         .title
         .filter(|t| !t.trim().is_empty())
+        .or_else(|| metadata.title.clone())
         .unwrap_or_else(|| {
             path.file_stem()
                 .map(|s| s.to_string_lossy().into_owned())
@@ -260,9 +448,23 @@ pub async fn upload_recording_with_progress(
     let base_url = normalize_url(&config.server_url);
     let client = build_client(&config.token)?;
 
-    // --- Step 1: Presign ---
-    if is_cancelled() {
-        emit_progress(&app, &UploadProgress {
+    // Tags: explicit tag_ids from options, else resolve the embedded genre
+    // tag to a matching ServerTag (best-effort -- a tagging hiccup never
+    // blocks the upload).
+    let tag_ids = match options.tag_ids {
+        Some(ids) if !ids.is_empty() => Some(ids),
+        _ => {
+            let resolved = resolve_tag_ids_from_metadata(&client, &base_url, &metadata).await;
+            (!resolved.is_empty()).then_some(resolved)
+        }
+    };
+    debug!(folder_id = ?options.folder_id, ?tag_ids, "resolved folder/tags");
+
+    // --- Step 0: Validate that the file's content matches its claimed format ---
+    if token.is_cancelled() {
+        warn!("upload cancelled");
+        emit_progress(app, &UploadProgress {
+            upload_id: handle.to_string(),
             phase: "cancelled".to_string(),
             bytes_sent: 0,
             bytes_total: file_size,
@@ -271,18 +473,61 @@ pub async fn upload_recording_with_progress(
         return Err("upload cancelled".to_string());
     }
 
-    emit_progress(&app, &UploadProgress {
-        phase: "presigning".to_string(),
+    emit_progress(app, &UploadProgress {
+        upload_id: handle.to_string(),
+        phase: "validating".to_string(),
         bytes_sent: 0,
         bytes_total: file_size,
         error: None,
     });
 
-    let presign_result = presign(&client, &base_url, &file_name, &content_type).await?;
+    validate_audio_format(path, &format)?;
+
+    // Best-effort: a file that can't be loudness-analyzed (too short, or a
+    // container symphonia can't decode) still uploads fine, just without a
+    // playback gain hint. Analyzed before transcoding so it reflects the
+    // source's actual level rather than a re-encoded copy.
+    let loudness = crate::loudness::analyze_loudness(path).ok();
+    let fingerprint = fingerprint_base64(path);
+
+    // --- Step 0.5: Optionally transcode to MP3 before upload ---
+    // The transcode runs against the validated original file; on success the
+    // temp file, its content-type/format/name replace the original for the
+    // remaining steps. `_transcoded_file` must stay alive until the OSS
+    // upload finishes, since `transcoded_path` borrows from it.
+    let (path, content_type, format, file_name, file_size, _transcoded_file) =
+        match transcode_to_mp3(app, path, &format, &file_name, options.quality_preset, handle, token).await? {
+            Some(result) => {
+                let transcoded_path = result.file.path().to_path_buf();
+                let transcoded_size = tokio::fs::metadata(&transcoded_path)
+                    .await
+                    .map_err(|e| format!("failed to stat transcoded file: {e}"))?
+                    .len();
+                (
+                    transcoded_path,
+                    "audio/mpeg".to_string(),
+                    "mp3".to_string(),
+                    result.file_name,
+                    transcoded_size,
+                    Some(result.file),
+                )
+            }
+            None => (
+                path.to_path_buf(),
+                content_type,
+                format,
+                file_name,
+                file_size,
+                None,
+            ),
+        };
+    let path = path.as_path();
 
-    // --- Step 2: Upload to OSS with progress ---
-    if is_cancelled() {
-        emit_progress(&app, &UploadProgress {
+    // --- Step 1: Presign ---
+    if token.is_cancelled() {
+        warn!("upload cancelled");
+        emit_progress(app, &UploadProgress {
+            upload_id: handle.to_string(),
             phase: "cancelled".to_string(),
             bytes_sent: 0,
             bytes_total: file_size,
@@ -291,25 +536,77 @@ pub async fn upload_recording_with_progress(
         return Err("upload cancelled".to_string());
     }
 
-    emit_progress(&app, &UploadProgress {
-        phase: "uploading".to_string(),
+    emit_progress(app, &UploadProgress {
+        upload_id: handle.to_string(),
+        phase: "presigning".to_string(),
         bytes_sent: 0,
         bytes_total: file_size,
         error: None,
     });
 
-    upload_to_oss_with_progress(
-        &app,
-        &presign_result.upload_url,
-        file_bytes,
-        &content_type,
-        file_size,
-    )
-    .await?;
+    // --- Step 2: Upload to OSS with progress ---
+    // `chunked` splits the file into fixed-size parts and checkpoints
+    // completed parts to a sidecar file, so a retry (or a resume after
+    // cancellation) can skip straight to the first missing part.
+    let (recording_id, oss_key) = if options.chunked {
+        chunked_upload_to_oss(
+            app,
+            &client,
+            &base_url,
+            path,
+            &file_name,
+            &content_type,
+            file_size,
+            handle,
+            token,
+        )
+        .await?
+    } else {
+        if token.is_cancelled() {
+            warn!("upload cancelled");
+            emit_progress(app, &UploadProgress {
+                upload_id: handle.to_string(),
+                phase: "cancelled".to_string(),
+                bytes_sent: 0,
+                bytes_total: file_size,
+                error: None,
+            });
+            return Err("upload cancelled".to_string());
+        }
+
+        let presign_result = presign(&client, &base_url, &file_name, &content_type).await?;
+
+        emit_progress(app, &UploadProgress {
+            upload_id: handle.to_string(),
+            phase: "uploading".to_string(),
+            bytes_sent: 0,
+            bytes_total: file_size,
+            error: None,
+        });
+
+        upload_to_oss_with_progress(
+            app,
+            &presign_result.upload_url,
+            path,
+            &content_type,
+            file_size,
+            handle,
+            token,
+        )
+        .await?;
+
+        (presign_result.recording_id, presign_result.oss_key)
+    };
+
+    tracing::Span::current().record("recording_id", recording_id.as_str());
+    tracing::Span::current().record("oss_key", oss_key.as_str());
+    info!("negotiated oss key");
 
     // --- Step 3: Create recording record ---
-    if is_cancelled() {
-        emit_progress(&app, &UploadProgress {
+    if token.is_cancelled() {
+        warn!("upload cancelled");
+        emit_progress(app, &UploadProgress {
+            upload_id: handle.to_string(),
             phase: "cancelled".to_string(),
             bytes_sent: file_size,
             bytes_total: file_size,
@@ -318,7 +615,8 @@ pub async fn upload_recording_with_progress(
         return Err("upload cancelled".to_string());
     }
 
-    emit_progress(&app, &UploadProgress {
+    emit_progress(app, &UploadProgress {
+        upload_id: handle.to_string(),
         phase: "creating".to_string(),
         bytes_sent: file_size,
         bytes_total: file_size,
@@ -328,20 +626,23 @@ pub async fn upload_recording_with_progress(
     create_recording(
         &client,
         &base_url,
-        &presign_result.recording_id,
+        &recording_id,
         &title,
         &file_name,
-        &presign_result.oss_key,
+        &oss_key,
         file_size,
-        duration,
-        sample_rate,
+        &metadata,
         &format,
         options.folder_id,
-        options.tag_ids,
+        tag_ids,
+        loudness,
+        fingerprint,
     )
-    .await?;
+    .await
+    .inspect_err(|e| error!(error = %e, "failed to create recording record"))?;
 
-    emit_progress(&app, &UploadProgress {
+    emit_progress(app, &UploadProgress {
+        upload_id: handle.to_string(),
         phase: "completed".to_string(),
         bytes_sent: file_size,
         bytes_total: file_size,
@@ -349,11 +650,65 @@ pub async fn upload_recording_with_progress(
     });
 
     Ok(UploadResult {
-        recording_id: presign_result.recording_id,
-        oss_key: presign_result.oss_key,
+        recording_id,
+        oss_key,
+        upload_id: handle.to_string(),
     })
 }
 
+/// Maximum number of uploads `upload_batch` runs at once; the rest of the
+/// batch waits on the semaphore for a slot to free up.
+const BATCH_CONCURRENCY: usize = 3;
+
+/// Upload multiple files concurrently, bounding parallelism with a semaphore
+/// so a large batch doesn't open dozens of simultaneous OSS connections at
+/// once (the rest wait their turn, as pict-rs bounds concurrent image
+/// processing).
+///
+/// Each file is assigned an upload handle before its task starts -- reusing
+/// `options.upload_id` if the caller set one, generating one otherwise -- so
+/// the returned `(handle, result)` pairs let the frontend correlate a file
+/// with its `upload-progress` events and `cancel_upload` calls from the
+/// moment the batch is submitted, without waiting for the first event.
+pub async fn upload_batch(
+    app: tauri::AppHandle,
+    mut files: Vec<UploadOptions>,
+) -> Vec<(String, Result<UploadResult, String>)> {
+    for options in &mut files {
+        if options.upload_id.is_none() {
+            options.upload_id = Some(generate_upload_handle(&options.file_path));
+        }
+    }
+
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(BATCH_CONCURRENCY));
+
+    let tasks: Vec<(String, tokio::task::JoinHandle<Result<UploadResult, String>>)> = files
+        .into_iter()
+        .map(|options| {
+            let handle = options.upload_id.clone().expect("assigned above");
+            let app = app.clone();
+            let semaphore = semaphore.clone();
+            let task = tokio::spawn(async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .expect("upload batch semaphore should never be closed");
+                upload_recording_with_progress(app, options).await
+            });
+            (handle, task)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (handle, task) in tasks {
+        let result = task
+            .await
+            .unwrap_or_else(|e| Err(format!("upload task panicked: {e}")));
+        results.push((handle, result));
+    }
+    results
+}
+
 /// Legacy upload function without progress tracking (kept for backward compat and tests).
 pub async fn upload_recording(file_path: &str) -> Result<UploadResult, String> {
     let config = crate::config::load_config()?;
@@ -373,18 +728,22 @@ pub async fn upload_recording(file_path: &str) -> Result<UploadResult, String> {
         .into_owned();
 
     let (content_type, format) = detect_audio_format(path)?;
+    validate_audio_format(path, &format)?;
 
     let file_bytes = tokio::fs::read(path)
         .await
         .map_err(|e| format!("failed to read file: {e}"))?;
     let file_size = file_bytes.len() as u64;
 
-    let (duration, sample_rate) = audio_metadata(path, &format);
+    let metadata = audio_metadata_with_ffprobe_fallback(path, &format, config.ffprobe_fallback);
+    let loudness = crate::loudness::analyze_loudness(path).ok();
+    let fingerprint = fingerprint_base64(path);
 
-    let title = path
-        .file_stem()
-        .map(|s| s.to_string_lossy().into_owned())
-        .unwrap_or_else(|| file_name.clone());
+    let title = metadata.title.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file_name.clone())
+    });
 
     let base_url = normalize_url(&config.server_url);
     let client = build_client(&config.token)?;
@@ -401,21 +760,23 @@ pub async fn upload_recording(file_path: &str) -> Result<UploadResult, String> {
         &file_name,
         &presign_result.oss_key,
         file_size,
-        duration,
-        sample_rate,
+        &metadata,
         &format,
         None,
         None,
+        loudness,
+        fingerprint,
     )
     .await?;
 
     Ok(UploadResult {
-        recording_id: presign_result.recording_id,
+        recording_id: presign_result.recording_id.clone(),
         oss_key: presign_result.oss_key,
+        upload_id: presign_result.recording_id,
     })
 }
 
-fn build_client(token: &str) -> Result<reqwest::Client, String> {
+pub(crate) fn build_client(token: &str) -> Result<reqwest::Client, String> {
     let mut headers = HeaderMap::new();
     headers.insert(
         AUTHORIZATION,
@@ -431,7 +792,7 @@ fn build_client(token: &str) -> Result<reqwest::Client, String> {
         .map_err(|e| format!("failed to build HTTP client: {e}"))
 }
 
-async fn presign(
+pub(crate) async fn presign(
     client: &reqwest::Client,
     base_url: &str,
     file_name: &str,
@@ -466,15 +827,393 @@ async fn presign(
         .map_err(|e| format!("invalid presign response: {e}"))
 }
 
+/// Fixed part size for chunked multipart uploads (see `UploadOptions::chunked`).
+/// 8 MiB matches OSS's minimum multipart part size (all parts but the last
+/// must meet it).
+const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+pub(crate) async fn presign_multipart(
+    client: &reqwest::Client,
+    base_url: &str,
+    file_name: &str,
+    content_type: &str,
+    part_count: u32,
+) -> Result<MultipartPresignResponse, String> {
+    let url = format!("{base_url}/api/upload/multipart/presign");
+
+    let body = serde_json::json!({
+        "fileName": file_name,
+        "contentType": content_type,
+        "partCount": part_count,
+    });
+
+    let response = client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("multipart presign request failed: {e}"))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("authentication failed -- check your device token".to_string());
+    }
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("multipart presign failed (HTTP {status}): {text}"));
+    }
+
+    response
+        .json::<MultipartPresignResponse>()
+        .await
+        .map_err(|e| format!("invalid multipart presign response: {e}"))
+}
+
+/// Re-presign URLs for specific parts of an already-initiated multipart
+/// upload. Used both for the initial batch (all parts) and, on resume, for
+/// just the parts a checkpoint says are still missing -- OSS part URLs are
+/// short-lived, so a resumed upload can't reuse ones issued on a prior run.
+pub(crate) async fn presign_multipart_parts(
+    client: &reqwest::Client,
+    base_url: &str,
+    oss_key: &str,
+    upload_id: &str,
+    part_numbers: &[u32],
+) -> Result<HashMap<u32, String>, String> {
+    if part_numbers.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let url = format!("{base_url}/api/upload/multipart/parts");
+    let body = serde_json::json!({
+        "ossKey": oss_key,
+        "uploadId": upload_id,
+        "partNumbers": part_numbers,
+    });
+
+    let response = client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("multipart part presign request failed: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("multipart part presign failed (HTTP {status}): {text}"));
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PartUrlsResponse {
+        part_urls: Vec<String>,
+    }
+    let parsed: PartUrlsResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("invalid multipart part presign response: {e}"))?;
+    if parsed.part_urls.len() != part_numbers.len() {
+        return Err("server returned the wrong number of presigned part URLs".to_string());
+    }
+
+    Ok(part_numbers.iter().copied().zip(parsed.part_urls).collect())
+}
+
+/// POST /api/upload/multipart/complete, telling OSS to assemble the parts
+/// uploaded so far into the final object.
+pub(crate) async fn complete_multipart(
+    client: &reqwest::Client,
+    base_url: &str,
+    oss_key: &str,
+    upload_id: &str,
+    parts: &[CompletedPart],
+) -> Result<(), String> {
+    let url = format!("{base_url}/api/upload/multipart/complete");
+
+    let body = serde_json::json!({
+        "ossKey": oss_key,
+        "uploadId": upload_id,
+        "parts": parts,
+    });
+
+    let response = client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("multipart complete request failed: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("multipart complete failed (HTTP {status}): {text}"));
+    }
+
+    Ok(())
+}
+
+/// On-disk checkpoint for a chunked upload, persisted as a sidecar file next
+/// to the source recording (see `checkpoint_path`) so a retry or a
+/// cancellation can resume from the first part that hasn't completed instead
+/// of restarting the whole upload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct UploadCheckpoint {
+    pub(crate) recording_id: String,
+    pub(crate) oss_key: String,
+    pub(crate) upload_id: String,
+    pub(crate) part_size: u64,
+    pub(crate) completed_parts: Vec<CompletedPart>,
+}
+
+/// Path of the checkpoint sidecar file for `file_path`, e.g.
+/// `recording.wav.upload-checkpoint.json`.
+fn checkpoint_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.as_os_str().to_os_string();
+    name.push(".upload-checkpoint.json");
+    PathBuf::from(name)
+}
+
+fn load_checkpoint(checkpoint_path: &Path) -> Option<UploadCheckpoint> {
+    let content = std::fs::read_to_string(checkpoint_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_checkpoint(checkpoint_path: &Path, checkpoint: &UploadCheckpoint) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(checkpoint)
+        .map_err(|e| format!("failed to serialize upload checkpoint: {e}"))?;
+    std::fs::write(checkpoint_path, content)
+        .map_err(|e| format!("failed to write upload checkpoint: {e}"))
+}
+
+fn delete_checkpoint(checkpoint_path: &Path) {
+    let _ = std::fs::remove_file(checkpoint_path);
+}
+
+/// Upload `path` to OSS as a resumable chunked (multipart) transfer, reusing
+/// an on-disk checkpoint if one already exists so a retry after a crash or a
+/// dropped connection resumes from the first missing part instead of
+/// restarting. Returns the `(recording_id, oss_key)` pair once every part has
+/// been uploaded and the multipart upload is completed server-side.
+///
+/// Shared by the inline `upload_recording_inner` path and the background
+/// `upload_queue`, so both get the same byte-offset resume behavior for
+/// `UploadOptions::chunked` uploads.
+pub(crate) async fn chunked_upload_to_oss(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    base_url: &str,
+    path: &Path,
+    file_name: &str,
+    content_type: &str,
+    file_size: u64,
+    handle: &str,
+    token: &CancellationToken,
+) -> Result<(String, String), String> {
+    let checkpoint_path = checkpoint_path(path);
+    let total_parts = file_size.div_ceil(MULTIPART_PART_SIZE).max(1) as u32;
+
+    let mut checkpoint = match load_checkpoint(&checkpoint_path) {
+        Some(checkpoint) => checkpoint,
+        None => {
+            let presign_result =
+                presign_multipart(client, base_url, file_name, content_type, total_parts).await?;
+            UploadCheckpoint {
+                recording_id: presign_result.recording_id,
+                oss_key: presign_result.oss_key,
+                upload_id: presign_result.upload_id,
+                part_size: MULTIPART_PART_SIZE,
+                completed_parts: Vec::new(),
+            }
+        }
+    };
+
+    if token.is_cancelled() {
+        warn!("upload cancelled");
+        emit_progress(app, &UploadProgress {
+            upload_id: handle.to_string(),
+            phase: "cancelled".to_string(),
+            bytes_sent: 0,
+            bytes_total: file_size,
+            error: None,
+        });
+        return Err("upload cancelled".to_string());
+    }
+
+    let missing_parts: Vec<u32> = (1..=total_parts)
+        .filter(|n| !checkpoint.completed_parts.iter().any(|p| p.part_number == *n))
+        .collect();
+    let part_urls = presign_multipart_parts(
+        client,
+        base_url,
+        &checkpoint.oss_key,
+        &checkpoint.upload_id,
+        &missing_parts,
+    )
+    .await?;
+
+    let parts = upload_to_oss_chunked_with_progress(
+        app,
+        &part_urls,
+        path,
+        file_size,
+        &mut checkpoint,
+        &checkpoint_path,
+        handle,
+        token,
+    )
+    .await?;
+
+    complete_multipart(
+        client,
+        base_url,
+        &checkpoint.oss_key,
+        &checkpoint.upload_id,
+        &parts,
+    )
+    .await?;
+    delete_checkpoint(&checkpoint_path);
+
+    Ok((checkpoint.recording_id, checkpoint.oss_key))
+}
+
+/// Upload to OSS in fixed-size parts, checkpointing completed parts to disk
+/// after each one so a retry -- or a resume after cancellation -- can skip
+/// straight to the first missing part instead of restarting from byte zero.
+///
+/// `part_urls` only needs entries for parts not already in
+/// `checkpoint.completed_parts` -- see `presign_multipart_parts`. Emits a
+/// `"resuming"` progress event up front when the checkpoint already has
+/// completed parts, then `"uploading"` events with `bytes_sent` counting
+/// from those already-uploaded bytes rather than zero.
+pub(crate) async fn upload_to_oss_chunked_with_progress(
+    app: &tauri::AppHandle,
+    part_urls: &HashMap<u32, String>,
+    file_path: &Path,
+    file_size: u64,
+    checkpoint: &mut UploadCheckpoint,
+    checkpoint_path: &Path,
+    handle: &str,
+    token: &CancellationToken,
+) -> Result<Vec<CompletedPart>, String> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let part_size = checkpoint.part_size;
+    let mut completed: HashMap<u32, CompletedPart> = checkpoint
+        .completed_parts
+        .iter()
+        .cloned()
+        .map(|p| (p.part_number, p))
+        .collect();
+
+    let already_sent = (completed.len() as u64 * part_size).min(file_size);
+    if !completed.is_empty() {
+        emit_progress(
+            app,
+            &UploadProgress {
+                upload_id: handle.to_string(),
+                phase: "resuming".to_string(),
+                bytes_sent: already_sent,
+                bytes_total: file_size,
+                error: None,
+            },
+        );
+    }
+
+    let oss_client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(600))
+        .build()
+        .map_err(|e| format!("failed to build OSS client: {e}"))?;
+
+    let mut file = tokio::fs::File::open(file_path)
+        .await
+        .map_err(|e| format!("failed to open file: {e}"))?;
+
+    let total_parts = file_size.div_ceil(part_size).max(1) as u32;
+
+    for part_number in 1..=total_parts {
+        if completed.contains_key(&part_number) {
+            continue;
+        }
+        if token.is_cancelled() {
+            warn!("upload cancelled");
+            return Err("upload cancelled".to_string());
+        }
+
+        let offset = (part_number as u64 - 1) * part_size;
+        let this_part_size = part_size.min(file_size - offset);
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(|e| format!("failed to seek to part {part_number}: {e}"))?;
+        let mut buf = vec![0u8; this_part_size as usize];
+        file.read_exact(&mut buf)
+            .await
+            .map_err(|e| format!("failed to read part {part_number}: {e}"))?;
+
+        let part_url = part_urls
+            .get(&part_number)
+            .ok_or_else(|| format!("missing presigned URL for part {part_number}"))?;
+
+        let response = oss_client
+            .put(part_url)
+            .header(CONTENT_LENGTH, this_part_size)
+            .body(buf)
+            .send()
+            .await
+            .map_err(|e| format!("part {part_number} upload failed: {e}"))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("part {part_number} upload failed (HTTP {status}): {text}"));
+        }
+        let etag = response
+            .headers()
+            .get("ETag")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| format!("OSS response for part {part_number} had no ETag"))?
+            .to_string();
+
+        let part = CompletedPart { part_number, etag };
+        completed.insert(part_number, part.clone());
+        checkpoint.completed_parts.push(part);
+        save_checkpoint(checkpoint_path, checkpoint)?;
+        debug!(part_number, total_parts, this_part_size, "uploaded part");
+
+        let bytes_sent = (completed.len() as u64 * part_size).min(file_size);
+        emit_progress(
+            app,
+            &UploadProgress {
+                upload_id: handle.to_string(),
+                phase: "uploading".to_string(),
+                bytes_sent,
+                bytes_total: file_size,
+                error: None,
+            },
+        );
+    }
+
+    let mut parts: Vec<CompletedPart> = completed.into_values().collect();
+    parts.sort_by_key(|p| p.part_number);
+    Ok(parts)
+}
+
 /// Upload to OSS with byte-level progress tracking and cancellation support.
-async fn upload_to_oss_with_progress(
+///
+/// Streams the file directly from disk via `ReaderStream` instead of
+/// buffering it into memory first, so peak memory stays at one chunk (the
+/// `ReaderStream` default, 4 KB) regardless of file size.
+pub(crate) async fn upload_to_oss_with_progress(
     app: &tauri::AppHandle,
     upload_url: &str,
-    file_bytes: Vec<u8>,
+    file_path: &Path,
     content_type: &str,
     file_size: u64,
+    handle: &str,
+    token: &CancellationToken,
 ) -> Result<(), String> {
     use futures_util::StreamExt;
+    use tokio_util::io::ReaderStream;
 
     // Use a fresh client without Authorization header for OSS
     let oss_client = reqwest::Client::builder()
@@ -482,26 +1221,27 @@ async fn upload_to_oss_with_progress(
         .build()
         .map_err(|e| format!("failed to build OSS client: {e}"))?;
 
-    // Chunk size for progress reporting (64 KB)
-    const CHUNK_SIZE: usize = 64 * 1024;
+    let file = tokio::fs::File::open(file_path)
+        .await
+        .map_err(|e| format!("failed to open file: {e}"))?;
 
-    let bytes_sent = Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let bytes_sent = Arc::new(AtomicU64::new(0));
     let bytes_sent_clone = bytes_sent.clone();
     let app_clone = app.clone();
+    let token_clone = token.clone();
+    let handle_owned = handle.to_string();
 
-    // Create a stream of chunks from the file bytes
-    let chunks: Vec<Result<bytes::Bytes, std::io::Error>> = file_bytes
-        .chunks(CHUNK_SIZE)
-        .map(|chunk| Ok(bytes::Bytes::copy_from_slice(chunk)))
-        .collect();
-
-    let stream = futures_util::stream::iter(chunks).map(move |chunk_result: Result<bytes::Bytes, std::io::Error>| {
+    let stream = ReaderStream::new(file).map(move |chunk_result| {
         if let Ok(chunk) = &chunk_result {
+            if token_clone.is_cancelled() {
+                return Err(std::io::Error::other("upload cancelled"));
+            }
             let sent = bytes_sent_clone.fetch_add(chunk.len() as u64, Ordering::SeqCst)
                 + chunk.len() as u64;
             emit_progress(
                 &app_clone,
                 &UploadProgress {
+                    upload_id: handle_owned.clone(),
                     phase: "uploading".to_string(),
                     bytes_sent: sent,
                     bytes_total: file_size,
@@ -522,14 +1262,16 @@ async fn upload_to_oss_with_progress(
         .send()
         .await
         .map_err(|e| {
-            if is_cancelled() {
+            if token.is_cancelled() {
+                warn!("upload cancelled");
                 "upload cancelled".to_string()
             } else {
                 format!("OSS upload failed: {e}")
             }
         })?;
 
-    if is_cancelled() {
+    if token.is_cancelled() {
+        warn!("upload cancelled");
         return Err("upload cancelled".to_string());
     }
 
@@ -571,7 +1313,7 @@ async fn upload_to_oss(
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn create_recording(
+pub(crate) async fn create_recording(
     client: &reqwest::Client,
     base_url: &str,
     recording_id: &str,
@@ -579,11 +1321,12 @@ async fn create_recording(
     file_name: &str,
     oss_key: &str,
     file_size: u64,
-    duration: Option<f64>,
-    sample_rate: Option<u32>,
+    metadata: &AudioMetadata,
     format: &str,
     folder_id: Option<String>,
     tag_ids: Option<Vec<String>>,
+    loudness: Option<crate::loudness::LoudnessInfo>,
+    fingerprint: Option<String>,
 ) -> Result<(), String> {
     let url = format!("{base_url}/api/recordings");
 
@@ -593,11 +1336,18 @@ async fn create_recording(
         file_name: file_name.to_string(),
         oss_key: oss_key.to_string(),
         file_size: Some(file_size),
-        duration,
+        duration: metadata.duration,
         format: format.to_string(),
-        sample_rate,
+        sample_rate: metadata.sample_rate,
+        channels: metadata.channels,
+        bitrate: metadata.bitrate,
+        artist: metadata.artist.clone(),
+        album: metadata.album.clone(),
         folder_id,
         tag_ids,
+        track_gain_db: loudness.map(|l| l.track_gain_db),
+        reference_lufs: loudness.map(|_| crate::loudness::DEFAULT_REFERENCE_LUFS),
+        fingerprint,
     };
 
     let response = client
@@ -616,12 +1366,28 @@ async fn create_recording(
         return Err(format!("create recording failed (HTTP {status}): {text}"));
     }
 
+    debug!(%recording_id, "recording record created");
     Ok(())
 }
 
+/// Compute `recordings::recording_fingerprint` for `path` and base64-encode
+/// it (little-endian `u32`s) for the `fingerprint` field of
+/// `CreateRecordingRequest`. Best-effort -- a file the fingerprinter can't
+/// decode still uploads, just without a fingerprint for the server to
+/// match against.
+pub(crate) fn fingerprint_base64(path: &Path) -> Option<String> {
+    let fingerprint = crate::recordings::recording_fingerprint(path).ok()?;
+    let mut bytes = Vec::with_capacity(fingerprint.len() * 4);
+    for value in fingerprint {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    use base64::Engine;
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
 /// Detect audio format from file extension.
 /// Returns (content_type, format) e.g. ("audio/mpeg", "mp3") or ("audio/mp4", "m4a").
-fn detect_audio_format(path: &Path) -> Result<(String, String), String> {
+pub(crate) fn detect_audio_format(path: &Path) -> Result<(String, String), String> {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
@@ -640,18 +1406,292 @@ fn detect_audio_format(path: &Path) -> Result<(String, String), String> {
     }
 }
 
-/// Read audio metadata (duration in seconds, sample rate in Hz).
-/// For WAV files, reads from the header. For MP3 files, parses frame headers.
-fn audio_metadata(path: &Path, format: &str) -> (Option<f64>, Option<u32>) {
-    match format {
-        "wav" => wav_metadata(path),
-        "mp3" => mp3_metadata(path),
-        _ => (None, None),
+/// Verify that a file's leading bytes actually match its claimed format,
+/// rather than trusting the extension alone. Rejects mislabeled or
+/// truncated files locally instead of presigning them with the wrong
+/// `contentType` and storing them corrupt.
+pub(crate) fn validate_audio_format(path: &Path, claimed_format: &str) -> Result<(), String> {
+    use std::io::Read;
+
+    let mut header = [0u8; 12];
+    let mut file = std::fs::File::open(path).map_err(|e| format!("failed to open file: {e}"))?;
+    let n = file
+        .read(&mut header)
+        .map_err(|e| format!("failed to read file header: {e}"))?;
+    let header = &header[..n];
+
+    if matches_claimed_format(header, claimed_format) {
+        return Ok(());
+    }
+
+    let detected = sniff_format(header).unwrap_or("unknown");
+    warn!(claimed_format, detected, "audio format mismatch");
+    Err(format!(
+        "file content does not match its extension: claimed '{claimed_format}', detected '{detected}'"
+    ))
+}
+
+/// Check the claimed format's expected magic bytes against the file header.
+fn matches_claimed_format(header: &[u8], claimed_format: &str) -> bool {
+    match claimed_format {
+        "mp3" => is_mp3_header(header),
+        "wav" => is_wav_header(header),
+        // M4A and AAC (ADTS-free, bare MPEG-4 audio) both live in an ISOBMFF
+        // container with the same `ftyp` box, so one signature covers both.
+        "m4a" | "aac" => is_ftyp_header(header),
+        "ogg" => header.len() >= 4 && &header[0..4] == b"OggS",
+        "flac" => header.len() >= 4 && &header[0..4] == b"fLaC",
+        "webm" => header.len() >= 4 && header[0..4] == [0x1A, 0x45, 0xDF, 0xA3],
+        _ => false,
+    }
+}
+
+fn is_mp3_header(header: &[u8]) -> bool {
+    (header.len() >= 3 && &header[0..3] == b"ID3")
+        || (header.len() >= 2 && header[0] == 0xFF && (header[1] & 0xE0) == 0xE0)
+}
+
+fn is_wav_header(header: &[u8]) -> bool {
+    header.len() >= 12 && &header[0..4] == b"RIFF" && &header[8..12] == b"WAVE"
+}
+
+fn is_ftyp_header(header: &[u8]) -> bool {
+    header.len() >= 8 && &header[4..8] == b"ftyp"
+}
+
+/// Best-effort guess at the actual container, for error messages only.
+fn sniff_format(header: &[u8]) -> Option<&'static str> {
+    if is_mp3_header(header) {
+        Some("mp3")
+    } else if is_wav_header(header) {
+        Some("wav")
+    } else if is_ftyp_header(header) {
+        Some("m4a/aac")
+    } else if header.len() >= 4 && &header[0..4] == b"OggS" {
+        Some("ogg")
+    } else if header.len() >= 4 && &header[0..4] == b"fLaC" {
+        Some("flac")
+    } else if header.len() >= 4 && header[0..4] == [0x1A, 0x45, 0xDF, 0xA3] {
+        Some("webm")
+    } else {
+        None
     }
 }
 
-/// Read WAV metadata (duration in seconds, sample rate in Hz).
-fn wav_metadata(path: &Path) -> (Option<f64>, Option<u32>) {
+/// Number of mono samples encoded per LAME call while transcoding. Chosen to
+/// emit a progress event roughly once a second at typical voice sample rates
+/// without calling into LAME per-sample.
+const TRANSCODE_CHUNK_SAMPLES: usize = 44100;
+
+/// A successful client-side transcode: a temporary MP3 file plus the new
+/// file name to present to the rest of the upload flow.
+pub(crate) struct TranscodeResult {
+    pub(crate) file: tempfile::NamedTempFile,
+    pub(crate) file_name: String,
+}
+
+/// Re-encode a WAV source to MP3 at the requested quality before upload.
+///
+/// Downmixes multi-channel input to mono, matching the recorder's own
+/// encoder setup (see `recorder::build_mp3_writer`). Emits `"transcoding"`
+/// progress events in sample counts and checks the cancellation flag between
+/// chunks.
+///
+/// Returns `Ok(None)` -- meaning "upload the source file unchanged" -- when
+/// `preset` is `Original` or the source is anything other than WAV (MP3
+/// included). Only WAV sources are read (through `hound`); FLAC and the
+/// other formats are not transcoded, even though
+/// `recordings::decode_to_mono_i16` can decode some of them.
+pub(crate) async fn transcode_to_mp3(
+    app: &tauri::AppHandle,
+    path: &Path,
+    format: &str,
+    file_name: &str,
+    preset: QualityPreset,
+    handle: &str,
+    token: &CancellationToken,
+) -> Result<Option<TranscodeResult>, String> {
+    let Some(bitrate) = preset.lame_bitrate() else {
+        return Ok(None);
+    };
+    if format != "wav" {
+        return Ok(None);
+    }
+
+    let reader =
+        hound::WavReader::open(path).map_err(|e| format!("failed to open WAV for transcoding: {e}"))?;
+    let spec = reader.spec();
+    let channels = spec.channels as usize;
+    let total_samples = (reader.len() as usize / channels.max(1)) as u64;
+
+    let mut builder =
+        mp3lame_encoder::Builder::new().ok_or("failed to create MP3 encoder")?;
+    builder
+        .set_num_channels(1)
+        .map_err(|e| format!("failed to set channels: {e:?}"))?;
+    builder
+        .set_sample_rate(spec.sample_rate)
+        .map_err(|e| format!("failed to set sample rate: {e:?}"))?;
+    builder
+        .set_brate(bitrate)
+        .map_err(|e| format!("failed to set bitrate: {e:?}"))?;
+    builder
+        .set_quality(mp3lame_encoder::Quality::Best)
+        .map_err(|e| format!("failed to set quality: {e:?}"))?;
+    let mut encoder = builder
+        .build()
+        .map_err(|e| format!("failed to build MP3 encoder: {e:?}"))?;
+
+    let mut out_file =
+        tempfile::NamedTempFile::new().map_err(|e| format!("failed to create temp file: {e}"))?;
+
+    let mut samples_iter = reader.into_samples::<i32>();
+    let mut frame: Vec<i32> = Vec::with_capacity(channels);
+    let mut mono_chunk: Vec<i16> = Vec::with_capacity(TRANSCODE_CHUNK_SAMPLES);
+    let mut samples_done: u64 = 0;
+
+    'outer: loop {
+        mono_chunk.clear();
+        while mono_chunk.len() < TRANSCODE_CHUNK_SAMPLES {
+            frame.clear();
+            for _ in 0..channels {
+                match samples_iter.next() {
+                    Some(Ok(s)) => frame.push(s),
+                    Some(Err(e)) => return Err(format!("failed to read WAV samples: {e}")),
+                    None => break 'outer,
+                }
+            }
+            if frame.len() < channels {
+                break 'outer;
+            }
+            let sum: i64 = frame.iter().map(|&s| s as i64).sum();
+            mono_chunk.push((sum / channels as i64) as i16);
+        }
+        if mono_chunk.is_empty() {
+            break;
+        }
+
+        if token.is_cancelled() {
+            warn!("upload cancelled");
+            return Err("upload cancelled".to_string());
+        }
+
+        encode_mono_chunk(&mut encoder, &mono_chunk, &mut out_file)?;
+        samples_done += mono_chunk.len() as u64;
+
+        emit_progress(
+            app,
+            &UploadProgress {
+                upload_id: handle.to_string(),
+                phase: "transcoding".to_string(),
+                bytes_sent: samples_done,
+                bytes_total: total_samples,
+                error: None,
+            },
+        );
+    }
+
+    let mut flush_buf = Vec::new();
+    flush_buf.reserve(mp3lame_encoder::max_required_buffer_size(0));
+    let flush_size = encoder
+        .flush::<mp3lame_encoder::FlushNoGap>(flush_buf.spare_capacity_mut())
+        .map_err(|e| format!("failed to flush MP3 encoder: {e:?}"))?;
+    unsafe { flush_buf.set_len(flush_size) };
+    use std::io::Write;
+    out_file
+        .write_all(&flush_buf)
+        .map_err(|e| format!("failed to write MP3 data: {e}"))?;
+
+    Ok(Some(TranscodeResult {
+        file: out_file,
+        file_name: replace_extension(file_name, "mp3"),
+    }))
+}
+
+/// Encode one chunk of mono PCM samples and append the result to `out_file`.
+fn encode_mono_chunk(
+    encoder: &mut mp3lame_encoder::Encoder,
+    samples: &[i16],
+    out_file: &mut tempfile::NamedTempFile,
+) -> Result<(), String> {
+    use std::io::Write;
+
+    let input = mp3lame_encoder::MonoPcm(samples);
+    let mut mp3_buf = Vec::new();
+    mp3_buf.reserve(mp3lame_encoder::max_required_buffer_size(samples.len()));
+    let encoded_size = encoder
+        .encode(input, mp3_buf.spare_capacity_mut())
+        .map_err(|e| format!("MP3 encoding failed: {e:?}"))?;
+    unsafe { mp3_buf.set_len(encoded_size) };
+
+    out_file
+        .write_all(&mp3_buf)
+        .map_err(|e| format!("failed to write MP3 data: {e}"))
+}
+
+/// Swap a file name's extension, e.g. `"take1.wav"` -> `"take1.mp3"`.
+fn replace_extension(file_name: &str, new_ext: &str) -> String {
+    match file_name.rfind('.') {
+        Some(idx) => format!("{}.{new_ext}", &file_name[..idx]),
+        None => format!("{file_name}.{new_ext}"),
+    }
+}
+
+/// Audio properties and tags read from a local file, used to populate
+/// [`CreateRecordingRequest`] beyond what the filename alone can tell us.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AudioMetadata {
+    pub(crate) duration: Option<f64>,
+    pub(crate) sample_rate: Option<u32>,
+    pub(crate) channels: Option<u8>,
+    pub(crate) bitrate: Option<u32>,
+    pub(crate) title: Option<String>,
+    pub(crate) artist: Option<String>,
+    pub(crate) album: Option<String>,
+    /// Embedded genre tag (ID3v2 `TCON`, Vorbis comment `GENRE`, MP4 `gnre`/`\xa9gen`),
+    /// used to auto-resolve a matching `ServerTag` -- see `resolve_tag_ids_from_metadata`.
+    pub(crate) genre: Option<String>,
+}
+
+/// Read audio properties and embedded tags for any supported format.
+///
+/// `lofty` identifies the container from its contents rather than the file
+/// extension, so this covers MP3, WAV, M4A, AAC, OGG, FLAC and WebM from one
+/// code path -- including FLAC's `STREAMINFO` duration/sample-rate, Ogg's
+/// Vorbis/Opus headers, and MP4/M4A's `mvhd` duration -- without needing a
+/// per-container branch here. Falls back to `hound`'s WAV header parsing
+/// (duration + sample rate only, no tags) if `lofty` can't parse the file
+/// at all.
+pub(crate) fn audio_metadata(path: &Path, format: &str) -> AudioMetadata {
+    use lofty::file::AudioFile;
+    use lofty::probe::Probe;
+    use lofty::tag::Accessor;
+
+    match Probe::open(path).and_then(|p| p.read()) {
+        Ok(tagged_file) => {
+            let properties = tagged_file.properties();
+            let tag = tagged_file.primary_tag().or_else(|| tagged_file.first_tag());
+            let duration = properties.duration().as_secs_f64();
+            debug!(format, duration, "probed audio properties via lofty");
+
+            AudioMetadata {
+                duration: Some(duration),
+                sample_rate: properties.sample_rate(),
+                channels: properties.channels(),
+                bitrate: properties.audio_bitrate(),
+                title: tag.and_then(|t| t.title().map(|s| s.to_string())),
+                artist: tag.and_then(|t| t.artist().map(|s| s.to_string())),
+                album: tag.and_then(|t| t.album().map(|s| s.to_string())),
+                genre: tag.and_then(|t| t.genre().map(|s| s.to_string())),
+            }
+        }
+        Err(_) if format == "wav" => wav_metadata_fallback(path),
+        Err(_) => AudioMetadata::default(),
+    }
+}
+
+/// Last-resort WAV header parse for files `lofty` rejects outright.
+fn wav_metadata_fallback(path: &Path) -> AudioMetadata {
     match hound::WavReader::open(path) {
         Ok(reader) => {
             let spec = reader.spec();
@@ -660,25 +1700,116 @@ fn wav_metadata(path: &Path) -> (Option<f64>, Option<u32>) {
             } else {
                 None
             };
-            (duration, Some(spec.sample_rate))
+            AudioMetadata {
+                duration,
+                sample_rate: Some(spec.sample_rate),
+                channels: Some(spec.channels as u8),
+                ..Default::default()
+            }
         }
-        Err(_) => (None, None),
+        Err(_) => AudioMetadata::default(),
     }
 }
 
-/// Read MP3 metadata (duration in seconds, sample rate in Hz) by parsing frame headers.
-fn mp3_metadata(path: &Path) -> (Option<f64>, Option<u32>) {
-    match mp3_duration::from_path(path) {
-        Ok(duration) => {
-            let secs = duration.as_secs_f64();
-            if secs <= 0.0 {
-                (None, None)
-            } else {
-                // Sample rate is not exposed by mp3-duration; default to None.
-                (Some(secs), None)
-            }
+/// Environment variable that, when set, overrides the `ffprobe` binary used
+/// by [`ffprobe_metadata`]. Useful for tests and non-standard installs,
+/// mirroring `config::CONFIG_ENV_VAR`.
+const FFPROBE_PATH_ENV_VAR: &str = "LYRE_FFPROBE_PATH";
+
+/// Locate the `ffprobe` binary: `LYRE_FFPROBE_PATH` takes precedence, then a
+/// search of `PATH`. Returns `None` if neither turns up an executable.
+pub(crate) fn resolve_ffprobe_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(FFPROBE_PATH_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("ffprobe"))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Shell out to `ffprobe` for duration/sample rate/channels. Used as a
+/// last-resort fallback when `audio_metadata`'s native parsers can't
+/// determine a file's duration (exotic containers, VBR MP3 with no Xing
+/// header, etc.) -- see `audio_metadata_with_ffprobe_fallback`.
+pub(crate) fn ffprobe_metadata(path: &Path, ffprobe_path: &Path) -> Result<AudioMetadata, String> {
+    let output = std::process::Command::new(ffprobe_path)
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("failed to run ffprobe: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("ffprobe exited with status {}", output.status));
+    }
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("failed to parse ffprobe output: {e}"))?;
+
+    let duration = parsed
+        .pointer("/format/duration")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<f64>().ok());
+
+    let audio_stream = parsed
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .and_then(|streams| {
+            streams
+                .iter()
+                .find(|s| s.get("codec_type").and_then(|c| c.as_str()) == Some("audio"))
+        });
+    let sample_rate = audio_stream
+        .and_then(|s| s.get("sample_rate"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<u32>().ok());
+    let channels = audio_stream
+        .and_then(|s| s.get("channels"))
+        .and_then(|v| v.as_u64())
+        .map(|c| c as u8);
+
+    Ok(AudioMetadata {
+        duration,
+        sample_rate,
+        channels,
+        ..Default::default()
+    })
+}
+
+/// `audio_metadata`, with an `ffprobe` fallback for files whose duration the
+/// native parsers couldn't determine. Gated on `ffprobe_fallback_enabled`
+/// (see `AppConfig::ffprobe_fallback`) so installs without ffmpeg see no
+/// change in behavior; if the flag is on but no `ffprobe` binary is found,
+/// this logs a warning and returns the native result unchanged rather than
+/// failing the upload.
+pub(crate) fn audio_metadata_with_ffprobe_fallback(
+    path: &Path,
+    format: &str,
+    ffprobe_fallback_enabled: bool,
+) -> AudioMetadata {
+    let metadata = audio_metadata(path, format);
+    if metadata.duration.is_some() || !ffprobe_fallback_enabled {
+        return metadata;
+    }
+
+    let Some(ffprobe_path) = resolve_ffprobe_path() else {
+        eprintln!(
+            "ffprobe fallback requested but no ffprobe binary was found (set {FFPROBE_PATH_ENV_VAR} to override)"
+        );
+        return metadata;
+    };
+
+    match ffprobe_metadata(path, &ffprobe_path) {
+        Ok(probed) => AudioMetadata {
+            duration: metadata.duration.or(probed.duration),
+            sample_rate: metadata.sample_rate.or(probed.sample_rate),
+            channels: metadata.channels.or(probed.channels),
+            ..metadata
+        },
+        Err(e) => {
+            eprintln!("ffprobe fallback failed for {}: {e}", path.display());
+            metadata
         }
-        Err(_) => (None, None),
     }
 }
 
@@ -686,8 +1817,45 @@ fn mp3_metadata(path: &Path) -> (Option<f64>, Option<u32>) {
 mod tests {
     use super::*;
 
+    /// A `tracing_subscriber` writer that appends into a shared buffer
+    /// instead of stdout, so a test can assert on what was actually logged
+    /// rather than just that a function returned `Ok`/`Err`.
+    #[derive(Clone, Default)]
+    struct SharedVecWriter(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedVecWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for SharedVecWriter {
+        type Writer = Self;
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Run `f` under a local (non-global) `tracing` subscriber and return
+    /// everything it logged as a string, so tests can assert that a given
+    /// code path actually left a trace rather than just checking its
+    /// `Result`.
+    fn capture_logs(f: impl FnOnce()) -> String {
+        let writer = SharedVecWriter::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(writer.clone())
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let bytes = writer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
     #[test]
-    fn test_wav_metadata_valid() {
+    fn test_wav_metadata_fallback_valid() {
         let tmp = tempfile::tempdir().unwrap();
         let path = tmp.path().join("test.wav");
 
@@ -703,28 +1871,28 @@ mod tests {
         }
         writer.finalize().unwrap();
 
-        let (duration, sample_rate) = wav_metadata(&path);
-        assert_eq!(sample_rate, Some(44100));
+        let metadata = wav_metadata_fallback(&path);
+        assert_eq!(metadata.sample_rate, Some(44100));
         // 44100 samples / 44100 Hz = 1.0 second
-        assert!((duration.unwrap() - 1.0).abs() < 0.01);
+        assert!((metadata.duration.unwrap() - 1.0).abs() < 0.01);
     }
 
     #[test]
-    fn test_wav_metadata_invalid_file() {
+    fn test_wav_metadata_fallback_invalid_file() {
         let tmp = tempfile::tempdir().unwrap();
         let path = tmp.path().join("not-a-wav.wav");
         std::fs::write(&path, "not wav data").unwrap();
 
-        let (duration, sample_rate) = wav_metadata(&path);
-        assert!(duration.is_none());
-        assert!(sample_rate.is_none());
+        let metadata = wav_metadata_fallback(&path);
+        assert!(metadata.duration.is_none());
+        assert!(metadata.sample_rate.is_none());
     }
 
     #[test]
-    fn test_wav_metadata_missing_file() {
-        let (duration, sample_rate) = wav_metadata(Path::new("/nonexistent.wav"));
-        assert!(duration.is_none());
-        assert!(sample_rate.is_none());
+    fn test_wav_metadata_fallback_missing_file() {
+        let metadata = wav_metadata_fallback(Path::new("/nonexistent.wav"));
+        assert!(metadata.duration.is_none());
+        assert!(metadata.sample_rate.is_none());
     }
 
     #[test]
@@ -783,6 +1951,75 @@ mod tests {
         assert_eq!(format, "webm");
     }
 
+    #[test]
+    fn test_validate_audio_format_wav_matches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("test.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        hound::WavWriter::create(&path, spec)
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        assert!(validate_audio_format(&path, "wav").is_ok());
+    }
+
+    #[test]
+    fn test_validate_audio_format_mismatched_extension() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("fake.wav");
+        std::fs::write(&path, b"OggS\0\0\0\0\0\0\0\0").unwrap();
+
+        let mut result = None;
+        let logs = capture_logs(|| result = Some(validate_audio_format(&path, "wav")));
+        let result = result.unwrap();
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.contains("claimed 'wav'"));
+        assert!(err.contains("detected 'ogg'"));
+
+        // The mismatch should have left a readable trace behind, not just
+        // the returned error string.
+        assert!(logs.contains("audio format mismatch"));
+        assert!(logs.contains("claimed_format=\"wav\""));
+        assert!(logs.contains("detected=\"ogg\""));
+    }
+
+    #[test]
+    fn test_validate_audio_format_truncated_unknown_content() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("truncated.mp3");
+        std::fs::write(&path, b"garbage").unwrap();
+
+        let result = validate_audio_format(&path, "mp3");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("detected 'unknown'"));
+    }
+
+    #[test]
+    fn test_validate_audio_format_m4a_and_aac_share_ftyp_signature() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("test.m4a");
+        std::fs::write(&path, b"\0\0\0\x18ftypM4A \0\0\0\0").unwrap();
+
+        assert!(validate_audio_format(&path, "m4a").is_ok());
+        assert!(validate_audio_format(&path, "aac").is_ok());
+    }
+
+    #[test]
+    fn test_validate_audio_format_mp3_frame_sync() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("test.mp3");
+        std::fs::write(&path, [0xFF, 0xFB, 0x90, 0x00]).unwrap();
+
+        assert!(validate_audio_format(&path, "mp3").is_ok());
+    }
+
     /// Create a valid MP3 file with the given number of samples at 44100 Hz mono.
     fn create_test_mp3(path: &Path, num_samples: usize) {
         use std::io::Write;
@@ -819,40 +2056,6 @@ mod tests {
         file.write_all(&flush_buf).unwrap();
     }
 
-    #[test]
-    fn test_mp3_metadata_valid_file() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = tmp.path().join("test.mp3");
-        create_test_mp3(&path, 44100); // 1 second of silence
-
-        let (duration, _sample_rate) = mp3_metadata(&path);
-        // Should be approximately 1 second (allow some MP3 padding tolerance)
-        assert!(duration.is_some(), "duration should be Some");
-        assert!(
-            (duration.unwrap() - 1.0).abs() < 0.1,
-            "expected ~1.0s, got {}",
-            duration.unwrap()
-        );
-    }
-
-    #[test]
-    fn test_mp3_metadata_invalid_file() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = tmp.path().join("not-mp3.mp3");
-        std::fs::write(&path, "not mp3 data").unwrap();
-
-        let (duration, sample_rate) = mp3_metadata(&path);
-        assert!(duration.is_none());
-        assert!(sample_rate.is_none());
-    }
-
-    #[test]
-    fn test_mp3_metadata_missing_file() {
-        let (duration, sample_rate) = mp3_metadata(Path::new("/nonexistent.mp3"));
-        assert!(duration.is_none());
-        assert!(sample_rate.is_none());
-    }
-
     #[test]
     fn test_audio_metadata_wav() {
         let tmp = tempfile::tempdir().unwrap();
@@ -870,9 +2073,12 @@ mod tests {
         }
         writer.finalize().unwrap();
 
-        let (duration, sample_rate) = audio_metadata(&path, "wav");
-        assert_eq!(sample_rate, Some(44100));
-        assert!((duration.unwrap() - 1.0).abs() < 0.01);
+        let mut metadata = None;
+        let logs = capture_logs(|| metadata = Some(audio_metadata(&path, "wav")));
+        let metadata = metadata.unwrap();
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert!((metadata.duration.unwrap() - 1.0).abs() < 0.01);
+        assert!(logs.contains("probed audio properties"));
     }
 
     #[test]
@@ -881,13 +2087,59 @@ mod tests {
         let path = tmp.path().join("test.mp3");
         create_test_mp3(&path, 44100); // 1 second
 
-        let (duration, _sample_rate) = audio_metadata(&path, "mp3");
-        assert!(duration.is_some(), "mp3 duration should be Some");
+        let metadata = audio_metadata(&path, "mp3");
+        assert!(metadata.duration.is_some(), "mp3 duration should be Some");
         assert!(
-            (duration.unwrap() - 1.0).abs() < 0.1,
+            (metadata.duration.unwrap() - 1.0).abs() < 0.1,
             "expected ~1.0s, got {}",
-            duration.unwrap()
+            metadata.duration.unwrap()
         );
+        assert_eq!(metadata.channels, Some(1));
+    }
+
+    #[test]
+    fn test_audio_metadata_missing_file_falls_back_to_empty() {
+        let metadata = audio_metadata(Path::new("/nonexistent.mp3"), "mp3");
+        assert!(metadata.duration.is_none());
+        assert!(metadata.sample_rate.is_none());
+        assert!(metadata.title.is_none());
+    }
+
+    #[test]
+    fn test_audio_metadata_with_ffprobe_fallback_disabled_skips_ffprobe() {
+        // With the flag off, a file with no recoverable duration stays empty
+        // even though `resolve_ffprobe_path` might find a real binary.
+        let metadata =
+            audio_metadata_with_ffprobe_fallback(Path::new("/nonexistent.mp3"), "mp3", false);
+        assert!(metadata.duration.is_none());
+    }
+
+    #[test]
+    fn test_audio_metadata_with_ffprobe_fallback_missing_binary_degrades_gracefully() {
+        let original = std::env::var(FFPROBE_PATH_ENV_VAR).ok();
+        std::env::set_var(FFPROBE_PATH_ENV_VAR, "/nonexistent/ffprobe");
+
+        let metadata =
+            audio_metadata_with_ffprobe_fallback(Path::new("/nonexistent.mp3"), "mp3", true);
+        assert!(metadata.duration.is_none());
+
+        match original {
+            Some(v) => std::env::set_var(FFPROBE_PATH_ENV_VAR, v),
+            None => std::env::remove_var(FFPROBE_PATH_ENV_VAR),
+        }
+    }
+
+    #[test]
+    fn test_resolve_ffprobe_path_prefers_env_override() {
+        let original = std::env::var(FFPROBE_PATH_ENV_VAR).ok();
+        std::env::set_var(FFPROBE_PATH_ENV_VAR, "/custom/ffprobe");
+
+        assert_eq!(resolve_ffprobe_path(), Some(PathBuf::from("/custom/ffprobe")));
+
+        match original {
+            Some(v) => std::env::set_var(FFPROBE_PATH_ENV_VAR, v),
+            None => std::env::remove_var(FFPROBE_PATH_ENV_VAR),
+        }
     }
 
     #[test]
@@ -901,8 +2153,15 @@ mod tests {
             duration: Some(3.5),
             format: "wav".to_string(),
             sample_rate: Some(44100),
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
             folder_id: None,
             tag_ids: None,
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"id\":\"abc-123\""));
@@ -913,6 +2172,8 @@ mod tests {
         // folder_id and tag_ids should be absent when None
         assert!(!json.contains("folderId"));
         assert!(!json.contains("tagIds"));
+        assert!(!json.contains("artist"));
+        assert!(!json.contains("album"));
     }
 
     #[test]
@@ -926,8 +2187,15 @@ mod tests {
             duration: Some(3.5),
             format: "wav".to_string(),
             sample_rate: Some(44100),
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
             folder_id: Some("folder-1".to_string()),
             tag_ids: Some(vec!["tag-1".to_string(), "tag-2".to_string()]),
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"folderId\":\"folder-1\""));
@@ -945,8 +2213,15 @@ mod tests {
             duration: Some(2.0),
             format: "mp3".to_string(),
             sample_rate: Some(44100),
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
             folder_id: None,
             tag_ids: None,
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"format\":\"mp3\""));
@@ -964,8 +2239,15 @@ mod tests {
             duration: None,
             format: "wav".to_string(),
             sample_rate: None,
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
             folder_id: None,
             tag_ids: None,
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("fileSize"));
@@ -973,6 +2255,133 @@ mod tests {
         assert!(!json.contains("sampleRate"));
         assert!(!json.contains("folderId"));
         assert!(!json.contains("tagIds"));
+        assert!(!json.contains("channels"));
+        assert!(!json.contains("bitrate"));
+        assert!(!json.contains("artist"));
+        assert!(!json.contains("album"));
+    }
+
+    #[test]
+    fn test_create_recording_request_with_tags_and_properties() {
+        let req = CreateRecordingRequest {
+            id: "abc-123".to_string(),
+            title: "Interview".to_string(),
+            file_name: "test.m4a".to_string(),
+            oss_key: "uploads/user1/abc-123/test.m4a".to_string(),
+            file_size: Some(1024),
+            duration: Some(3.5),
+            format: "m4a".to_string(),
+            sample_rate: Some(48000),
+            channels: Some(2),
+            bitrate: Some(128),
+            artist: Some("Jane".to_string()),
+            album: Some("Field Recordings".to_string()),
+            folder_id: None,
+            tag_ids: None,
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"channels\":2"));
+        assert!(json.contains("\"bitrate\":128"));
+        assert!(json.contains("\"artist\":\"Jane\""));
+        assert!(json.contains("\"album\":\"Field Recordings\""));
+    }
+
+    #[test]
+    fn test_create_recording_request_with_loudness() {
+        let req = CreateRecordingRequest {
+            id: "abc-123".to_string(),
+            title: "Test Recording".to_string(),
+            file_name: "test.wav".to_string(),
+            oss_key: "uploads/user1/abc-123/test.wav".to_string(),
+            file_size: Some(1024),
+            duration: Some(3.5),
+            format: "wav".to_string(),
+            sample_rate: Some(44100),
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
+            folder_id: None,
+            tag_ids: None,
+            track_gain_db: Some(-2.5),
+            reference_lufs: Some(-18.0),
+            fingerprint: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"trackGainDb\":-2.5"));
+        assert!(json.contains("\"referenceLufs\":-18.0"));
+    }
+
+    #[test]
+    fn test_create_recording_request_with_fingerprint() {
+        let req = CreateRecordingRequest {
+            id: "abc-123".to_string(),
+            title: "Test Recording".to_string(),
+            file_name: "test.wav".to_string(),
+            oss_key: "uploads/user1/abc-123/test.wav".to_string(),
+            file_size: Some(1024),
+            duration: Some(3.5),
+            format: "wav".to_string(),
+            sample_rate: Some(44100),
+            channels: None,
+            bitrate: None,
+            artist: None,
+            album: None,
+            folder_id: None,
+            tag_ids: None,
+            track_gain_db: None,
+            reference_lufs: None,
+            fingerprint: Some("AQAAAA==".to_string()),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("\"fingerprint\":\"AQAAAA==\""));
+    }
+
+    #[test]
+    fn test_fingerprint_base64_roundtrips_little_endian_u32s() {
+        use base64::Engine;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("test.wav");
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..44100_u32 {
+            let t = i as f64 / 44100.0;
+            let sample = (1000.0 * (2.0 * std::f64::consts::PI * 440.0 * t).sin()) as i16;
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let Some(encoded) = fingerprint_base64(&path) else {
+            // A tiny synthetic WAV may not carry enough audio for
+            // rusty_chromaprint to emit frames -- that's fine, the
+            // helper degrading to None rather than panicking is the
+            // behavior under test.
+            return;
+        };
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .unwrap();
+        assert_eq!(decoded.len() % 4, 0);
+        let roundtripped: Vec<u32> = decoded
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let expected = crate::recordings::recording_fingerprint(&path).unwrap();
+        assert_eq!(roundtripped, expected);
+    }
+
+    #[test]
+    fn test_fingerprint_base64_missing_file_is_none() {
+        assert!(fingerprint_base64(Path::new("/nonexistent/file.wav")).is_none());
     }
 
     #[test]
@@ -996,17 +2405,57 @@ mod tests {
         assert!(opts.title.is_none());
         assert!(opts.folder_id.is_none());
         assert!(opts.tag_ids.is_none());
+        assert!(!opts.chunked);
+    }
+
+    #[test]
+    fn test_checkpoint_path_appends_suffix() {
+        let path = checkpoint_path(Path::new("/tmp/recording.wav"));
+        assert_eq!(
+            path,
+            PathBuf::from("/tmp/recording.wav.upload-checkpoint.json")
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("test.wav.upload-checkpoint.json");
+
+        assert!(load_checkpoint(&path).is_none());
+
+        let checkpoint = UploadCheckpoint {
+            recording_id: "rec-1".to_string(),
+            oss_key: "uploads/rec-1/test.wav".to_string(),
+            upload_id: "oss-upload-1".to_string(),
+            part_size: MULTIPART_PART_SIZE,
+            completed_parts: vec![CompletedPart {
+                part_number: 1,
+                etag: "etag-1".to_string(),
+            }],
+        };
+        save_checkpoint(&path, &checkpoint).unwrap();
+
+        let loaded = load_checkpoint(&path).unwrap();
+        assert_eq!(loaded.recording_id, "rec-1");
+        assert_eq!(loaded.completed_parts.len(), 1);
+        assert_eq!(loaded.completed_parts[0].etag, "etag-1");
+
+        delete_checkpoint(&path);
+        assert!(load_checkpoint(&path).is_none());
     }
 
     #[test]
     fn test_upload_progress_serialization() {
         let progress = UploadProgress {
+            upload_id: "upload-1".to_string(),
             phase: "uploading".to_string(),
             bytes_sent: 1024,
             bytes_total: 4096,
             error: None,
         };
         let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"uploadId\":\"upload-1\""));
         assert!(json.contains("\"phase\":\"uploading\""));
         assert!(json.contains("\"bytesSent\":1024"));
         assert!(json.contains("\"bytesTotal\":4096"));
@@ -1016,6 +2465,7 @@ mod tests {
     #[test]
     fn test_upload_progress_with_error() {
         let progress = UploadProgress {
+            upload_id: "upload-1".to_string(),
             phase: "error".to_string(),
             bytes_sent: 0,
             bytes_total: 4096,
@@ -1026,13 +2476,33 @@ mod tests {
     }
 
     #[test]
-    fn test_cancel_flag() {
-        reset_cancel();
-        assert!(!is_cancelled());
-        cancel_upload();
-        assert!(is_cancelled());
-        reset_cancel();
-        assert!(!is_cancelled());
+    fn test_cancel_upload_targets_only_its_own_handle() {
+        let token_a = register_upload("upload-a");
+        let token_b = register_upload("upload-b");
+
+        cancel_upload("upload-a");
+
+        assert!(token_a.is_cancelled());
+        assert!(!token_b.is_cancelled());
+
+        unregister_upload("upload-a");
+        unregister_upload("upload-b");
+    }
+
+    #[test]
+    fn test_cancel_upload_unknown_handle_is_a_no_op() {
+        // Should not panic even though nothing is registered under this handle.
+        cancel_upload("no-such-upload");
+    }
+
+    #[test]
+    fn test_unregister_upload_drops_stale_token() {
+        let token = register_upload("upload-c");
+        unregister_upload("upload-c");
+
+        cancel_upload("upload-c");
+
+        assert!(!token.is_cancelled());
     }
 
     #[test]
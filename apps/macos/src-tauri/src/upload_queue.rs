@@ -0,0 +1,660 @@
+//! Persistent background upload queue with automatic retry and backoff.
+//!
+//! Unlike `upload::upload_recording_with_progress`, which is a single
+//! in-flight future that fails outright on a transient network drop, this
+//! queue persists pending uploads to disk (file path, title, folder/tag IDs,
+//! and the already-issued `recording_id`/`oss_key` once presign succeeds)
+//! and drains them in `drain_queue`. Each step retries with a capped
+//! exponential backoff before the entry is abandoned. Nothing drains the
+//! queue on a timer: entries are processed only when `retry_now` runs.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::http_client::normalize_url;
+use crate::upload::{self, UploadOptions, UploadProgress};
+
+const QUEUE_FILE: &str = "upload-queue.json";
+
+/// Environment variable that, when set, overrides the default queue file
+/// path. Useful for tests, mirroring `config::CONFIG_ENV_VAR`.
+const QUEUE_ENV_VAR: &str = "LYRE_UPLOAD_QUEUE";
+
+const STATUS_FILE: &str = "upload-status.json";
+
+/// Environment variable that, when set, overrides the default status file
+/// path. Useful for tests, mirroring `QUEUE_ENV_VAR`.
+const STATUS_ENV_VAR: &str = "LYRE_UPLOAD_STATUS";
+
+/// Upload lifecycle for a single file, queryable alongside
+/// `recordings::list_recordings` so the UI can show a per-recording badge
+/// without threading upload state through `RecordingInfo` itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadState {
+    Pending,
+    Uploading,
+    Done,
+    Failed,
+}
+
+/// Ceiling on retry attempts per queued upload before it is abandoned.
+const CLAIM_ATTEMPT_LIMIT: u32 = 5;
+
+/// Base delay for exponential backoff between retries (2s, 4s, 8s, ...).
+const BACKOFF_BASE: Duration = Duration::from_secs(2);
+
+/// A single queued upload, persisted across restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpload {
+    /// Locally-generated id used to track this entry in the queue.
+    pub id: String,
+    pub file_path: String,
+    pub title: Option<String>,
+    pub folder_id: Option<String>,
+    pub tag_ids: Option<Vec<String>>,
+    /// Set once presign succeeds, so a retry after a crash doesn't re-presign.
+    pub recording_id: Option<String>,
+    pub oss_key: Option<String>,
+    pub upload_url: Option<String>,
+    /// Mirrors `UploadOptions::chunked` -- when set, the OSS upload step
+    /// uses the resumable multipart path (`upload::chunked_upload_to_oss`)
+    /// instead of a single PUT, so a retry resumes from the first part
+    /// that hasn't completed rather than re-uploading the whole file.
+    #[serde(default)]
+    pub chunked: bool,
+    /// Number of attempts made so far across all steps.
+    pub attempt: u32,
+}
+
+fn queue_path() -> Result<PathBuf, String> {
+    if let Ok(override_path) = std::env::var(QUEUE_ENV_VAR) {
+        return Ok(PathBuf::from(override_path));
+    }
+    let data_dir = dirs::data_dir().ok_or("could not determine app data directory")?;
+    Ok(data_dir.join("com.lyre.app").join(QUEUE_FILE))
+}
+
+fn load_queue() -> Vec<PendingUpload> {
+    let Ok(path) = queue_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save_queue(queue: &[PendingUpload]) -> Result<(), String> {
+    let path = queue_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create queue directory: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(queue)
+        .map_err(|e| format!("failed to serialize queue: {e}"))?;
+    std::fs::write(&path, content).map_err(|e| format!("failed to write queue: {e}"))
+}
+
+fn status_path() -> Result<PathBuf, String> {
+    if let Ok(override_path) = std::env::var(STATUS_ENV_VAR) {
+        return Ok(PathBuf::from(override_path));
+    }
+    let data_dir = dirs::data_dir().ok_or("could not determine app data directory")?;
+    Ok(data_dir.join("com.lyre.app").join(STATUS_FILE))
+}
+
+fn load_status_map() -> std::collections::HashMap<String, UploadState> {
+    let Ok(path) = status_path() else {
+        return std::collections::HashMap::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return std::collections::HashMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save_status_map(map: &std::collections::HashMap<String, UploadState>) -> Result<(), String> {
+    let path = status_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create status directory: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(map)
+        .map_err(|e| format!("failed to serialize upload status: {e}"))?;
+    std::fs::write(&path, content).map_err(|e| format!("failed to write upload status: {e}"))
+}
+
+/// Record the last-known terminal (or pending) state for `file_path`. Called
+/// at each step transition in `enqueue_upload`/`drain_queue` -- the live
+/// "Uploading" state is never persisted here since it's derived dynamically
+/// from `upload::is_upload_active` instead.
+fn set_status(file_path: &str, state: UploadState) {
+    let mut map = load_status_map();
+    map.insert(file_path.to_string(), state);
+    let _ = save_status_map(&map);
+}
+
+/// Look up the upload lifecycle state for `file_path`, queryable alongside
+/// `recordings::list_recordings` to show a per-recording upload badge.
+/// Returns `None` if the file was never enqueued for upload.
+pub fn upload_state_for(file_path: &str) -> Option<UploadState> {
+    let queue = load_queue();
+    if let Some(entry) = queue.iter().find(|e| e.file_path == file_path) {
+        if upload::is_upload_active(&entry.id) {
+            return Some(UploadState::Uploading);
+        }
+    }
+    load_status_map().get(file_path).copied()
+}
+
+/// Add an upload to the durable queue. Returns the new entry's id.
+/// Does not start uploading — pending entries are processed only when
+/// `drain_queue` runs (through `retry_now`); no background task drains the
+/// queue on its own.
+pub fn enqueue_upload(options: UploadOptions) -> Result<String, String> {
+    let file_stem = Path::new(&options.file_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "upload".to_string());
+    let id = format!("{}-{file_stem}", chrono::Local::now().timestamp_millis());
+
+    let mut queue = load_queue();
+    queue.push(PendingUpload {
+        id: id.clone(),
+        file_path: options.file_path.clone(),
+        title: options.title,
+        folder_id: options.folder_id,
+        tag_ids: options.tag_ids,
+        recording_id: None,
+        oss_key: None,
+        upload_url: None,
+        chunked: options.chunked,
+        attempt: 0,
+    });
+    save_queue(&queue)?;
+    set_status(&options.file_path, UploadState::Pending);
+    Ok(id)
+}
+
+/// List all uploads currently pending in the queue.
+pub fn list_pending() -> Vec<PendingUpload> {
+    load_queue()
+}
+
+/// Drain the queue once, attempting every pending entry in order.
+/// Successful entries are removed; entries that exhaust `CLAIM_ATTEMPT_LIMIT`
+/// are dropped and logged. Called on demand via `retry_now`; nothing calls
+/// it on a timer.
+pub async fn drain_queue(app: &tauri::AppHandle) {
+    let mut queue = load_queue();
+    let mut remaining = Vec::with_capacity(queue.len());
+
+    for mut entry in queue.drain(..) {
+        // The entry's own id doubles as its upload handle, so `cancel_upload`
+        // can target a specific queued upload the same way it targets one
+        // started via `upload_recording_with_progress`.
+        let token = upload::register_upload(&entry.id);
+        let result = process_entry(app, &mut entry, &token).await;
+        upload::unregister_upload(&entry.id);
+
+        match result {
+            Ok(()) => {
+                upload::emit_progress(
+                    app,
+                    &UploadProgress {
+                        upload_id: entry.id.clone(),
+                        phase: "completed".to_string(),
+                        bytes_sent: 0,
+                        bytes_total: 0,
+                        error: None,
+                    },
+                );
+                set_status(&entry.file_path, UploadState::Done);
+            }
+            Err(e) => {
+                if entry.attempt >= CLAIM_ATTEMPT_LIMIT {
+                    eprintln!(
+                        "giving up on queued upload {} after {} attempts: {e}",
+                        entry.id, entry.attempt
+                    );
+                    set_status(&entry.file_path, UploadState::Failed);
+                } else {
+                    remaining.push(entry);
+                }
+            }
+        }
+    }
+
+    let _ = save_queue(&remaining);
+}
+
+/// Drain the queue now. This is the only entry point that processes queued
+/// uploads; there is no scheduled worker.
+pub async fn retry_now(app: &tauri::AppHandle) {
+    drain_queue(app).await;
+}
+
+/// Run each step of the upload for one queued entry, retrying transient
+/// failures with capped exponential backoff before surfacing the error to
+/// the caller (which re-queues the entry for the next drain).
+async fn process_entry(
+    app: &tauri::AppHandle,
+    entry: &mut PendingUpload,
+    token: &tokio_util::sync::CancellationToken,
+) -> Result<(), String> {
+    let config = crate::config::load_config()?;
+    if config.server_url.is_empty() || config.token.is_empty() {
+        return Err("server URL and token must be configured first".to_string());
+    }
+
+    let file_path = PathBuf::from(&entry.file_path);
+    if !file_path.exists() {
+        return Err(format!("file not found: {}", entry.file_path));
+    }
+
+    let file_name = file_path
+        .file_name()
+        .ok_or("invalid file path")?
+        .to_string_lossy()
+        .into_owned();
+    let (content_type, format) = upload::detect_audio_format(&file_path)?;
+    upload::validate_audio_format(&file_path, &format)?;
+    let file_size = std::fs::metadata(&file_path)
+        .map_err(|e| format!("failed to stat file: {e}"))?
+        .len();
+    let metadata =
+        upload::audio_metadata_with_ffprobe_fallback(&file_path, &format, config.ffprobe_fallback);
+    let loudness = crate::loudness::analyze_loudness(&file_path).ok();
+    let fingerprint = upload::fingerprint_base64(&file_path);
+    let title = entry
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| metadata.title.clone())
+        .unwrap_or_else(|| {
+            file_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file_name.clone())
+        });
+
+    let base_url = normalize_url(&config.server_url);
+    let client = upload::build_client(&config.token)?;
+    let folder_id = entry.folder_id.clone();
+    let tag_ids = match entry.tag_ids.clone() {
+        Some(ids) if !ids.is_empty() => Some(ids),
+        _ => {
+            let resolved = upload::resolve_tag_ids_from_metadata(&client, &base_url, &metadata).await;
+            (!resolved.is_empty()).then_some(resolved)
+        }
+    };
+
+    let (recording_id, oss_key) = if entry.chunked {
+        // Chunked uploads resume from their own on-disk checkpoint (keyed by
+        // the source file path), so there's nothing to stash on `entry`
+        // itself between attempts -- a retry just calls back in and picks up
+        // the first missing part.
+        let mut attempt = entry.attempt;
+        let result = with_retry(app, &entry.id, &mut attempt, || {
+            upload::chunked_upload_to_oss(
+                app,
+                &client,
+                &base_url,
+                &file_path,
+                &file_name,
+                &content_type,
+                file_size,
+                &entry.id,
+                token,
+            )
+        })
+        .await;
+        entry.attempt = attempt;
+        result?
+    } else {
+        // --- Presign (skipped on retry if a previous attempt already succeeded) ---
+        if entry.recording_id.is_none() || entry.oss_key.is_none() || entry.upload_url.is_none() {
+            let mut attempt = entry.attempt;
+            let presign = with_retry(app, &entry.id, &mut attempt, || {
+                upload::presign(&client, &base_url, &file_name, &content_type)
+            })
+            .await;
+            entry.attempt = attempt;
+            let presign = presign?;
+            entry.recording_id = Some(presign.recording_id);
+            entry.oss_key = Some(presign.oss_key);
+            entry.upload_url = Some(presign.upload_url);
+            let _ = save_queue_entry(entry);
+        }
+
+        let upload_url = entry.upload_url.clone().ok_or("missing upload URL")?;
+        let oss_key = entry.oss_key.clone().ok_or("missing OSS key")?;
+        let recording_id = entry.recording_id.clone().ok_or("missing recording id")?;
+
+        // --- Upload bytes to OSS ---
+        let mut attempt = entry.attempt;
+        let result = with_retry(app, &entry.id, &mut attempt, || {
+            upload::upload_to_oss_with_progress(
+                app,
+                &upload_url,
+                &file_path,
+                &content_type,
+                file_size,
+                &entry.id,
+                token,
+            )
+        })
+        .await;
+        entry.attempt = attempt;
+        result?;
+
+        (recording_id, oss_key)
+    };
+
+    // --- Create recording record ---
+    let mut attempt = entry.attempt;
+    let result = with_retry(app, &entry.id, &mut attempt, || {
+        upload::create_recording(
+            &client,
+            &base_url,
+            &recording_id,
+            &title,
+            &file_name,
+            &oss_key,
+            file_size,
+            &metadata,
+            &format,
+            folder_id.clone(),
+            tag_ids.clone(),
+            loudness,
+            fingerprint.clone(),
+        )
+    })
+    .await;
+    entry.attempt = attempt;
+    result?;
+
+    Ok(())
+}
+
+/// Persist a single entry's progress (e.g. a successful presign) so a crash
+/// mid-upload doesn't lose it.
+fn save_queue_entry(entry: &PendingUpload) -> Result<(), String> {
+    let mut queue = load_queue();
+    if let Some(existing) = queue.iter_mut().find(|e| e.id == entry.id) {
+        *existing = entry.clone();
+    } else {
+        queue.push(entry.clone());
+    }
+    save_queue(&queue)
+}
+
+/// Retry `f` with capped exponential backoff, bumping `*attempt` on every
+/// failure and emitting a `"retrying"` progress event in between.
+async fn with_retry<T, F, Fut>(
+    app: &tauri::AppHandle,
+    handle: &str,
+    attempt: &mut u32,
+    mut f: F,
+) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>>,
+{
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                *attempt += 1;
+                if *attempt >= CLAIM_ATTEMPT_LIMIT {
+                    return Err(e);
+                }
+                upload::emit_progress(
+                    app,
+                    &UploadProgress {
+                        upload_id: handle.to_string(),
+                        phase: "retrying".to_string(),
+                        bytes_sent: 0,
+                        bytes_total: 0,
+                        error: Some(e),
+                    },
+                );
+                let backoff = BACKOFF_BASE * 2u32.pow(*attempt - 1);
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn with_temp_queue<F: FnOnce()>(f: F) {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("upload-queue.json");
+        let previous = env::var(QUEUE_ENV_VAR).ok();
+        env::set_var(QUEUE_ENV_VAR, &path);
+        f();
+        match previous {
+            Some(v) => env::set_var(QUEUE_ENV_VAR, v),
+            None => env::remove_var(QUEUE_ENV_VAR),
+        }
+    }
+
+    /// Like `with_temp_queue`, but also isolates the upload-status file, for
+    /// tests exercising `upload_state_for`.
+    fn with_temp_queue_and_status<F: FnOnce()>(f: F) {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue_path = tmp.path().join("upload-queue.json");
+        let status_path = tmp.path().join("upload-status.json");
+        let previous_queue = env::var(QUEUE_ENV_VAR).ok();
+        let previous_status = env::var(STATUS_ENV_VAR).ok();
+        env::set_var(QUEUE_ENV_VAR, &queue_path);
+        env::set_var(STATUS_ENV_VAR, &status_path);
+        f();
+        match previous_queue {
+            Some(v) => env::set_var(QUEUE_ENV_VAR, v),
+            None => env::remove_var(QUEUE_ENV_VAR),
+        }
+        match previous_status {
+            Some(v) => env::set_var(STATUS_ENV_VAR, v),
+            None => env::remove_var(STATUS_ENV_VAR),
+        }
+    }
+
+    #[test]
+    fn test_enqueue_then_list_pending() {
+        with_temp_queue(|| {
+            let id = enqueue_upload(UploadOptions {
+                file_path: "/tmp/test.mp3".to_string(),
+                title: Some("My Recording".to_string()),
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+
+            let pending = list_pending();
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].id, id);
+            assert_eq!(pending[0].file_path, "/tmp/test.mp3");
+            assert_eq!(pending[0].title, Some("My Recording".to_string()));
+            assert_eq!(pending[0].attempt, 0);
+            assert!(pending[0].recording_id.is_none());
+        });
+    }
+
+    #[test]
+    fn test_enqueue_preserves_chunked_flag() {
+        with_temp_queue(|| {
+            enqueue_upload(UploadOptions {
+                file_path: "/tmp/test.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: true,
+            })
+            .unwrap();
+
+            let pending = list_pending();
+            assert!(pending[0].chunked);
+        });
+    }
+
+    #[test]
+    fn test_list_pending_empty_when_no_queue_file() {
+        with_temp_queue(|| {
+            assert!(list_pending().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_enqueue_appends_to_existing_queue() {
+        with_temp_queue(|| {
+            enqueue_upload(UploadOptions {
+                file_path: "/tmp/a.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+            enqueue_upload(UploadOptions {
+                file_path: "/tmp/b.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+
+            let pending = list_pending();
+            assert_eq!(pending.len(), 2);
+            assert_eq!(pending[0].file_path, "/tmp/a.mp3");
+            assert_eq!(pending[1].file_path, "/tmp/b.mp3");
+        });
+    }
+
+    #[test]
+    fn test_save_queue_entry_updates_existing_by_id() {
+        with_temp_queue(|| {
+            let id = enqueue_upload(UploadOptions {
+                file_path: "/tmp/a.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+
+            let mut entry = list_pending().into_iter().next().unwrap();
+            entry.recording_id = Some("rec-1".to_string());
+            entry.attempt = 2;
+            save_queue_entry(&entry).unwrap();
+
+            let pending = list_pending();
+            assert_eq!(pending.len(), 1);
+            assert_eq!(pending[0].id, id);
+            assert_eq!(pending[0].recording_id, Some("rec-1".to_string()));
+            assert_eq!(pending[0].attempt, 2);
+        });
+    }
+
+    #[test]
+    fn test_pending_upload_serialization_round_trip() {
+        let entry = PendingUpload {
+            id: "abc".to_string(),
+            file_path: "/tmp/test.wav".to_string(),
+            title: Some("Title".to_string()),
+            folder_id: Some("folder-1".to_string()),
+            tag_ids: Some(vec!["tag-1".to_string()]),
+            recording_id: None,
+            oss_key: None,
+            upload_url: None,
+            chunked: false,
+            attempt: 0,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        let parsed: PendingUpload = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.id, "abc");
+        assert_eq!(parsed.folder_id, Some("folder-1".to_string()));
+        assert_eq!(parsed.tag_ids, Some(vec!["tag-1".to_string()]));
+    }
+
+    #[test]
+    fn test_upload_state_unknown_file_is_none() {
+        with_temp_queue_and_status(|| {
+            assert_eq!(upload_state_for("/tmp/never-queued.mp3"), None);
+        });
+    }
+
+    #[test]
+    fn test_upload_state_pending_after_enqueue() {
+        with_temp_queue_and_status(|| {
+            enqueue_upload(UploadOptions {
+                file_path: "/tmp/a.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+
+            assert_eq!(upload_state_for("/tmp/a.mp3"), Some(UploadState::Pending));
+        });
+    }
+
+    #[test]
+    fn test_upload_state_done_after_set_status() {
+        with_temp_queue_and_status(|| {
+            set_status("/tmp/a.mp3", UploadState::Done);
+            assert_eq!(upload_state_for("/tmp/a.mp3"), Some(UploadState::Done));
+        });
+    }
+
+    #[test]
+    fn test_upload_state_failed_after_set_status() {
+        with_temp_queue_and_status(|| {
+            set_status("/tmp/a.mp3", UploadState::Failed);
+            assert_eq!(upload_state_for("/tmp/a.mp3"), Some(UploadState::Failed));
+        });
+    }
+
+    #[test]
+    fn test_upload_state_uploading_while_active() {
+        with_temp_queue_and_status(|| {
+            let id = enqueue_upload(UploadOptions {
+                file_path: "/tmp/a.mp3".to_string(),
+                title: None,
+                folder_id: None,
+                tag_ids: None,
+                quality_preset: Default::default(),
+                upload_id: None,
+                chunked: false,
+            })
+            .unwrap();
+
+            let _token = upload::register_upload(&id);
+            assert_eq!(upload_state_for("/tmp/a.mp3"), Some(UploadState::Uploading));
+            upload::unregister_upload(&id);
+            assert_eq!(upload_state_for("/tmp/a.mp3"), Some(UploadState::Pending));
+        });
+    }
+}
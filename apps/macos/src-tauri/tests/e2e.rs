@@ -16,7 +16,7 @@ use tempfile::TempDir;
 
 use lyre::{
     batch_delete_recordings, delete_recording, find_cleanable_recordings, list_recordings,
-    AudioDeviceManager, CleanupFilter, RecorderConfig, RecorderState,
+    restore_recording, AudioDeviceManager, CleanupFilter, RecorderConfig, RecorderState,
 };
 
 // ============================================================================
@@ -247,7 +247,7 @@ fn e2e_record_then_list_then_delete() {
     );
 
     // Delete the recording
-    delete_recording(&rec.path, &output_dir).unwrap();
+    delete_recording(&rec.path, &output_dir, false).unwrap();
 
     // Verify it's gone
     let recordings_after = list_recordings(&output_dir).unwrap();
@@ -527,7 +527,7 @@ fn e2e_delete_recording_success() {
     create_test_wav(&wav_path);
     assert!(wav_path.exists());
 
-    delete_recording(wav_path.to_str().unwrap(), dir).unwrap();
+    delete_recording(wav_path.to_str().unwrap(), dir, false).unwrap();
     assert!(!wav_path.exists(), "file should be deleted");
 
     // List should be empty
@@ -546,7 +546,7 @@ fn e2e_delete_recording_path_traversal_blocked() {
     assert!(outside_file.exists());
 
     // Attempt to delete it via the recordings API — should be rejected
-    let result = delete_recording(outside_file.to_str().unwrap(), output_dir.path());
+    let result = delete_recording(outside_file.to_str().unwrap(), output_dir.path(), false);
     assert!(result.is_err(), "should reject path outside output dir");
     assert!(
         result.unwrap_err().contains("outside"),
@@ -558,11 +558,31 @@ fn e2e_delete_recording_path_traversal_blocked() {
     );
 }
 
+#[test]
+fn e2e_delete_recording_soft_then_restore() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir = tmp_dir.path();
+
+    let wav_path = dir.join("to-trash.wav");
+    create_test_wav(&wav_path);
+
+    delete_recording(wav_path.to_str().unwrap(), dir, true).unwrap();
+    assert!(!wav_path.exists(), "file should be moved out of the output dir");
+    assert!(
+        list_recordings(dir).unwrap().is_empty(),
+        "trashed file should not be listed"
+    );
+
+    restore_recording("to-trash.wav", dir).unwrap();
+    assert!(wav_path.exists(), "restore should bring the file back");
+    assert_eq!(list_recordings(dir).unwrap().len(), 1);
+}
+
 #[test]
 fn e2e_delete_recording_nonexistent_file() {
     let tmp_dir = TempDir::new().unwrap();
     let ghost = tmp_dir.path().join("ghost.wav");
-    let result = delete_recording(ghost.to_str().unwrap(), tmp_dir.path());
+    let result = delete_recording(ghost.to_str().unwrap(), tmp_dir.path(), false);
     assert!(result.is_err(), "should fail for nonexistent file");
 }
 
@@ -584,7 +604,7 @@ fn e2e_batch_delete_all_succeed() {
         })
         .collect();
 
-    let result = batch_delete_recordings(&files, dir);
+    let result = batch_delete_recordings(&files, dir, false);
     assert_eq!(result.deleted_count, 3);
     assert!(result.freed_bytes > 0);
     assert!(result.errors.is_empty());
@@ -606,7 +626,7 @@ fn e2e_batch_delete_partial_failure() {
         existing.to_string_lossy().into_owned(),
         dir.join("ghost.wav").to_string_lossy().into_owned(),
     ];
-    let result = batch_delete_recordings(&paths, dir);
+    let result = batch_delete_recordings(&paths, dir, false);
 
     assert_eq!(result.deleted_count, 1, "1 should succeed");
     assert_eq!(result.errors.len(), 1, "1 should fail");
@@ -616,7 +636,7 @@ fn e2e_batch_delete_partial_failure() {
 #[test]
 fn e2e_batch_delete_empty_list() {
     let tmp_dir = TempDir::new().unwrap();
-    let result = batch_delete_recordings(&[], tmp_dir.path());
+    let result = batch_delete_recordings(&[], tmp_dir.path(), false);
     assert_eq!(result.deleted_count, 0);
     assert_eq!(result.freed_bytes, 0);
     assert!(result.errors.is_empty());
@@ -720,7 +740,7 @@ fn e2e_cleanup_then_batch_delete_lifecycle() {
 
     // Batch delete them
     let paths: Vec<String> = to_delete.iter().map(|r| r.path.clone()).collect();
-    let result = batch_delete_recordings(&paths, dir);
+    let result = batch_delete_recordings(&paths, dir, false);
     assert_eq!(result.deleted_count, 3);
     assert!(result.errors.is_empty());
 